@@ -17,6 +17,15 @@ pub enum Error {
     #[error("i18n: received invalid language code '{0}'")]
     UnknownLanguageCode(String),
 
+    #[error("i18n: invalid message format: {0}")]
+    MessageFormat(String),
+
+    #[error("i18n: PO parse error at line {0}: {1}")]
+    PoParse(usize, String),
+
+    #[error("i18n: store function is not configured")]
+    StoreFn,
+
     #[error("i18n: unable to get storage path")]
     StoragePath,
 
@@ -0,0 +1,690 @@
+//!
+//! Extraction and validation of translatable strings (native platforms only).
+//!
+//! [`Catalog`] scans Rust sources for `i18n("...")`, `i18n_args("...", ..)`,
+//! `i18n_format("...", ..)` and `i18n_ctx("context", "...")` call sites
+//! and produces the default language catalog. Method calls (such as
+//! `I18N.i18n("...")` on a [`Namespace`](crate::namespace::Namespace))
+//! are skipped as namespaced strings belong to the catalog registered
+//! by the namespace rather than to the application dictionary. The catalog
+//! can be checked against the `i18n.json` data file, reporting unused and
+//! untranslated strings for each enabled language as well as translations
+//! whose placeholders (`{name}`) do not match the source string. Strings
+//! supplied to [`MESSAGE_FUNCTIONS`] are additionally validated as ICU-style
+//! messages (see [`format`](crate::format)). This allows
+//! broken translations to be detected during CI, for example using a test:
+//!
+//! ```ignore
+//! #[test]
+//! fn translations() -> workflow_i18n::result::Result<()> {
+//!     let mut catalog = Catalog::new();
+//!     catalog.scan_dir("src")?;
+//!     let report = catalog.check("en", include_str!("../i18n.json"))?;
+//!     assert!(!report.has_errors(), "{report}");
+//!     Ok(())
+//! }
+//! ```
+//!
+
+use crate::format::Message;
+use crate::i18n::{context_key, split_context_key, Data};
+use crate::po::{PoEntry, PoFile};
+use crate::result::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Functions whose first (string literal) argument is extracted.
+pub const FUNCTIONS: &[&str] = &["i18n", "i18n_args", "i18n_format"];
+
+/// Functions whose first argument is an ICU-style message
+/// (see [`format`](crate::format)).
+pub const MESSAGE_FUNCTIONS: &[&str] = &["i18n_format"];
+
+/// Functions whose first two (string literal) arguments are
+/// extracted as the message context and the source string.
+pub const CONTEXT_FUNCTIONS: &[&str] = &["i18n_ctx"];
+
+/// Location of a call site.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)
+    }
+}
+
+/// Catalog of strings extracted from the source code.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    entries: BTreeMap<String, Vec<Location>>,
+    messages: BTreeSet<String>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extracted strings and their call site locations.
+    pub fn entries(&self) -> &BTreeMap<String, Vec<Location>> {
+        &self.entries
+    }
+
+    /// Returns true if the string is used as an ICU-style message
+    /// (supplied to one of the [`MESSAGE_FUNCTIONS`]).
+    pub fn is_message(&self, key: &str) -> bool {
+        self.messages.contains(key)
+    }
+
+    /// Extract strings from the supplied source code.
+    pub fn scan_source(&mut self, file: impl AsRef<Path>, source: &str) {
+        for (text, line, is_message) in extract(source) {
+            if is_message {
+                self.messages.insert(text.clone());
+            }
+            self.entries.entry(text).or_default().push(Location {
+                file: file.as_ref().to_path_buf(),
+                line,
+            });
+        }
+    }
+
+    /// Extract strings from the supplied source file.
+    pub fn scan_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let source = std::fs::read_to_string(path.as_ref())?;
+        self.scan_source(path, &source);
+        Ok(())
+    }
+
+    /// Extract strings from all `.rs` files in the supplied folder
+    /// (recursively, skipping `target` and hidden folders).
+    pub fn scan_dir(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let mut paths = std::fs::read_dir(path.as_ref())?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.sort();
+
+        for path in paths {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            if path.is_dir() {
+                if name != "target" && !name.starts_with('.') {
+                    self.scan_dir(&path)?;
+                }
+            } else if name.ends_with(".rs") {
+                self.scan_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Default language catalog in the format produced by
+    /// [`export_default_language`](crate::i18n::export_default_language).
+    pub fn to_json(&self) -> Result<String> {
+        let map = self
+            .entries
+            .keys()
+            .map(|key| (key.clone(), serde_json::Value::String(key.clone())))
+            .collect::<serde_json::Map<_, _>>();
+        Ok(crate::json::to_json(&serde_json::Value::Object(map)))
+    }
+
+    /// Default language catalog as a POT template including source references.
+    pub fn to_pot(&self) -> PoFile {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(key, locations)| {
+                let (context, msgid) = split_context_key(key);
+                PoEntry {
+                    references: locations
+                        .iter()
+                        .map(|location| location.to_string())
+                        .collect(),
+                    context: context.map(String::from),
+                    msgid: msgid.to_string(),
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        PoFile {
+            header: vec![(
+                "Content-Type".to_string(),
+                "text/plain; charset=UTF-8".to_string(),
+            )],
+            entries,
+        }
+    }
+
+    /// Check translations of all enabled languages in the supplied
+    /// `i18n.json` data against the extracted strings. Languages without
+    /// a translation map report all extracted strings as untranslated.
+    pub fn check(&self, default_code: &str, json_data: &str) -> Result<CheckReport> {
+        let data = serde_json::from_str::<Data>(json_data)?;
+
+        let mut codes = data.enabled.clone();
+        if !codes.contains(&default_code) {
+            codes.insert(0, default_code);
+        }
+
+        let mut report = CheckReport::default();
+        for code in codes {
+            let translations = data
+                .translations
+                .get(code)
+                .into_iter()
+                .flat_map(|translations| translations.iter())
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<BTreeMap<_, _>>();
+
+            let mut language = LanguageReport {
+                language_code: code.to_string(),
+                ..Default::default()
+            };
+
+            language.unused = translations
+                .keys()
+                .filter(|key| !self.entries.contains_key(**key))
+                .map(|key| key.to_string())
+                .collect();
+
+            for key in self.entries.keys() {
+                let Some(translated) = translations.get(key.as_str()) else {
+                    language.untranslated.push(key.clone());
+                    continue;
+                };
+
+                let (_, source) = split_context_key(key);
+                let is_message = self.is_message(key);
+                let expected = match placeholders(source, is_message) {
+                    Ok(expected) => expected,
+                    Err(err) => {
+                        language.invalid.push((key.clone(), err.to_string()));
+                        continue;
+                    }
+                };
+                match placeholders(translated, is_message) {
+                    Ok(found) if found != expected => {
+                        language.mismatched.push(Mismatch {
+                            key: key.clone(),
+                            missing: expected.difference(&found).cloned().collect(),
+                            unexpected: found.difference(&expected).cloned().collect(),
+                        });
+                    }
+                    Ok(_) => {}
+                    Err(err) => language.invalid.push((key.clone(), err.to_string())),
+                }
+            }
+
+            report.languages.push(language);
+        }
+
+        Ok(report)
+    }
+}
+
+/// Placeholders of an ICU-style message or, for plain strings, the
+/// `{name}` arguments replaced by [`i18n_args`](crate::i18n::i18n_args).
+fn placeholders(text: &str, is_message: bool) -> Result<BTreeSet<String>> {
+    if is_message {
+        return Ok(Message::parse(text)?
+            .arguments()
+            .into_iter()
+            .map(String::from)
+            .collect());
+    }
+
+    let mut placeholders = BTreeSet::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let end = rest.find(['{', '}']).unwrap_or(rest.len());
+        let name = &rest[..end];
+        if rest[end..].starts_with('}')
+            && !name.is_empty()
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        {
+            placeholders.insert(name.to_string());
+        }
+        rest = &rest[end..];
+    }
+    Ok(placeholders)
+}
+
+/// Translation whose placeholders do not match the source string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mismatch {
+    pub key: String,
+    /// placeholders present in the source string but missing in the translation
+    pub missing: Vec<String>,
+    /// placeholders present in the translation but not in the source string
+    pub unexpected: Vec<String>,
+}
+
+/// Check results of a single language.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguageReport {
+    pub language_code: String,
+    /// translated strings not present in the source code
+    pub unused: Vec<String>,
+    /// strings present in the source code without translation
+    pub untranslated: Vec<String>,
+    /// translations with placeholders not matching the source string
+    pub mismatched: Vec<Mismatch>,
+    /// messages (strings supplied to [`MESSAGE_FUNCTIONS`]) or their translations
+    /// that are not valid ICU-style messages (see [`format`](crate::format))
+    pub invalid: Vec<(String, String)>,
+}
+
+/// Results of [`Catalog::check`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckReport {
+    pub languages: Vec<LanguageReport>,
+}
+
+impl CheckReport {
+    /// Returns true if any translation is broken (has mismatched
+    /// placeholders or is not a valid message).
+    pub fn has_errors(&self) -> bool {
+        self.languages
+            .iter()
+            .any(|language| !language.mismatched.is_empty() || !language.invalid.is_empty())
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let printable = |key: &str| key.replace(crate::i18n::CONTEXT_SEPARATOR, "|");
+
+        for language in self.languages.iter() {
+            writeln!(
+                f,
+                "[{}] unused: {}, untranslated: {}, mismatched: {}, invalid: {}",
+                language.language_code,
+                language.unused.len(),
+                language.untranslated.len(),
+                language.mismatched.len(),
+                language.invalid.len()
+            )?;
+            for mismatch in language.mismatched.iter() {
+                writeln!(
+                    f,
+                    "  mismatched placeholders in '{}': missing {:?}, unexpected {:?}",
+                    printable(&mismatch.key),
+                    mismatch.missing,
+                    mismatch.unexpected
+                )?;
+            }
+            for (key, err) in language.invalid.iter() {
+                writeln!(f, "  invalid message '{}': {err}", printable(key))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+}
+
+/// Returns string literals supplied as the first argument
+/// to [`FUNCTIONS`] together with their line numbers and
+/// whether they are supplied to [`MESSAGE_FUNCTIONS`].
+fn extract(source: &str) -> Vec<(String, usize, bool)> {
+    let tokens = Lexer::new(source).tokens();
+    let mut strings = Vec::new();
+
+    for (index, (token, _)) in tokens.iter().enumerate() {
+        let Token::Ident(ident) = token else {
+            continue;
+        };
+        let with_context = CONTEXT_FUNCTIONS.contains(&ident.as_str());
+        if !with_context && !FUNCTIONS.contains(&ident.as_str()) {
+            continue;
+        }
+        // skip method calls (namespaced strings)
+        if index > 0 && matches!(tokens.get(index - 1), Some((Token::Punct('.'), _))) {
+            continue;
+        }
+
+        // allow macro invocations (`i18n!("...")`)
+        let mut next = index + 1;
+        if matches!(tokens.get(next), Some((Token::Punct('!'), _))) {
+            next += 1;
+        }
+        if !matches!(tokens.get(next), Some((Token::Punct('('), _))) {
+            continue;
+        }
+
+        let mut args = Vec::new();
+        let count = if with_context { 2 } else { 1 };
+        for _ in 0..count {
+            match (tokens.get(next + 1), tokens.get(next + 2)) {
+                (Some((Token::Str(text), line)), Some((Token::Punct(',' | ')'), _))) => {
+                    args.push((text.as_str(), *line));
+                    next += 2;
+                }
+                _ => break,
+            }
+        }
+
+        let is_message = MESSAGE_FUNCTIONS.contains(&ident.as_str());
+        match args.as_slice() {
+            [(text, line)] if !with_context => strings.push((text.to_string(), *line, is_message)),
+            [(context, _), (text, line)] => {
+                strings.push((context_key(Some(context), text), *line, is_message))
+            }
+            _ => {}
+        }
+    }
+
+    strings
+}
+
+/// Minimal Rust lexer producing identifiers, string literals and
+/// punctuation, skipping comments, character literals and whitespace.
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Lexer {
+            chars: source.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn tokens(mut self) -> Vec<(Token, usize)> {
+        let mut tokens = Vec::new();
+
+        while let Some(c) = self.bump() {
+            let line = self.line;
+            match c {
+                c if c.is_whitespace() => {}
+                '/' if self.peek() == Some('/') => {
+                    while self.peek().map(|c| c != '\n').unwrap_or(false) {
+                        self.bump();
+                    }
+                }
+                '/' if self.peek() == Some('*') => self.block_comment(),
+                '"' => tokens.push((Token::Str(self.string()), line)),
+                '\'' => self.char_or_lifetime(),
+                c if c.is_alphanumeric() || c == '_' => {
+                    let mut ident = String::from(c);
+                    while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_') {
+                        ident.push(c);
+                        self.bump();
+                    }
+
+                    match (ident.as_str(), self.peek()) {
+                        ("r" | "br" | "cr", Some('"' | '#')) if self.is_raw_string() => {
+                            tokens.push((Token::Str(self.raw_string()), line))
+                        }
+                        // raw identifier (`r#match`)
+                        ("r", Some('#')) => {
+                            self.bump();
+                            let mut ident = String::new();
+                            while let Some(c) =
+                                self.peek().filter(|c| c.is_alphanumeric() || *c == '_')
+                            {
+                                ident.push(c);
+                                self.bump();
+                            }
+                            tokens.push((Token::Ident(ident), line));
+                        }
+                        ("b" | "c", Some('"')) => {
+                            self.bump();
+                            tokens.push((Token::Str(self.string()), line));
+                        }
+                        ("b", Some('\'')) => {
+                            self.bump();
+                            self.char_or_lifetime();
+                        }
+                        _ => tokens.push((Token::Ident(ident), line)),
+                    }
+                }
+                c => tokens.push((Token::Punct(c), line)),
+            }
+        }
+
+        tokens
+    }
+
+    fn block_comment(&mut self) {
+        self.bump();
+        let mut depth = 1;
+        while depth > 0 {
+            match self.bump() {
+                Some('/') if self.peek() == Some('*') => {
+                    self.bump();
+                    depth += 1;
+                }
+                Some('*') if self.peek() == Some('/') => {
+                    self.bump();
+                    depth -= 1;
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+    }
+
+    fn char_or_lifetime(&mut self) {
+        match self.bump() {
+            Some('\\') => {
+                while let Some(c) = self.bump() {
+                    if c == '\'' {
+                        break;
+                    }
+                }
+            }
+            Some(_) if self.peek() == Some('\'') => {
+                self.bump();
+            }
+            // lifetime or label
+            _ => {}
+        }
+    }
+
+    /// Read a string literal (after the opening quote) decoding escape sequences.
+    fn string(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.bump() {
+            match c {
+                '"' => break,
+                '\\' => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('0') => text.push('\0'),
+                    Some('x') => {
+                        let hex = [self.bump(), self.bump()]
+                            .into_iter()
+                            .flatten()
+                            .collect::<String>();
+                        if let Ok(code) = u8::from_str_radix(&hex, 16) {
+                            text.push(code as char);
+                        }
+                    }
+                    Some('u') => {
+                        let mut hex = String::new();
+                        while let Some(c) = self.bump() {
+                            match c {
+                                '{' => {}
+                                '}' => break,
+                                c => hex.push(c),
+                            }
+                        }
+                        if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                        {
+                            text.push(c);
+                        }
+                    }
+                    // line continuation
+                    Some('\n') => {
+                        while self.peek().map(char::is_whitespace).unwrap_or(false) {
+                            self.bump();
+                        }
+                    }
+                    Some(c) => text.push(c),
+                    None => break,
+                },
+                c => text.push(c),
+            }
+        }
+        text
+    }
+
+    /// Returns true if a raw string literal (`"`, `#"`, `##"`, ...) follows the `r` prefix.
+    fn is_raw_string(&self) -> bool {
+        let mut chars = self.chars.clone().skip_while(|c| *c == '#');
+        chars.next() == Some('"')
+    }
+
+    /// Read a raw string literal (after the `r` prefix).
+    fn raw_string(&mut self) -> String {
+        let mut hashes = 0;
+        while self.peek() == Some('#') {
+            self.bump();
+            hashes += 1;
+        }
+        if self.bump() != Some('"') {
+            return String::new();
+        }
+
+        let mut text = String::new();
+        while let Some(c) = self.bump() {
+            if c == '"' {
+                let mut closing = 0;
+                while closing < hashes && self.peek() == Some('#') {
+                    self.bump();
+                    closing += 1;
+                }
+                if closing == hashes {
+                    break;
+                }
+                text.push('"');
+                text.extend(std::iter::repeat_n('#', closing));
+            } else {
+                text.push(c);
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"
+        // i18n("commented out")
+        /* i18n("block /* nested */ comment") */
+        fn view<'a>(name: &'a str, count: usize) {
+            let quote = '"';
+            label(i18n("Settings"));
+            label(i18n("Use {} or { to open a block"));
+            label(workflow_i18n::i18n_args("Hello, {name}!", &[("name", name)]));
+            label(i18n_format(r#"{count, plural, one {# "file"} other {# "files"}}"#, &[("count", count.into())]));
+            label(i18n("Multi\
+                line"));
+            label(i18n(&dynamic));
+            label(i18n_ctx("button", "Open"));
+            button(I18N.i18n_ctx("button", "Namespaced"));
+            let r#type = i18n("Type");
+        }
+    "##;
+
+    #[test]
+    fn test_extract() -> Result<()> {
+        let mut catalog = Catalog::new();
+        catalog.scan_source("src/view.rs", SOURCE);
+
+        let keys = catalog
+            .entries()
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                "Hello, {name}!",
+                "Multiline",
+                "Settings",
+                "Type",
+                "Use {} or { to open a block",
+                "button\u{4}Open",
+                r#"{count, plural, one {# "file"} other {# "files"}}"#
+            ]
+        );
+        assert_eq!(
+            catalog.entries()["Settings"][0].to_string(),
+            "src/view.rs:6"
+        );
+        assert!(catalog
+            .to_pot()
+            .to_string()
+            .contains("#: src/view.rs:6\nmsgid \"Settings\""));
+
+        let json_data = r#"{
+            "enabled": ["en", "de", "fr"],
+            "aliases": {},
+            "languages": { "en": "English", "de": "Deutsch", "fr": "Français" },
+            "translations": {
+                "en": { "Settings": "Settings", "Hello, {name}!": "Hello, {name}!", "Multiline": "Multiline" },
+                "de": { "Settings": "Einstellungen", "Hello, {name}!": "Hallo, {nmae}!", "Removed": "Entfernt", "Use {} or { to open a block": "{} oder { öffnet einen Block" }
+            }
+        }"#;
+        let report = catalog.check("en", json_data)?;
+        assert!(report.has_errors());
+
+        let en = &report.languages[0];
+        assert_eq!(en.untranslated.len(), 4);
+        assert!(en.mismatched.is_empty());
+        assert!(en.invalid.is_empty());
+        assert!(catalog.is_message(r#"{count, plural, one {# "file"} other {# "files"}}"#));
+        assert!(!catalog.is_message("Use {} or { to open a block"));
+
+        let de = &report.languages[1];
+        assert_eq!(de.unused, ["Removed"]);
+        assert_eq!(de.untranslated.len(), 4);
+        assert!(de.invalid.is_empty());
+        assert_eq!(
+            de.mismatched,
+            [Mismatch {
+                key: "Hello, {name}!".to_string(),
+                missing: vec!["name".to_string()],
+                unexpected: vec!["nmae".to_string()],
+            }]
+        );
+
+        let fr = &report.languages[2];
+        assert_eq!(fr.untranslated.len(), catalog.entries().len());
+        Ok(())
+    }
+}
@@ -0,0 +1,561 @@
+//!
+//! ICU-style message formatting.
+//!
+//! Messages use a subset of the [ICU MessageFormat](https://unicode-org.github.io/icu/userguide/format_parse/messages/)
+//! syntax, allowing translations to express plural forms, select
+//! (e.g. gender) variants and locale-aware number formatting:
+//!
+//! - `{name}` - argument value (numbers are formatted using the language conventions)
+//! - `{count, number}` or `{count, number, integer}` - number formatted using the language conventions
+//! - `{count, plural, =0 {no files} one {# file} other {# files}}` - plural
+//!   variant selected by exact value or by the CLDR plural category of the
+//!   language (`zero`, `one`, `two`, `few`, `many`, `other`). `#` is replaced
+//!   by the formatted number; `offset:N` subtracts `N` from the number.
+//! - `{gender, select, female {she} male {he} other {they}}` - variant
+//!   selected by the argument value.
+//!
+//! Literal braces can be quoted using apostrophes (`'{'`), a double
+//! apostrophe produces a single apostrophe and any other apostrophe is
+//! treated literally. Since messages are regular strings, they are stored
+//! in the `i18n.json` data file the same way as plain translations, with
+//! each language supplying the plural variants it requires:
+//!
+//! ```text
+//! "en": { "{count, plural, one {# file} other {# files}}": "{count, plural, one {# file} other {# files}}" }
+//! "pl": { "{count, plural, one {# file} other {# files}}": "{count, plural, one {# plik} few {# pliki} many {# plików} other {# pliku}}" }
+//! ```
+//!
+//! See [`i18n_format()`](crate::i18n::i18n_format).
+//!
+
+use crate::error::Error;
+use crate::number::{format_integer, format_number};
+use crate::plural::{plural_category, PluralCategory};
+use crate::result::Result;
+
+/// Message argument value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arg<'a> {
+    Str(&'a str),
+    Int(i64),
+    Float(f64),
+}
+
+impl<'a> From<&'a str> for Arg<'a> {
+    fn from(value: &'a str) -> Self {
+        Arg::Str(value)
+    }
+}
+
+impl<'a> From<&'a String> for Arg<'a> {
+    fn from(value: &'a String) -> Self {
+        Arg::Str(value.as_str())
+    }
+}
+
+macro_rules! impl_from {
+    ($variant:ident, $as:ty, $($type:ty),*) => {
+        $(
+            impl From<$type> for Arg<'_> {
+                fn from(value: $type) -> Self {
+                    Arg::$variant(value as $as)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(Int, i64, i8, i16, i32, i64, isize, u8, u16, u32);
+impl_from!(Float, f64, f32, f64);
+
+macro_rules! impl_from_saturating {
+    ($($type:ty),*) => {
+        $(
+            /// Values exceeding [`i64::MAX`] saturate to [`i64::MAX`].
+            impl From<$type> for Arg<'_> {
+                fn from(value: $type) -> Self {
+                    Arg::Int(i64::try_from(value).unwrap_or(i64::MAX))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_saturating!(u64, usize);
+
+impl Arg<'_> {
+    fn format(&self, language_code: &str, integer: bool) -> String {
+        match self {
+            Arg::Str(value) => value.to_string(),
+            Arg::Int(value) => format_integer(language_code, *value),
+            Arg::Float(value) if integer => format_integer(language_code, value.round() as i64),
+            Arg::Float(value) => format_number(language_code, *value),
+        }
+    }
+
+    fn as_number(&self) -> Option<Arg<'static>> {
+        match self {
+            Arg::Str(value) => {
+                let value = value.trim();
+                value
+                    .parse::<i64>()
+                    .map(Arg::Int)
+                    .or_else(|_| value.parse::<f64>().map(Arg::Float))
+                    .ok()
+            }
+            Arg::Int(value) => Some(Arg::Int(*value)),
+            Arg::Float(value) => Some(Arg::Float(*value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PluralKey {
+    Exact(f64),
+    Category(PluralCategory),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Arg(String),
+    Number {
+        name: String,
+        integer: bool,
+    },
+    Plural {
+        name: String,
+        offset: i64,
+        cases: Vec<(PluralKey, Vec<Part>)>,
+    },
+    Select {
+        name: String,
+        cases: Vec<(String, Vec<Part>)>,
+    },
+    /// `#` within a plural variant
+    Hash,
+}
+
+/// Parsed message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    parts: Vec<Part>,
+}
+
+impl Message {
+    pub fn parse(text: &str) -> Result<Message> {
+        let mut parser = Parser { text, pos: 0 };
+        let parts = parser.parse_message(false, false)?;
+        Ok(Message { parts })
+    }
+
+    /// Names of the arguments referenced by the message.
+    pub fn arguments(&self) -> Vec<&str> {
+        fn collect<'a>(parts: &'a [Part], names: &mut Vec<&'a str>) {
+            for part in parts {
+                let name = match part {
+                    Part::Arg(name) | Part::Number { name, .. } => name,
+                    Part::Plural { name, cases, .. } => {
+                        cases.iter().for_each(|(_, parts)| collect(parts, names));
+                        name
+                    }
+                    Part::Select { name, cases } => {
+                        cases.iter().for_each(|(_, parts)| collect(parts, names));
+                        name
+                    }
+                    Part::Text(_) | Part::Hash => continue,
+                };
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+
+        let mut names = Vec::new();
+        collect(&self.parts, &mut names);
+        names
+    }
+
+    /// Format the message for the supplied language. Arguments
+    /// missing from `args` are output as `{name}` placeholders.
+    pub fn format(&self, language_code: &str, args: &[(&str, Arg<'_>)]) -> String {
+        let mut out = String::new();
+        write_parts(&mut out, &self.parts, language_code, args, None);
+        out
+    }
+}
+
+fn write_parts(
+    out: &mut String,
+    parts: &[Part],
+    language_code: &str,
+    args: &[(&str, Arg<'_>)],
+    hash: Option<Arg<'_>>,
+) {
+    let arg = |name: &str| {
+        args.iter()
+            .find_map(|(key, value)| (*key == name).then_some(*value))
+    };
+    let placeholder = |out: &mut String, name: &str| {
+        out.push('{');
+        out.push_str(name);
+        out.push('}');
+    };
+
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Arg(name) => match arg(name) {
+                Some(value) => out.push_str(&value.format(language_code, false)),
+                None => placeholder(out, name),
+            },
+            Part::Number { name, integer } => match arg(name) {
+                Some(value) => out.push_str(&value.format(language_code, *integer)),
+                None => placeholder(out, name),
+            },
+            Part::Hash => match hash {
+                Some(value) => out.push_str(&value.format(language_code, false)),
+                None => out.push('#'),
+            },
+            Part::Plural {
+                name,
+                offset,
+                cases,
+            } => {
+                let Some(value) = arg(name) else {
+                    placeholder(out, name);
+                    continue;
+                };
+
+                // exact keys match the value before applying the offset
+                let (exact, number, category) = match value.as_number() {
+                    Some(Arg::Int(value)) => (
+                        Some(value as f64),
+                        Some(Arg::Int(value - offset)),
+                        plural_category(language_code, value - offset),
+                    ),
+                    Some(Arg::Float(value)) => (
+                        Some(value),
+                        Some(Arg::Float(value - *offset as f64)),
+                        plural_category(language_code, value - *offset as f64),
+                    ),
+                    _ => (None, None, PluralCategory::Other),
+                };
+
+                let variant = cases
+                    .iter()
+                    .find(
+                        |(key, _)| matches!(key, PluralKey::Exact(value) if Some(*value) == exact),
+                    )
+                    .or_else(|| {
+                        cases
+                            .iter()
+                            .find(|(key, _)| *key == PluralKey::Category(category))
+                    })
+                    .or_else(|| {
+                        cases
+                            .iter()
+                            .find(|(key, _)| *key == PluralKey::Category(PluralCategory::Other))
+                    });
+
+                if let Some((_, parts)) = variant {
+                    write_parts(out, parts, language_code, args, number.or(Some(value)));
+                }
+            }
+            Part::Select { name, cases } => {
+                let Some(value) = arg(name) else {
+                    placeholder(out, name);
+                    continue;
+                };
+
+                let key = value.format(language_code, false);
+                let variant = cases
+                    .iter()
+                    .find(|(case, _)| *case == key)
+                    .or_else(|| cases.iter().find(|(case, _)| case == "other"));
+                if let Some((_, parts)) = variant {
+                    write_parts(out, parts, language_code, args, hash);
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        Error::MessageFormat(format!("{message} at {} in '{}'", self.pos, self.text))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map(char::is_whitespace).unwrap_or(false) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("expected '{expected}'"))),
+        }
+    }
+
+    /// Read a name, keyword or a variant key.
+    fn word(&mut self) -> &str {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .peek()
+            .map(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | ','))
+            .unwrap_or(false)
+        {
+            self.bump();
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn parse_message(&mut self, in_plural: bool, nested: bool) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            match self.peek() {
+                None if nested => return Err(self.error("unterminated variant")),
+                None => break,
+                Some('}') if nested => break,
+                Some('}') => return Err(self.error("unmatched '}'")),
+                Some('{') => {
+                    self.bump();
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.parse_argument(in_plural)?);
+                }
+                Some('#') if in_plural => {
+                    self.bump();
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Hash);
+                }
+                Some('\'') => {
+                    self.bump();
+                    match self.peek() {
+                        Some('\'') => {
+                            self.bump();
+                            text.push('\'');
+                        }
+                        Some('{' | '}') => self.quoted(&mut text),
+                        Some('#') if in_plural => self.quoted(&mut text),
+                        _ => text.push('\''),
+                    }
+                }
+                Some(c) => {
+                    self.bump();
+                    text.push(c);
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// Read quoted text up to the closing apostrophe (or the end of the message).
+    fn quoted(&mut self, text: &mut String) {
+        while let Some(c) = self.bump() {
+            if c == '\'' {
+                if self.peek() == Some('\'') {
+                    self.bump();
+                } else {
+                    break;
+                }
+            }
+            text.push(c);
+        }
+    }
+
+    fn parse_argument(&mut self, in_plural: bool) -> Result<Part> {
+        let name = self.word().to_string();
+        if name.is_empty() {
+            return Err(self.error("missing argument name"));
+        }
+
+        self.skip_whitespace();
+        match self.bump() {
+            Some('}') => return Ok(Part::Arg(name)),
+            Some(',') => {}
+            _ => return Err(self.error("expected ',' or '}'")),
+        }
+
+        match self.word() {
+            "number" => {
+                self.skip_whitespace();
+                let integer = match self.bump() {
+                    Some('}') => {
+                        return Ok(Part::Number {
+                            name,
+                            integer: false,
+                        })
+                    }
+                    Some(',') => match self.word() {
+                        "integer" => true,
+                        style => {
+                            let style = style.to_string();
+                            return Err(self.error(&format!("unsupported number style '{style}'")));
+                        }
+                    },
+                    _ => return Err(self.error("expected ',' or '}'")),
+                };
+                self.expect('}')?;
+                Ok(Part::Number { name, integer })
+            }
+            "plural" => {
+                self.expect(',')?;
+                self.skip_whitespace();
+                let mut offset = 0;
+                if self.text[self.pos..].starts_with("offset:") {
+                    self.pos += "offset:".len();
+                    offset = self
+                        .word()
+                        .parse::<i64>()
+                        .map_err(|_| self.error("invalid plural offset"))?;
+                }
+
+                let cases = self
+                    .parse_cases(true)?
+                    .into_iter()
+                    .map(|(key, parts)| {
+                        let key = match key.strip_prefix('=') {
+                            Some(value) => {
+                                PluralKey::Exact(value.parse::<f64>().map_err(|_| {
+                                    self.error(&format!("invalid plural key '{key}'"))
+                                })?)
+                            }
+                            None => PluralKey::Category(key.parse()?),
+                        };
+                        Ok((key, parts))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                if !cases
+                    .iter()
+                    .any(|(key, _)| *key == PluralKey::Category(PluralCategory::Other))
+                {
+                    return Err(self.error("missing 'other' plural variant"));
+                }
+                Ok(Part::Plural {
+                    name,
+                    offset,
+                    cases,
+                })
+            }
+            "select" => {
+                self.expect(',')?;
+                let cases = self.parse_cases(in_plural)?;
+                if !cases.iter().any(|(key, _)| key == "other") {
+                    return Err(self.error("missing 'other' select variant"));
+                }
+                Ok(Part::Select { name, cases })
+            }
+            kind => {
+                let kind = kind.to_string();
+                Err(self.error(&format!("unsupported argument type '{kind}'")))
+            }
+        }
+    }
+
+    fn parse_cases(&mut self, in_plural: bool) -> Result<Vec<(String, Vec<Part>)>> {
+        let mut cases = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') {
+                self.bump();
+                break;
+            }
+
+            let key = self.word().to_string();
+            if key.is_empty() {
+                return Err(self.error("missing variant key"));
+            }
+            self.expect('{')?;
+            let parts = self.parse_message(in_plural, true)?;
+            self.expect('}')?;
+            cases.push((key, parts));
+        }
+        Ok(cases)
+    }
+}
+
+/// Format a message for the supplied language without translation.
+pub fn format_message(language_code: &str, text: &str, args: &[(&str, Arg<'_>)]) -> Result<String> {
+    Ok(Message::parse(text)?.format(language_code, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_format() -> Result<()> {
+        let files = "{count, plural, =0 {no files} one {# file} other {# files}}";
+        assert_eq!(
+            format_message("en", files, &[("count", 0.into())])?,
+            "no files"
+        );
+        assert_eq!(
+            format_message("en", files, &[("count", 1.into())])?,
+            "1 file"
+        );
+        assert_eq!(
+            format_message("en", files, &[("count", 1200.into())])?,
+            "1,200 files"
+        );
+
+        let pl = "{count, plural, one {# plik} few {# pliki} many {# plików} other {# pliku}}";
+        let pl = |count: Arg<'_>| format_message("pl", pl, &[("count", count)]);
+        assert_eq!(pl(2.into())?, "2 pliki");
+        assert_eq!(pl(5.into())?, "5 plików");
+        assert_eq!(pl(1.5.into())?, "1,5 pliku");
+
+        let invite = "{host} invited {guest} to {gender, select, female {her} male {his} other {their}} party \
+            {guests, plural, offset:1 =0 {alone} one {with one other guest} other {with # other guests}}. It's '{'fun'}'!";
+        let message = Message::parse(invite)?;
+        assert_eq!(message.arguments(), ["host", "guest", "gender", "guests"]);
+        assert_eq!(
+            message.format(
+                "en",
+                &[
+                    ("host", "Alice".into()),
+                    ("gender", "female".into()),
+                    ("guests", 3.into())
+                ]
+            ),
+            "Alice invited {guest} to her party with 2 other guests. It's {fun}!"
+        );
+
+        assert_eq!(Arg::from(u64::MAX), Arg::Int(i64::MAX));
+
+        assert!(Message::parse("{count, plural, one {# file}}").is_err());
+        assert!(Message::parse("{count, plural, other {# files}").is_err());
+        assert!(Message::parse("unmatched }").is_err());
+        Ok(())
+    }
+}
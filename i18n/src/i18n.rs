@@ -1,8 +1,10 @@
 use crate::error::Error;
+use crate::format::{Arg, Message};
 use crate::result::Result;
 use arc_swap::*;
 use ritehash::FxHasher64;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasherDefault;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, MutexGuard};
@@ -10,12 +12,57 @@ pub type FxBuildHasher = BuildHasherDefault<FxHasher64>;
 pub type FxHashMap<K, V> = HashMap<K, V, FxBuildHasher>;
 
 static mut JSON_DATA: Option<String> = None;
-static mut JSON_DATA_GUARD: Option<Mutex<()>> = None;
+static JSON_DATA_GUARD: Mutex<()> = Mutex::new(());
 static DICTIONARY: ArcSwapOption<Dictionary> = ArcSwapOption::const_empty();
+static OWNED_TEXT: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
 
 pub type StoreFn = dyn Send + Sync + Fn(&str) -> Result<()> + 'static;
 pub type DictionaryArgs<'a> = Vec<(&'static str, &'a str)>;
 
+/// Separator between the message context and the source string
+/// in dictionary keys (following the gettext `msgctxt` convention).
+pub const CONTEXT_SEPARATOR: char = '\u{4}';
+
+/// Create a dictionary key for a source string with an optional message context.
+pub fn context_key(context: Option<&str>, text: &str) -> String {
+    match context {
+        Some(context) => format!("{context}{CONTEXT_SEPARATOR}{text}"),
+        None => text.to_string(),
+    }
+}
+
+// a tiny accelerator to save a heap alloc when looking up context keys;
+// the context is the concatenation of the supplied parts
+#[inline(always)]
+pub(crate) fn with_context_key<R>(context: &[&str], text: &str, f: impl FnOnce(&str) -> R) -> R {
+    if context.is_empty() {
+        return f(text);
+    }
+
+    let mut separator = [0u8; 4];
+    let separator: &str = CONTEXT_SEPARATOR.encode_utf8(&mut separator);
+    let mut buffer = [0u8; 256];
+    let len = context.iter().map(|part| part.len()).sum::<usize>() + separator.len() + text.len();
+    if len > buffer.len() {
+        return f(&context_key(Some(&context.concat()), text));
+    }
+
+    let mut written = 0;
+    for part in context.iter().chain([&separator, &text]) {
+        buffer[written..written + part.len()].copy_from_slice(part.as_bytes());
+        written += part.len();
+    }
+    f(std::str::from_utf8(&buffer[..written]).unwrap())
+}
+
+/// Split a dictionary key into the message context and the source string.
+pub fn split_context_key(key: &str) -> (Option<&str>, &str) {
+    match key.split_once(CONTEXT_SEPARATOR) {
+        Some((context, text)) => (Some(context), text),
+        None => (None, key),
+    }
+}
+
 pub struct Builder {
     current_code: String,
     default_code: String,
@@ -135,15 +182,25 @@ pub fn import_translation_files<P: AsRef<Path>>(source_folder_path: P, reload: b
 
     let translations = dictionary
         .translations
-        .clone()
-        .into_iter()
+        .iter()
+        .map(|(code, translation)| {
+            (
+                *code,
+                Arc::new(
+                    translation
+                        .iter()
+                        .map(|(k, v)| (Text::from(*k), Text::from(*v)))
+                        .collect(),
+                ),
+            )
+        })
         .chain(merged_translations.iter().map(|(code, translation)| {
             (
                 code.as_str(),
                 Arc::new(
                     translation
                         .iter()
-                        .map(|(k, v)| (k.as_str(), v.as_str()))
+                        .map(|(k, v)| (Text::from(k.as_str()), Text::from(v.as_str())))
                         .collect(),
                 ),
             )
@@ -155,6 +212,7 @@ pub fn import_translation_files<P: AsRef<Path>>(source_folder_path: P, reload: b
         aliases: dictionary.aliases.clone(),
         languages: dictionary.languages.clone(),
         translations,
+        info: dictionary.info.clone(),
     };
 
     let json = serde_json::to_value(&data)?;
@@ -200,7 +258,7 @@ pub fn dictionary() -> Arc<Dictionary> {
 }
 
 pub fn guard() -> MutexGuard<'static, ()> {
-    unsafe { JSON_DATA_GUARD.as_ref().unwrap().lock().unwrap() }
+    JSON_DATA_GUARD.lock().unwrap()
 }
 
 pub fn load(json_data_file: impl AsRef<Path>) -> Result<()> {
@@ -243,31 +301,25 @@ pub fn i18n(text: &str) -> &str {
     match dictionary.translate(text) {
         Some(translated) => translated,
         None => {
-            let needs_store = {
-                let mut missing = dictionary.missing.lock().unwrap();
-                if !missing.contains_key(text) {
-                    missing.insert(text.to_string(), text.to_string());
-                    true
-                } else {
-                    false
-                }
-            };
-
-            if needs_store {
-                if let Some(store_fn) = dictionary.store_fn() {
-                    match dictionary.to_json() {
-                        Ok(json_data) => {
-                            if let Err(err) = store_fn(json_data.as_str()) {
-                                println!("i18n error: {}", err);
-                            }
-                        }
-                        Err(err) => {
-                            println!("i18n error: {}", err);
-                        }
-                    }
-                }
-            }
+            dictionary.insert_missing(text, text);
+            text
+        }
+    }
+}
+
+/// Translate a string qualified by a message context (such as `"button"`
+/// or `"status"`) to the currently user-selected language, allowing the
+/// same source string to have different translations in different contexts.
+pub fn i18n_ctx<'a>(context: &str, text: &'a str) -> &'a str {
+    #[cfg(feature = "thread-safe")]
+    let _guard = guard();
+
+    let dictionary = dictionary();
 
+    match dictionary.translate_ctx(context, text) {
+        Some(translated) => translated,
+        None => {
+            with_context_key(&[context], text, |key| dictionary.insert_missing(key, text));
             text
         }
     }
@@ -299,12 +351,23 @@ where
 /// Parameter 'replacements' is a vector consisting of key value pairs,
 /// where the key is the placeholder within 'text'.
 pub fn i18n_args<'a, K, V>(text: &str, replacements: impl IntoIterator<Item = &'a (K, V)>) -> String
+where
+    K: AsRef<str> + 'a,
+    V: AsRef<str> + 'a,
+{
+    replace_args(i18n(text), replacements)
+}
+
+pub(crate) fn replace_args<'a, K, V>(
+    translated: &str,
+    replacements: impl IntoIterator<Item = &'a (K, V)>,
+) -> String
 where
     K: AsRef<str> + 'a,
     V: AsRef<str> + 'a,
 {
     let mut buffer = [0u8; 64];
-    let mut translated = String::from(i18n(text));
+    let mut translated = String::from(translated);
 
     for (key, value) in replacements {
         let placeholder = make_arg(&mut buffer, key);
@@ -314,10 +377,38 @@ where
     translated
 }
 
+/// Translate an ICU-style message (see [`format`](crate::format)) to the
+/// currently user-selected language and format it using the supplied
+/// arguments, selecting plural variants and formatting numbers according
+/// to the rules of the current language. If the translated message can
+/// not be parsed, the translated text is returned as-is.
+pub fn i18n_format(text: &str, args: &[(&str, Arg<'_>)]) -> String {
+    format_translated(i18n(text), args)
+}
+
+pub(crate) fn format_translated(translated: &str, args: &[(&str, Arg<'_>)]) -> String {
+    match Message::parse(translated) {
+        Ok(message) => message.format(dictionary().current_code().as_str(), args),
+        Err(err) => {
+            println!("i18n error: {}", err);
+            translated.to_string()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    pub fn test_i18n_format() {
+        Builder::new("en", "en").try_init().unwrap();
+
+        let text = "{name} has {count, plural, one {# new message} other {# new messages}}";
+        let formatted = i18n_format(text, &[("name", "John".into()), ("count", 1200.into())]);
+        assert_eq!(formatted, "John has 1,200 new messages");
+    }
+
     #[test]
     pub fn test_i18n_args() {
         Builder::new("en", "en").try_init().unwrap();
@@ -328,6 +419,47 @@ mod tests {
         // println!("{translated}");
         assert_eq!(translated, "Hello, John!");
     }
+
+    #[test]
+    pub fn test_translate_ctx() -> Result<()> {
+        let json_data = r#"{
+            "enabled": ["en", "de"],
+            "aliases": {},
+            "languages": { "en": "English", "de": "Deutsch" },
+            "translations": {
+                "en": { "Open": "Open" },
+                "de": { "Open": "Offen", "button\u0004Open": "Öffnen" }
+            }
+        }"#;
+        let dictionary = Dictionary::try_new("de", "en", Some(json_data), None)?;
+
+        assert_eq!(dictionary.translate("Open"), Some("Offen"));
+        assert_eq!(dictionary.translate_ctx("button", "Open"), Some("Öffnen"));
+        assert_eq!(dictionary.translate_ctx("status", "Open"), None);
+
+        dictionary.insert_missing(&context_key(Some("status"), "Open"), "Open");
+        let storable = Storable::from(&dictionary);
+        assert_eq!(storable.translations["en"]["status\u{4}Open"], "Open");
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_reload_reuses_owned_text() -> Result<()> {
+        let json_data = r#"{
+            "enabled": ["en"],
+            "aliases": {},
+            "languages": { "en": "English" },
+            "translations": { "en": { "Say \"hi\"": "Say \"hi\"" } }
+        }"#;
+        let first = Dictionary::try_new("en", "en", Some(json_data), None)?;
+        let second = Dictionary::try_new("en", "en", Some(json_data), None)?;
+
+        let first = first.translate("Say \"hi\"").unwrap();
+        let second = second.translate("Say \"hi\"").unwrap();
+        assert_eq!(first, "Say \"hi\"");
+        assert!(std::ptr::eq(first, second));
+        Ok(())
+    }
 }
 
 /// Dictionary structure containing all translations and related data.
@@ -337,9 +469,11 @@ pub struct Dictionary {
     /// list of aliases ("en-GB": "en"), ("en-US": "en"), ("zh-CN": "zh_HANS"), ("zh-TW": "zh_HANT") etc.
     aliases: FxHashMap<&'static str, &'static str>,
     /// Map of translations {"ja": { "Hello" : "こんにちは" }}
-    translations: FxHashMap<&'static str, Arc<FxHashMap<&'static str, &'static str>>>,
+    pub(crate) translations: FxHashMap<&'static str, Arc<FxHashMap<&'static str, &'static str>>>,
+    /// Translator metadata {"ja": { "Hello" : { "comments": [...] } }}
+    pub(crate) info: FxHashMap<String, FxHashMap<String, EntryInfo>>,
     /// Missing translation entries (default language)
-    missing: Mutex<FxHashMap<String, String>>,
+    pub(crate) missing: Mutex<FxHashMap<String, String>>,
     /// Enabled language codes ["en", "ja"]
     enabled: Vec<&'static str>,
     /// Current language code
@@ -360,7 +494,7 @@ pub struct Dictionary {
 
 impl Dictionary {
     /// Create a new dictionary from JSON data. JSON data must be `&'static str`, i.e. loaded by the application via the `include_str!()` macro.
-    fn try_new(
+    pub(crate) fn try_new(
         current_code: impl Into<String>,
         default_code: impl Into<String>,
         json_data: Option<&'static str>,
@@ -372,12 +506,24 @@ impl Dictionary {
             languages,
             aliases,
             translations,
+            info,
         } = if let Some(json_data) = json_data {
             serde_json::from_str::<Data>(json_data)?
         } else {
             Data::default()
         };
 
+        let translations: FxHashMap<_, _> = translations
+            .into_iter()
+            .map(|(code, translation)| {
+                let translation = translation
+                    .iter()
+                    .map(|(k, v)| (k.clone().into_static(), v.clone().into_static()))
+                    .collect::<FxHashMap<_, _>>();
+                (code, Arc::new(translation))
+            })
+            .collect();
+
         let current_code: String = current_code.into();
         let current_code = aliases
             .get(current_code.as_str())
@@ -414,6 +560,7 @@ impl Dictionary {
             languages,
             aliases,
             translations,
+            info,
             missing: Mutex::new(FxHashMap::default()),
             enabled,
             current_code: ArcSwap::new(Arc::new(current_code)),
@@ -426,7 +573,7 @@ impl Dictionary {
     }
 
     /// Resolve a language code or a language alias to a language code.
-    fn resolve_aliases(&self, maybe_alias: impl Into<String>) -> Result<String> {
+    pub(crate) fn resolve_aliases(&self, maybe_alias: impl Into<String>) -> Result<String> {
         let maybe_alias = maybe_alias.into();
         match self.aliases.get(maybe_alias.as_str()) {
             Some(code) => Ok(code.to_string()),
@@ -470,6 +617,65 @@ impl Dictionary {
         current_translations.get(text).copied()
     }
 
+    /// Translate a string qualified by a message context.
+    #[inline(always)]
+    pub fn translate_ctx(&self, context: &str, text: &str) -> Option<&'static str> {
+        with_context_key(&[context], text, |key| self.translate(key))
+    }
+
+    /// Translate a string (optionally qualified by a message context)
+    /// using the catalog registered by the supplied namespace
+    /// (see [`Namespace`](crate::namespace::Namespace)), falling back
+    /// to the application dictionary (the string qualified by the
+    /// namespace context first, then the string itself).
+    pub fn translate_ns(
+        &self,
+        namespace: &str,
+        context: Option<&str>,
+        text: &str,
+    ) -> Option<&'static str> {
+        let current_code = self.current_code();
+        let context = context.as_slice();
+        with_context_key(context, text, |key| {
+            crate::namespace::translate(namespace, current_code.as_str(), key)
+        })
+        .or_else(|| {
+            crate::namespace::with_namespace_key(namespace, context.first().copied(), text, |key| {
+                self.translate(key)
+            })
+        })
+        .or_else(|| with_context_key(context, text, |key| self.translate(key)))
+    }
+
+    /// Record a string missing in the default language
+    /// and store the updated data if it was not seen before.
+    pub(crate) fn insert_missing(&self, key: &str, text: &str) {
+        let needs_store = {
+            let mut missing = self.missing.lock().unwrap();
+            if !missing.contains_key(key) {
+                missing.insert(key.to_string(), text.to_string());
+                true
+            } else {
+                false
+            }
+        };
+
+        if needs_store {
+            if let Some(store_fn) = self.store_fn() {
+                match self.to_json() {
+                    Ok(json_data) => {
+                        if let Err(err) = store_fn(json_data.as_str()) {
+                            println!("i18n error: {}", err);
+                        }
+                    }
+                    Err(err) => {
+                        println!("i18n error: {}", err);
+                    }
+                }
+            }
+        }
+    }
+
     #[inline(always)]
     pub fn default_translations(&self) -> &Arc<FxHashMap<&'static str, &'static str>> {
         &self.default_translations
@@ -516,11 +722,7 @@ impl Dictionary {
     }
 
     pub fn to_json(&self) -> Result<String> {
-        let data = Storable::from(self);
-        let json = serde_json::to_value(data)?;
-        let json_data = crate::json::to_json(&json);
-        Ok(json_data)
-        // Ok(serde_json::to_string_pretty(&data)?)
+        Storable::from(self).to_json()
     }
 }
 
@@ -600,14 +802,89 @@ impl Languages {
     }
 }
 
+/// Translation string borrowed from the JSON data unless
+/// it contains escape sequences (quotes, newlines etc.).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(transparent)]
+pub struct Text<'data>(Cow<'data, str>);
+
+impl Text<'static> {
+    /// Obtain a `'static` reference to the string. Strings not borrowed from
+    /// the (static) JSON data are leaked, as the dictionary lives for the
+    /// duration of the application. Leaked strings are pooled and reused
+    /// when the dictionary is reloaded, as such only new strings are leaked.
+    pub(crate) fn into_static(self) -> &'static str {
+        match self.0 {
+            Cow::Borrowed(text) => text,
+            Cow::Owned(text) => {
+                let mut pool = OWNED_TEXT.lock().unwrap();
+                let pool = pool.get_or_insert_with(HashSet::new);
+                match pool.get(text.as_str()) {
+                    Some(text) => text,
+                    None => {
+                        let text: &'static str = Box::leak(text.into_boxed_str());
+                        pool.insert(text);
+                        text
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Text<'_> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'data> From<&'data str> for Text<'data> {
+    fn from(text: &'data str) -> Self {
+        Text(Cow::Borrowed(text))
+    }
+}
+
+impl<'de: 'data, 'data> serde::Deserialize<'de> for Text<'data> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Text<'de>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E>(self, text: &'de str) -> std::result::Result<Self::Value, E> {
+                Ok(Text(Cow::Borrowed(text)))
+            }
+
+            fn visit_str<E>(self, text: &str) -> std::result::Result<Self::Value, E> {
+                Ok(Text(Cow::Owned(text.to_string())))
+            }
+
+            fn visit_string<E>(self, text: String) -> std::result::Result<Self::Value, E> {
+                Ok(Text(Cow::Owned(text)))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
 /// i18n.json data file structure.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(bound(deserialize = "'de: 'data"))]
 pub struct Data<'data> {
-    enabled: Vec<&'data str>,
+    pub(crate) enabled: Vec<&'data str>,
     aliases: FxHashMap<&'data str, &'data str>,
     languages: FxHashMap<&'data str, &'data str>,
-    translations: FxHashMap<&'data str, Arc<FxHashMap<&'data str, &'data str>>>,
+    pub(crate) translations: FxHashMap<&'data str, Arc<FxHashMap<Text<'data>, Text<'data>>>>,
+    #[serde(default, skip_serializing_if = "FxHashMap::is_empty")]
+    info: FxHashMap<String, FxHashMap<String, EntryInfo>>,
 }
 
 impl Default for Data<'_> {
@@ -633,16 +910,45 @@ impl Default for Data<'_> {
             aliases,
             languages,
             translations,
+            info: FxHashMap::default(),
         }
     }
 }
 
+/// Translator metadata of a translation entry, preserved
+/// when importing and exporting PO and XLIFF files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EntryInfo {
+    /// translator comments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<String>,
+    /// translation requiring review (not used at runtime)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuzzy: Option<String>,
+}
+
+impl EntryInfo {
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty() && self.fuzzy.is_none()
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct Storable {
     enabled: Vec<&'static str>,
     aliases: FxHashMap<&'static str, &'static str>,
     languages: FxHashMap<&'static str, &'static str>,
-    translations: FxHashMap<&'static str, FxHashMap<String, String>>,
+    pub(crate) translations: FxHashMap<&'static str, FxHashMap<String, String>>,
+    #[serde(skip_serializing_if = "FxHashMap::is_empty")]
+    pub(crate) info: FxHashMap<String, FxHashMap<String, EntryInfo>>,
+}
+
+impl Storable {
+    /// Serialize into the `i18n.json` data format.
+    pub fn to_json(&self) -> Result<String> {
+        let json = serde_json::to_value(self)?;
+        Ok(crate::json::to_json(&json))
+    }
 }
 
 impl From<&Dictionary> for Storable {
@@ -651,6 +957,7 @@ impl From<&Dictionary> for Storable {
             languages,
             aliases,
             translations,
+            info,
             missing,
             default_code,
             enabled,
@@ -682,6 +989,7 @@ impl From<&Dictionary> for Storable {
             aliases: aliases.clone(),
             languages: languages.clone(),
             translations: translations.clone(),
+            info: info.clone(),
         }
     }
 }
@@ -8,13 +8,43 @@
 //!
 //! i18n is a performance-oriented library for internationalization and translation embedding into Rust applications.
 //!
+//! In addition to plain string translation using [`i18n()`] and [`i18n_args()`],
+//! [`i18n_format()`] supports ICU-style messages with plural forms (selected
+//! using CLDR plural rules of the current language), select variants and
+//! locale-aware number formatting (see the [`format`] module).
+//!
+//! Dictionaries can be exported to and imported from gettext PO files
+//! (see the [`po`] module) and exported as XLIFF 1.2 (see the [`xliff`] module)
+//! for use with translation tools.
+//!
+//! The same source string can be translated differently depending on its
+//! message context using [`i18n_ctx()`] (e.g. `i18n_ctx("button", "Open")`),
+//! while libraries can ship their own translation catalogs registered under
+//! a [`Namespace`](namespace::Namespace) (see the [`namespace`] module).
+//!
+//! On native platforms, the [`extract`] module scans crate sources for
+//! translatable strings, producing the default language catalog and
+//! validating translations (unused, untranslated and mismatched placeholders).
+//!
 pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod extract;
+pub mod format;
 pub mod i18n;
 pub mod json;
+pub mod namespace;
+pub mod number;
+pub mod plural;
+pub mod po;
 pub mod result;
+pub mod xliff;
 
+pub use format::Arg;
 pub use i18n::i18n;
 pub use i18n::i18n_args;
+pub use i18n::i18n_ctx;
+pub use i18n::i18n_format;
+pub use namespace::Namespace;
 
 pub mod prelude {
     pub use crate::i18n;
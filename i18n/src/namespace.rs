@@ -0,0 +1,256 @@
+//!
+//! Per-crate translation namespaces.
+//!
+//! Libraries (such as shared UI crates) can ship their own translation
+//! catalogs and register them under a unique namespace, keeping their
+//! strings separate from the application dictionary and from other
+//! libraries. A namespace catalog contains translations keyed by the
+//! language code:
+//!
+//! ```json
+//! {
+//!     "de": { "Cancel": "Abbrechen", "button\u0004Open": "Öffnen" },
+//!     "fr": { "Cancel": "Annuler", "button\u0004Open": "Ouvrir" }
+//! }
+//! ```
+//!
+//! Namespaced strings are translated to the language currently
+//! selected in the application dictionary. Strings missing in the
+//! namespace catalog are looked up in the application dictionary under
+//! the namespace context (`workflow-ux` or `workflow-ux/button` for
+//! strings with a message context) and then as-is. Strings not found
+//! are recorded as missing under the namespace context, allowing the
+//! application to translate them using PO/XLIFF export:
+//!
+//! ```ignore
+//! static I18N: Namespace = Namespace::new("workflow-ux");
+//!
+//! I18N.register(include_str!("../i18n.json"))?;
+//! let label = I18N.i18n_ctx("button", "Open");
+//! ```
+//!
+
+use crate::format::Arg;
+use crate::i18n::{dictionary, format_translated, replace_args, with_context_key, FxHashMap, Text};
+use crate::result::Result;
+use arc_swap::ArcSwapOption;
+use std::sync::Arc;
+
+type Translations = FxHashMap<&'static str, &'static str>;
+type Catalogs = FxHashMap<&'static str, Arc<FxHashMap<String, Translations>>>;
+
+static CATALOGS: ArcSwapOption<Catalogs> = ArcSwapOption::const_empty();
+
+/// Separator between the namespace and the message context
+/// in the namespace context of application dictionary keys.
+pub const NAMESPACE_SEPARATOR: &str = "/";
+
+/// Invoke the supplied function with the application dictionary key
+/// of a namespaced string (qualified by the namespace context).
+pub(crate) fn with_namespace_key<R>(
+    namespace: &str,
+    context: Option<&str>,
+    text: &str,
+    f: impl FnOnce(&str) -> R,
+) -> R {
+    match context {
+        Some(context) => with_context_key(&[namespace, NAMESPACE_SEPARATOR, context], text, f),
+        None => with_context_key(&[namespace], text, f),
+    }
+}
+
+/// Translation lookup in the catalog registered by the supplied namespace.
+pub(crate) fn translate(namespace: &str, language_code: &str, key: &str) -> Option<&'static str> {
+    CATALOGS
+        .load()
+        .as_ref()?
+        .get(namespace)?
+        .get(language_code)?
+        .get(key)
+        .copied()
+}
+
+/// Translation namespace of a library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Namespace {
+    name: &'static str,
+}
+
+impl Namespace {
+    pub const fn new(name: &'static str) -> Self {
+        Namespace { name }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Register the translation catalog of this namespace, replacing
+    /// any previously registered catalog. JSON data must be `&'static str`,
+    /// i.e. loaded via the `include_str!()` macro.
+    pub fn register(&self, json_data: &'static str) -> Result<()> {
+        let catalog = serde_json::from_str::<FxHashMap<&str, FxHashMap<Text, Text>>>(json_data)?
+            .into_iter()
+            .map(|(code, translations)| {
+                let translations = translations
+                    .into_iter()
+                    .map(|(k, v)| (k.into_static(), v.into_static()))
+                    .collect::<Translations>();
+                (code.to_string(), translations)
+            })
+            .collect::<FxHashMap<_, _>>();
+        let catalog = Arc::new(catalog);
+
+        CATALOGS.rcu(|catalogs| {
+            let mut catalogs = catalogs.as_deref().cloned().unwrap_or_default();
+            catalogs.insert(self.name, catalog.clone());
+            Some(Arc::new(catalogs))
+        });
+
+        Ok(())
+    }
+
+    /// Remove the translation catalog of this namespace.
+    pub fn unregister(&self) {
+        CATALOGS.rcu(|catalogs| {
+            let mut catalogs = catalogs.as_deref().cloned().unwrap_or_default();
+            catalogs.remove(self.name);
+            Some(Arc::new(catalogs))
+        });
+    }
+
+    pub fn is_registered(&self) -> bool {
+        CATALOGS
+            .load()
+            .as_ref()
+            .map(|catalogs| catalogs.contains_key(self.name))
+            .unwrap_or(false)
+    }
+
+    /// Translate a string to the currently user-selected language.
+    pub fn i18n<'a>(&self, text: &'a str) -> &'a str {
+        self.translate(None, text)
+    }
+
+    /// Translate a string qualified by a message context
+    /// to the currently user-selected language.
+    pub fn i18n_ctx<'a>(&self, context: &str, text: &'a str) -> &'a str {
+        self.translate(Some(context), text)
+    }
+
+    fn translate<'a>(&self, context: Option<&str>, text: &'a str) -> &'a str {
+        #[cfg(feature = "thread-safe")]
+        let _guard = crate::i18n::guard();
+
+        let dictionary = dictionary();
+
+        match dictionary.translate_ns(self.name, context, text) {
+            Some(translated) => translated,
+            None => {
+                with_namespace_key(self.name, context, text, |key| {
+                    dictionary.insert_missing(key, text)
+                });
+                text
+            }
+        }
+    }
+
+    /// Translate a string to the currently user-selected language
+    /// and replace given placeholders with given values
+    /// (see [`i18n_args`](crate::i18n::i18n_args)).
+    pub fn i18n_args<'a, K, V>(
+        &self,
+        text: &str,
+        replacements: impl IntoIterator<Item = &'a (K, V)>,
+    ) -> String
+    where
+        K: AsRef<str> + 'a,
+        V: AsRef<str> + 'a,
+    {
+        replace_args(self.i18n(text), replacements)
+    }
+
+    /// Translate an ICU-style message to the currently user-selected
+    /// language and format it using the supplied arguments
+    /// (see [`i18n_format`](crate::i18n::i18n_format)).
+    pub fn i18n_format(&self, text: &str, args: &[(&str, Arg<'_>)]) -> String {
+        format_translated(self.i18n(text), args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Dictionary;
+
+    #[test]
+    fn test_namespace() -> Result<()> {
+        static UX: Namespace = Namespace::new("test-ux");
+        static WALLET: Namespace = Namespace::new("test-wallet");
+
+        UX.register(r#"{ "de": { "Open": "Offen", "button\u0004Open": "Öffnen" } }"#)?;
+        WALLET.register(r#"{ "de": { "Open": "Geöffnet" } }"#)?;
+        assert!(UX.is_registered());
+
+        let json_data = r#"{
+            "enabled": ["en", "de"],
+            "aliases": {},
+            "languages": { "en": "English", "de": "Deutsch" },
+            "translations": {
+                "en": {},
+                "de": { "Open": "Auf", "Close": "Schließen", "test-wallet/button\u0004Close": "Zumachen" }
+            }
+        }"#;
+        let dictionary = Dictionary::try_new("de", "en", Some(json_data), None)?;
+
+        assert_eq!(dictionary.translate("Open"), Some("Auf"));
+        assert_eq!(
+            dictionary.translate_ns("test-ux", None, "Open"),
+            Some("Offen")
+        );
+        assert_eq!(
+            dictionary.translate_ns("test-ux", Some("button"), "Open"),
+            Some("Öffnen")
+        );
+        assert_eq!(
+            dictionary.translate_ns("test-wallet", None, "Open"),
+            Some("Geöffnet")
+        );
+        assert_eq!(
+            dictionary.translate_ns("test-wallet", Some("button"), "Open"),
+            None
+        );
+        assert_eq!(
+            dictionary.translate_ns("test-wallet", Some("button"), "Close"),
+            Some("Zumachen")
+        );
+        assert_eq!(
+            dictionary.translate_ns("test-wallet", None, "Close"),
+            Some("Schließen")
+        );
+        assert_eq!(dictionary.translate_ns("test-wallet", None, "Send"), None);
+
+        WALLET.unregister();
+        assert!(!WALLET.is_registered());
+        assert_eq!(
+            dictionary.translate_ns("test-wallet", None, "Open"),
+            Some("Auf")
+        );
+
+        with_namespace_key("test-wallet", Some("button"), "Send", |key| {
+            dictionary.insert_missing(key, "Send")
+        });
+        let long = "x".repeat(300);
+        assert_eq!(
+            with_namespace_key("test-wallet", None, &long, |key| key.to_string()),
+            format!("test-wallet\u{4}{long}")
+        );
+
+        let storable = crate::i18n::Storable::from(&dictionary);
+        assert_eq!(
+            storable.translations["en"]["test-wallet/button\u{4}Send"],
+            "Send"
+        );
+        Ok(())
+    }
+}
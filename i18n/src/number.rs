@@ -0,0 +1,136 @@
+//!
+//! Locale-aware number formatting.
+//!
+//! Numbers are formatted using the decimal separator and digit
+//! grouping conventions of the language (based on the CLDR `latn`
+//! number symbols). Floating point values are rounded to
+//! [`MAX_FRACTION_DIGITS`] fraction digits with trailing zeros removed.
+//!
+
+use crate::plural::{primary_language, MAX_FRACTION_DIGITS};
+
+/// Number formatting symbols of a language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberSymbols {
+    pub decimal: char,
+    pub group: char,
+    /// minimum number of integer digits required to apply grouping
+    pub min_grouping: usize,
+    /// group digits by two after the first group of three (`12,34,567`)
+    pub indian_grouping: bool,
+}
+
+const NBSP: char = '\u{a0}';
+const NNBSP: char = '\u{202f}';
+
+impl NumberSymbols {
+    const fn new(decimal: char, group: char) -> Self {
+        NumberSymbols {
+            decimal,
+            group,
+            min_grouping: 4,
+            indian_grouping: false,
+        }
+    }
+
+    /// Symbols used by the supplied language.
+    pub fn for_language(language_code: &str) -> Self {
+        match primary_language(language_code).as_str() {
+            "da" | "de" | "el" | "hr" | "id" | "is" | "it" | "nl" | "pt" | "ro" | "sl" | "sr"
+            | "tr" | "vi" => NumberSymbols::new(',', '.'),
+            "es" => NumberSymbols {
+                min_grouping: 5,
+                ..NumberSymbols::new(',', '.')
+            },
+            "pl" => NumberSymbols {
+                min_grouping: 5,
+                ..NumberSymbols::new(',', NBSP)
+            },
+            "af" | "bg" | "cs" | "et" | "fi" | "hu" | "lt" | "nb" | "no" | "ru" | "sk" | "sv"
+            | "uk" => NumberSymbols::new(',', NBSP),
+            "fr" => NumberSymbols::new(',', NNBSP),
+            "bn" | "hi" | "pa" | "ta" | "te" => NumberSymbols {
+                indian_grouping: true,
+                ..NumberSymbols::new('.', ',')
+            },
+            _ => NumberSymbols::new('.', ','),
+        }
+    }
+
+    fn write_integer(&self, out: &mut String, digits: &str) {
+        if digits.len() < self.min_grouping {
+            out.push_str(digits);
+            return;
+        }
+
+        let mut groups = Vec::new();
+        let mut rest = digits;
+        let mut size = 3;
+        while rest.len() > size {
+            let (head, tail) = rest.split_at(rest.len() - size);
+            groups.push(tail);
+            rest = head;
+            if self.indian_grouping {
+                size = 2;
+            }
+        }
+        out.push_str(rest);
+        for group in groups.iter().rev() {
+            out.push(self.group);
+            out.push_str(group);
+        }
+    }
+}
+
+/// Format an integer using the conventions of the supplied language.
+pub fn format_integer(language_code: &str, value: i64) -> String {
+    let symbols = NumberSymbols::for_language(language_code);
+    let mut out = String::new();
+    if value < 0 {
+        out.push('-');
+    }
+    symbols.write_integer(&mut out, &value.unsigned_abs().to_string());
+    out
+}
+
+/// Format a floating point value using the conventions of the supplied language.
+pub fn format_number(language_code: &str, value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    let symbols = NumberSymbols::for_language(language_code);
+    let text = format!("{:.*}", MAX_FRACTION_DIGITS, value.abs());
+    let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    let fraction = fraction.trim_end_matches('0');
+
+    let mut out = String::new();
+    if value < 0.0 && (integer != "0" || !fraction.is_empty()) {
+        out.push('-');
+    }
+    symbols.write_integer(&mut out, integer);
+    if !fraction.is_empty() {
+        out.push(symbols.decimal);
+        out.push_str(fraction);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_format() {
+        assert_eq!(format_integer("en", -1234567), "-1,234,567");
+        assert_eq!(format_integer("de", 1234567), "1.234.567");
+        assert_eq!(format_integer("es", 1234), "1234");
+        assert_eq!(format_integer("es", 12345), "12.345");
+        assert_eq!(format_integer("hi", 12345678), "1,23,45,678");
+        assert_eq!(format_integer("ru", 1234), "1\u{a0}234");
+        assert_eq!(format_number("en", 1234.5), "1,234.5");
+        assert_eq!(format_number("fr", 1234.56789), "1\u{202f}234,568");
+        assert_eq!(format_number("de", -0.0001), "0");
+        assert_eq!(format_number("de", 2.0), "2");
+    }
+}
@@ -0,0 +1,423 @@
+//!
+//! CLDR plural category selection.
+//!
+//! Plural categories are selected using the CLDR cardinal plural rules
+//! of the language (<https://www.unicode.org/cldr/charts/latest/supplemental/language_plural_rules.html>).
+//! Rules are provided for all languages listed in [`Languages`](crate::i18n::Languages);
+//! language codes are matched by their primary subtag (`pt-BR` uses
+//! the `pt` rules) and unknown languages select [`PluralCategory::Other`].
+//!
+
+use crate::error::Error;
+use std::str::FromStr;
+
+/// Maximum number of fraction digits retained when
+/// formatting and selecting plural categories for
+/// floating point values.
+pub const MAX_FRACTION_DIGITS: usize = 3;
+
+/// CLDR plural category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+impl FromStr for PluralCategory {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zero" => Ok(PluralCategory::Zero),
+            "one" => Ok(PluralCategory::One),
+            "two" => Ok(PluralCategory::Two),
+            "few" => Ok(PluralCategory::Few),
+            "many" => Ok(PluralCategory::Many),
+            "other" => Ok(PluralCategory::Other),
+            _ => Err(Error::MessageFormat(format!(
+                "unknown plural category '{s}'"
+            ))),
+        }
+    }
+}
+
+/// CLDR plural operands of a number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PluralOperands {
+    /// absolute value
+    pub n: f64,
+    /// integer digits
+    pub i: u64,
+    /// number of visible fraction digits (with trailing zeros)
+    pub v: usize,
+    /// visible fraction digits (with trailing zeros)
+    pub f: u64,
+    /// visible fraction digits (without trailing zeros)
+    pub t: u64,
+}
+
+impl PluralOperands {
+    /// Returns `n % m` if `n` is an integer.
+    fn n_mod(&self, m: u64) -> Option<u64> {
+        (self.v == 0 || self.f == 0).then_some(self.i % m)
+    }
+
+    fn n_is(&self, value: u64) -> bool {
+        self.n_mod(u64::MAX) == Some(value)
+    }
+}
+
+impl From<i64> for PluralOperands {
+    fn from(value: i64) -> Self {
+        let i = value.unsigned_abs();
+        PluralOperands {
+            n: i as f64,
+            i,
+            v: 0,
+            f: 0,
+            t: 0,
+        }
+    }
+}
+
+impl From<f64> for PluralOperands {
+    fn from(value: f64) -> Self {
+        let text = format!("{:.*}", MAX_FRACTION_DIGITS, value.abs());
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        text.parse().unwrap_or(PluralOperands {
+            n: value.abs(),
+            i: 0,
+            v: 0,
+            f: 0,
+            t: 0,
+        })
+    }
+}
+
+impl FromStr for PluralOperands {
+    type Err = Error;
+
+    /// Parse operands from a decimal number (fraction
+    /// digits, including trailing zeros, are significant).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::MessageFormat(format!("invalid plural operand '{s}'"));
+
+        let s = s.trim().trim_start_matches('-');
+        let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if integer.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let i = integer.parse::<u64>().map_err(|_| invalid())?;
+        let f = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<u64>().map_err(|_| invalid())?
+        };
+        let trimmed = fraction.trim_end_matches('0');
+        let t = if trimmed.is_empty() {
+            0
+        } else {
+            trimmed.parse::<u64>().map_err(|_| invalid())?
+        };
+
+        Ok(PluralOperands {
+            n: s.parse::<f64>().map_err(|_| invalid())?,
+            i,
+            v: fraction.len(),
+            f,
+            t,
+        })
+    }
+}
+
+/// Returns the primary language subtag (`"pt"` for `"pt-BR"` or `"pt_BR"`).
+pub(crate) fn primary_language(code: &str) -> String {
+    code.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Select the CLDR cardinal plural category for the supplied language and number.
+pub fn plural_category(language_code: &str, operands: impl Into<PluralOperands>) -> PluralCategory {
+    use PluralCategory::*;
+
+    let o: PluralOperands = operands.into();
+    let (i, v, f, t) = (o.i, o.v, o.f, o.t);
+    let in_range = |value: Option<u64>, from: u64, to: u64| {
+        value
+            .map(|value| (from..=to).contains(&value))
+            .unwrap_or(false)
+    };
+    // `e = 0 and i != 0 and i % 1000000 = 0 and v = 0`
+    let millions = i != 0 && i % 1_000_000 == 0 && v == 0;
+
+    match primary_language(language_code).as_str() {
+        "ja" | "ko" | "th" | "vi" | "zh" => Other,
+
+        "af" | "bg" | "el" | "hu" | "mn" | "nb" | "no" | "ta" | "te" | "tr" => {
+            if o.n_is(1) {
+                One
+            } else {
+                Other
+            }
+        }
+
+        "de" | "en" | "et" | "fi" | "nl" | "sv" | "ur" => {
+            if i == 1 && v == 0 {
+                One
+            } else {
+                Other
+            }
+        }
+
+        "it" => {
+            if i == 1 && v == 0 {
+                One
+            } else if millions {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        "es" => {
+            if o.n_is(1) {
+                One
+            } else if millions {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        "fr" | "pt" => {
+            if i <= 1 {
+                One
+            } else if millions {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        "da" => {
+            if o.n_is(1) || (t != 0 && i <= 1) {
+                One
+            } else {
+                Other
+            }
+        }
+
+        "bn" | "fa" | "hi" => {
+            if i == 0 || o.n_is(1) {
+                One
+            } else {
+                Other
+            }
+        }
+
+        "pa" => {
+            if o.n_is(0) || o.n_is(1) {
+                One
+            } else {
+                Other
+            }
+        }
+
+        "fil" => {
+            // `v = 0 and i = 1,2,3` is covered by `v = 0 and i % 10 != 4,6,9`
+            if (v == 0 && ![4, 6, 9].contains(&(i % 10)))
+                || (v != 0 && ![4, 6, 9].contains(&(f % 10)))
+            {
+                One
+            } else {
+                Other
+            }
+        }
+
+        "is" => {
+            if (t == 0 && i % 10 == 1 && i % 100 != 11) || (t % 10 == 1 && t % 100 != 11) {
+                One
+            } else {
+                Other
+            }
+        }
+
+        "he" => {
+            if (i == 1 && v == 0) || (i == 0 && v != 0) {
+                One
+            } else if i == 2 && v == 0 {
+                Two
+            } else {
+                Other
+            }
+        }
+
+        "cs" | "sk" => {
+            if i == 1 && v == 0 {
+                One
+            } else if (2..=4).contains(&i) && v == 0 {
+                Few
+            } else if v != 0 {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        "pl" => {
+            if i == 1 && v == 0 {
+                One
+            } else if v == 0 && (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) {
+                Few
+            } else if v == 0
+                && ((i != 1 && i % 10 <= 1)
+                    || (5..=9).contains(&(i % 10))
+                    || (12..=14).contains(&(i % 100)))
+            {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        "ru" | "uk" => {
+            if v == 0 && i % 10 == 1 && i % 100 != 11 {
+                One
+            } else if v == 0 && (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) {
+                Few
+            } else if v == 0
+                && (i % 10 == 0 || (5..=9).contains(&(i % 10)) || (11..=14).contains(&(i % 100)))
+            {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        "hr" | "sr" => {
+            if (v == 0 && i % 10 == 1 && i % 100 != 11) || (f % 10 == 1 && f % 100 != 11) {
+                One
+            } else if (v == 0 && (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)))
+                || ((2..=4).contains(&(f % 10)) && !(12..=14).contains(&(f % 100)))
+            {
+                Few
+            } else {
+                Other
+            }
+        }
+
+        "sl" => {
+            if v == 0 && i % 100 == 1 {
+                One
+            } else if v == 0 && i % 100 == 2 {
+                Two
+            } else if v != 0 || (3..=4).contains(&(i % 100)) {
+                Few
+            } else {
+                Other
+            }
+        }
+
+        "lt" => {
+            if o.n_mod(10) == Some(1) && !in_range(o.n_mod(100), 11, 19) {
+                One
+            } else if in_range(o.n_mod(10), 2, 9) && !in_range(o.n_mod(100), 11, 19) {
+                Few
+            } else if f != 0 {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        "ro" => {
+            if i == 1 && v == 0 {
+                One
+            } else if v != 0 || o.n_is(0) || (!o.n_is(1) && in_range(o.n_mod(100), 1, 19)) {
+                Few
+            } else {
+                Other
+            }
+        }
+
+        "ar" => {
+            if o.n_is(0) {
+                Zero
+            } else if o.n_is(1) {
+                One
+            } else if o.n_is(2) {
+                Two
+            } else if in_range(o.n_mod(100), 3, 10) {
+                Few
+            } else if in_range(o.n_mod(100), 11, 99) {
+                Many
+            } else {
+                Other
+            }
+        }
+
+        _ => Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PluralCategory::*;
+
+    #[test]
+    fn test_plural_category() {
+        let categories = |code: &str, numbers: &[i64]| {
+            numbers
+                .iter()
+                .map(|n| plural_category(code, *n))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(categories("en", &[0, 1, 2]), [Other, One, Other]);
+        assert_eq!(plural_category("en-US", 1.5), Other);
+        assert_eq!(
+            plural_category("en", "1.0".parse::<PluralOperands>().unwrap()),
+            Other
+        );
+        assert_eq!(
+            categories("fr", &[0, 1, 2, 1_000_000]),
+            [One, One, Other, Many]
+        );
+        assert_eq!(
+            categories("pl", &[1, 2, 5, 12, 22, 25]),
+            [One, Few, Many, Many, Few, Many]
+        );
+        assert_eq!(plural_category("pl", 1.5), Other);
+        assert_eq!(
+            categories("ru", &[1, 3, 11, 21, 111, 0]),
+            [One, Few, Many, One, Many, Many]
+        );
+        assert_eq!(
+            categories("ar", &[0, 1, 2, 3, 11, 100]),
+            [Zero, One, Two, Few, Many, Other]
+        );
+        assert_eq!(categories("ja", &[1]), [Other]);
+        assert_eq!(categories("xx", &[1]), [Other]);
+    }
+}
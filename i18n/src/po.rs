@@ -0,0 +1,508 @@
+//!
+//! Gettext PO / POT import and export.
+//!
+//! Dictionaries can be exported as a `.pot` template (containing
+//! the default language strings and strings recorded as missing at
+//! runtime) or as a `.po` file for a specific language, edited using
+//! PO-based translation tools (Weblate, Poedit etc.) and imported back.
+//!
+//! Message contexts are mapped to `msgctxt` (see [`context_key`]).
+//! Translator comments and fuzzy translations are preserved in the
+//! `i18n.json` data file ([`EntryInfo`]); fuzzy translations are not
+//! used at runtime. PO plural entries (`msgid_plural`) are not supported,
+//! plural forms are expressed using ICU messages (see [`format`](crate::format)).
+//!
+//! ```ignore
+//! po::export_po("pl", |data| Ok(std::fs::write("pl.po", data)?))?;
+//! let report = po::import_po("pl", &std::fs::read_to_string("pl.po")?, true)?;
+//! for key in report.stale {
+//!     println!("stale translation: {key}");
+//! }
+//! ```
+//!
+
+use crate::error::Error;
+use crate::i18n::{context_key, dictionary, from_string, split_context_key, EntryInfo, Storable};
+use crate::result::Result;
+use std::fmt;
+
+/// PO file entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoEntry {
+    /// translator comments (`# `)
+    pub comments: Vec<String>,
+    /// extracted comments (`#.`)
+    pub extracted: Vec<String>,
+    /// source references (`#:`)
+    pub references: Vec<String>,
+    /// flags (`#,`)
+    pub flags: Vec<String>,
+    pub context: Option<String>,
+    pub msgid: String,
+    pub msgstr: String,
+}
+
+impl PoEntry {
+    pub fn is_fuzzy(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "fuzzy")
+    }
+}
+
+/// Parsed PO (or POT) file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoFile {
+    /// header entries (`Language: pl` etc.)
+    pub header: Vec<(String, String)>,
+    pub entries: Vec<PoEntry>,
+}
+
+#[derive(Clone, Copy)]
+enum Keyword {
+    Context,
+    Id,
+    Str,
+}
+
+impl PoFile {
+    pub fn parse(text: &str) -> Result<PoFile> {
+        let mut po = PoFile::default();
+        let mut entry = PoEntry::default();
+        let mut keyword = None;
+        let mut has_msgstr = false;
+
+        let mut finish = |entry: &mut PoEntry, has_msgstr: &mut bool| {
+            let entry = std::mem::take(entry);
+            if !*has_msgstr {
+                return;
+            }
+            *has_msgstr = false;
+            if entry.msgid.is_empty() && entry.context.is_none() {
+                po.header = entry
+                    .msgstr
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                    .collect();
+            } else {
+                po.entries.push(entry);
+            }
+        };
+
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: &str| Error::PoParse(index + 1, message.to_string());
+            let line = line.trim();
+
+            if line.is_empty() {
+                finish(&mut entry, &mut has_msgstr);
+                keyword = None;
+            } else if line.starts_with("#~") || line.starts_with("#|") {
+                // obsolete entries and previous strings are ignored
+            } else if let Some(comment) = line.strip_prefix('#') {
+                if has_msgstr {
+                    finish(&mut entry, &mut has_msgstr);
+                }
+                keyword = None;
+                match comment.chars().next() {
+                    Some('.') => entry.extracted.push(comment[1..].trim().to_string()),
+                    Some(':') => entry
+                        .references
+                        .extend(comment[1..].split_whitespace().map(String::from)),
+                    Some(',') => entry
+                        .flags
+                        .extend(comment[1..].split(',').map(|flag| flag.trim().to_string())),
+                    _ => entry
+                        .comments
+                        .push(comment.strip_prefix(' ').unwrap_or(comment).to_string()),
+                }
+            } else if line.starts_with("msgid_plural") || line.starts_with("msgstr[") {
+                return Err(error(
+                    "plural entries are not supported (use ICU plural messages)",
+                ));
+            } else if line.starts_with('"') {
+                let value = unquote(line).ok_or_else(|| error("invalid string"))?;
+                match keyword {
+                    Some(Keyword::Context) => entry
+                        .context
+                        .get_or_insert_with(String::new)
+                        .push_str(&value),
+                    Some(Keyword::Id) => entry.msgid.push_str(&value),
+                    Some(Keyword::Str) => entry.msgstr.push_str(&value),
+                    None => return Err(error("unexpected string")),
+                }
+            } else {
+                let (name, value) = line
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| error("expected keyword"))?;
+                let value = unquote(value.trim()).ok_or_else(|| error("invalid string"))?;
+                match name {
+                    "msgctxt" | "msgid" if has_msgstr => finish(&mut entry, &mut has_msgstr),
+                    _ => {}
+                }
+                match name {
+                    "msgctxt" => {
+                        entry.context = Some(value);
+                        keyword = Some(Keyword::Context);
+                    }
+                    "msgid" => {
+                        entry.msgid = value;
+                        keyword = Some(Keyword::Id);
+                    }
+                    "msgstr" => {
+                        entry.msgstr = value;
+                        keyword = Some(Keyword::Str);
+                        has_msgstr = true;
+                    }
+                    _ => return Err(error(&format!("unknown keyword '{name}'"))),
+                }
+            }
+        }
+        finish(&mut entry, &mut has_msgstr);
+
+        Ok(po)
+    }
+
+    /// Returns the value of the supplied header entry.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.header
+            .iter()
+            .find_map(|(key, value)| (key == name).then_some(value.as_str()))
+    }
+}
+
+fn unquote(text: &str) -> Option<String> {
+    let text = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                c => out.push(c),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Some(out)
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, keyword: &str, text: &str) -> fmt::Result {
+    let escape = |line: &str| {
+        line.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t")
+            .replace('\r', "\\r")
+    };
+
+    if text.trim_end_matches('\n').contains('\n') {
+        writeln!(f, "{keyword} \"\"")?;
+        for line in text.split_inclusive('\n') {
+            writeln!(f, "\"{}\"", escape(line))?;
+        }
+        Ok(())
+    } else {
+        writeln!(f, "{keyword} \"{}\"", escape(text))
+    }
+}
+
+impl fmt::Display for PoFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "msgid \"\"")?;
+        writeln!(f, "msgstr \"\"")?;
+        for (name, value) in self.header.iter() {
+            writeln!(f, "\"{name}: {value}\\n\"")?;
+        }
+
+        for entry in self.entries.iter() {
+            writeln!(f)?;
+            for comment in entry.comments.iter() {
+                writeln!(f, "# {comment}")?;
+            }
+            for comment in entry.extracted.iter() {
+                writeln!(f, "#. {comment}")?;
+            }
+            if !entry.references.is_empty() {
+                writeln!(f, "#: {}", entry.references.join(" "))?;
+            }
+            if !entry.flags.is_empty() {
+                writeln!(f, "#, {}", entry.flags.join(", "))?;
+            }
+            if let Some(context) = &entry.context {
+                write_string(f, "msgctxt", context)?;
+            }
+            write_string(f, "msgid", &entry.msgid)?;
+            write_string(f, "msgstr", &entry.msgstr)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of a PO file import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// number of imported translations
+    pub translated: usize,
+    /// number of imported fuzzy translations
+    pub fuzzy: usize,
+    /// number of entries without translation
+    pub untranslated: usize,
+    /// keys (see [`context_key`]) of entries whose source
+    /// string no longer exists; these entries are not imported
+    pub stale: Vec<String>,
+}
+
+/// Default language strings (including strings recorded as missing) sorted by key.
+fn sources<'s>(storable: &'s Storable, default_code: &str) -> Result<Vec<&'s String>> {
+    let mut sources = storable
+        .translations
+        .get(default_code)
+        .ok_or_else(|| Error::UnknownLanguageCode(default_code.to_string()))?
+        .keys()
+        .collect::<Vec<_>>();
+    sources.sort();
+    Ok(sources)
+}
+
+/// Create a PO file for the supplied language or a POT template if `None`.
+pub(crate) fn to_po(
+    storable: &Storable,
+    default_code: &str,
+    language_code: Option<&str>,
+) -> Result<PoFile> {
+    let translations = language_code
+        .map(|code| {
+            storable
+                .translations
+                .get(code)
+                .ok_or_else(|| Error::UnknownLanguageCode(code.to_string()))
+        })
+        .transpose()?;
+    let info = language_code.and_then(|code| storable.info.get(code));
+
+    let mut header = vec![
+        ("MIME-Version".to_string(), "1.0".to_string()),
+        (
+            "Content-Type".to_string(),
+            "text/plain; charset=UTF-8".to_string(),
+        ),
+        ("Content-Transfer-Encoding".to_string(), "8bit".to_string()),
+    ];
+    if let Some(code) = language_code {
+        header.push(("Language".to_string(), code.to_string()));
+    }
+
+    let entries = sources(storable, default_code)?
+        .into_iter()
+        .map(|key| {
+            let (context, msgid) = split_context_key(key);
+            let info = info.and_then(|info| info.get(key));
+            let mut entry = PoEntry {
+                context: context.map(String::from),
+                msgid: msgid.to_string(),
+                ..Default::default()
+            };
+
+            if let Some(info) = info {
+                entry.comments.clone_from(&info.comments);
+            }
+            if let Some(translated) = translations.and_then(|translations| translations.get(key)) {
+                entry.msgstr.clone_from(translated);
+            } else if let Some(fuzzy) = info.and_then(|info| info.fuzzy.as_ref()) {
+                entry.msgstr.clone_from(fuzzy);
+                entry.flags.push("fuzzy".to_string());
+            }
+            entry
+        })
+        .collect();
+
+    Ok(PoFile { header, entries })
+}
+
+/// Merge translations from the PO file into the supplied language.
+pub(crate) fn merge_po(
+    storable: &mut Storable,
+    default_code: &str,
+    language_code: &str,
+    po: &PoFile,
+) -> Result<ImportReport> {
+    let sources = sources(storable, default_code)?
+        .into_iter()
+        .cloned()
+        .collect::<std::collections::HashSet<_>>();
+
+    let mut report = ImportReport::default();
+    let translations = storable
+        .translations
+        .get_mut(language_code)
+        .ok_or_else(|| Error::UnknownLanguageCode(language_code.to_string()))?;
+    let info = storable.info.entry(language_code.to_string()).or_default();
+
+    for entry in po.entries.iter() {
+        let key = context_key(entry.context.as_deref(), &entry.msgid);
+        if !sources.contains(&key) {
+            report.stale.push(key);
+            continue;
+        }
+
+        let mut entry_info = EntryInfo {
+            comments: entry.comments.clone(),
+            fuzzy: None,
+        };
+
+        if entry.msgstr.is_empty() {
+            // an empty msgstr (e.g. an entry added by a newer template)
+            // does not discard the existing translation
+            entry_info.fuzzy = info.get(&key).and_then(|info| info.fuzzy.clone());
+            if !translations.contains_key(&key) {
+                report.untranslated += 1;
+            }
+        } else if entry.is_fuzzy() {
+            translations.remove(&key);
+            entry_info.fuzzy = Some(entry.msgstr.clone());
+            report.fuzzy += 1;
+        } else {
+            translations.insert(key.clone(), entry.msgstr.clone());
+            report.translated += 1;
+        }
+
+        if entry_info.is_empty() {
+            info.remove(&key);
+        } else {
+            info.insert(key, entry_info);
+        }
+    }
+
+    if info.is_empty() {
+        storable.info.remove(language_code);
+    }
+
+    Ok(report)
+}
+
+/// Export the default language strings (including strings recorded
+/// as missing at runtime) as a POT template.
+pub fn export_pot(store_fn: impl Fn(&str) -> Result<()> + Send + Sync + 'static) -> Result<()> {
+    let dictionary = dictionary();
+    let storable = Storable::from(dictionary.as_ref());
+    let po = to_po(&storable, dictionary.default_code(), None)?;
+    store_fn(po.to_string().as_str())
+}
+
+/// Export translations of the supplied language as a PO file.
+pub fn export_po(
+    language_code: &str,
+    store_fn: impl Fn(&str) -> Result<()> + Send + Sync + 'static,
+) -> Result<()> {
+    let dictionary = dictionary();
+    let language_code = dictionary.resolve_aliases(language_code)?;
+    let storable = Storable::from(dictionary.as_ref());
+    let po = to_po(&storable, dictionary.default_code(), Some(&language_code))?;
+    store_fn(po.to_string().as_str())
+}
+
+/// Import translations of the supplied language from a PO file and store
+/// the updated data using the dictionary store function, optionally
+/// reloading the dictionary. Entries whose source string no longer
+/// exists are reported in [`ImportReport::stale`].
+pub fn import_po(language_code: &str, po_data: &str, reload: bool) -> Result<ImportReport> {
+    let dictionary = dictionary();
+    let language_code = dictionary.resolve_aliases(language_code)?;
+    let store_fn = dictionary.store_fn().clone().ok_or(Error::StoreFn)?;
+
+    let po = PoFile::parse(po_data)?;
+    let mut storable = Storable::from(dictionary.as_ref());
+    let report = merge_po(
+        &mut storable,
+        dictionary.default_code(),
+        &language_code,
+        &po,
+    )?;
+
+    let json_data = storable.to_json()?;
+    store_fn(json_data.as_str())?;
+
+    if reload {
+        from_string(json_data)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Dictionary;
+
+    const JSON_DATA: &str = r#"{
+        "enabled": ["en", "pl"],
+        "aliases": {},
+        "languages": { "en": "English", "pl": "Polish" },
+        "translations": {
+            "en": { "Hello": "Hello", "button\u0004Open": "Open", "button\u0004Save": "Save", "Removed": "Removed" },
+            "pl": { "Hello": "Cześć", "button\u0004Save": "Zapisz" }
+        }
+    }"#;
+
+    #[test]
+    fn test_po_import_export() -> Result<()> {
+        let dictionary = Dictionary::try_new("en", "en", Some(JSON_DATA), None)?;
+        let mut storable = Storable::from(&dictionary);
+
+        let pot = to_po(&storable, "en", None)?.to_string();
+        assert!(pot.contains("msgctxt \"button\"\nmsgid \"Open\"\nmsgstr \"\"\n"));
+
+        let po = PoFile::parse(
+            r#"
+msgid ""
+msgstr ""
+"Language: pl\n"
+
+# greeting
+msgid "Hello"
+msgstr "Witaj"
+
+#, fuzzy
+msgctxt "button"
+msgid "Open"
+msgstr "Otwórz"
+
+msgid "Obsolete"
+msgstr ""
+"Przestarzałe"
+
+msgctxt "button"
+msgid "Save"
+msgstr ""
+"#,
+        )?;
+        assert_eq!(po.header("Language"), Some("pl"));
+
+        storable
+            .translations
+            .get_mut("en")
+            .unwrap()
+            .remove("Removed");
+        let report = merge_po(&mut storable, "en", "pl", &po)?;
+        assert_eq!(report.translated, 1);
+        assert_eq!(report.fuzzy, 1);
+        assert_eq!(report.untranslated, 0);
+        assert_eq!(report.stale, ["Obsolete"]);
+        assert_eq!(storable.translations["pl"]["Hello"], "Witaj");
+        assert_eq!(storable.translations["pl"]["button\u{4}Save"], "Zapisz");
+        assert!(!storable.translations["pl"].contains_key("button\u{4}Open"));
+
+        let exported = to_po(&storable, "en", Some("pl"))?;
+        assert_eq!(PoFile::parse(&exported.to_string())?, exported);
+        assert_eq!(exported.entries[0].comments, ["greeting"]);
+        assert_eq!(exported.entries[1].flags, ["fuzzy"]);
+        assert_eq!(exported.entries[1].msgstr, "Otwórz");
+
+        let json = storable.to_json()?;
+        let reloaded = Dictionary::try_new("en", "en", Some(Box::leak(json.into())), None)?;
+        assert_eq!(Storable::from(&reloaded).info, storable.info);
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//!
+//! XLIFF 1.2 export.
+//!
+//! Translations of a language are exported as an XLIFF 1.2 document
+//! with one `trans-unit` per source string. Message contexts are
+//! exported as `x-gettext-msgctxt` context entries (and included in the
+//! `resname` as `context::source`, keeping it unique), translator comments
+//! as translator notes and fuzzy translations as targets in the
+//! `needs-review-translation` state.
+//!
+
+use crate::error::Error;
+use crate::i18n::{dictionary, split_context_key, Storable};
+use crate::result::Result;
+use std::fmt::Write;
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Create an XLIFF 1.2 document containing translations of the supplied language.
+pub(crate) fn to_xliff(
+    storable: &Storable,
+    default_code: &str,
+    language_code: &str,
+) -> Result<String> {
+    let unknown = |code: &str| Error::UnknownLanguageCode(code.to_string());
+    let sources = storable
+        .translations
+        .get(default_code)
+        .ok_or_else(|| unknown(default_code))?;
+    let translations = storable
+        .translations
+        .get(language_code)
+        .ok_or_else(|| unknown(language_code))?;
+    let info = storable.info.get(language_code);
+
+    let mut keys = sources.keys().collect::<Vec<_>>();
+    keys.sort();
+
+    let mut xml = String::new();
+    // writing to a String can not fail
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        xml,
+        r#"<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">"#
+    );
+    let _ = writeln!(
+        xml,
+        r#"  <file original="i18n.json" datatype="plaintext" source-language="{}" target-language="{}">"#,
+        escape(default_code),
+        escape(language_code)
+    );
+    let _ = writeln!(xml, "    <body>");
+
+    for (index, key) in keys.into_iter().enumerate() {
+        let (context, source) = split_context_key(key);
+        let info = info.and_then(|info| info.get(key));
+
+        let resname = match context {
+            Some(context) => format!("{context}::{source}"),
+            None => source.to_string(),
+        };
+        let _ = writeln!(
+            xml,
+            r#"      <trans-unit id="{}" resname="{}">"#,
+            index + 1,
+            escape(&resname)
+        );
+        let _ = writeln!(xml, "        <source>{}</source>", escape(source));
+        if let Some(target) = translations.get(key) {
+            let _ = writeln!(
+                xml,
+                r#"        <target state="translated">{}</target>"#,
+                escape(target)
+            );
+        } else if let Some(fuzzy) = info.and_then(|info| info.fuzzy.as_ref()) {
+            let _ = writeln!(
+                xml,
+                r#"        <target state="needs-review-translation">{}</target>"#,
+                escape(fuzzy)
+            );
+        }
+        if let Some(context) = context {
+            let _ = writeln!(
+                xml,
+                r#"        <context-group purpose="information"><context context-type="x-gettext-msgctxt">{}</context></context-group>"#,
+                escape(context)
+            );
+        }
+        for comment in info.iter().flat_map(|info| info.comments.iter()) {
+            let _ = writeln!(
+                xml,
+                r#"        <note from="translator">{}</note>"#,
+                escape(comment)
+            );
+        }
+        let _ = writeln!(xml, "      </trans-unit>");
+    }
+
+    let _ = writeln!(xml, "    </body>");
+    let _ = writeln!(xml, "  </file>");
+    let _ = writeln!(xml, "</xliff>");
+    Ok(xml)
+}
+
+/// Export translations of the supplied language as an XLIFF 1.2 document.
+pub fn export_xliff(
+    language_code: &str,
+    store_fn: impl Fn(&str) -> Result<()> + Send + Sync + 'static,
+) -> Result<()> {
+    let dictionary = dictionary();
+    let language_code = dictionary.resolve_aliases(language_code)?;
+    let storable = Storable::from(dictionary.as_ref());
+    let xml = to_xliff(&storable, dictionary.default_code(), &language_code)?;
+    store_fn(xml.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Dictionary;
+
+    #[test]
+    fn test_xliff_export() -> Result<()> {
+        let json_data = r#"{
+            "enabled": ["en", "de"],
+            "aliases": {},
+            "languages": { "en": "English", "de": "Deutsch" },
+            "translations": {
+                "en": { "Open": "Open", "Save & exit": "Save & exit", "button\u0004Open": "Open" },
+                "de": { "Save & exit": "Speichern & beenden" }
+            },
+            "info": { "de": { "button\u0004Open": { "comments": ["verb"], "fuzzy": "Öffnen" } } }
+        }"#;
+        let dictionary = Dictionary::try_new("en", "en", Some(json_data), None)?;
+        let xml = to_xliff(&Storable::from(&dictionary), "en", "de")?;
+
+        assert!(xml.contains(r#"source-language="en" target-language="de""#));
+        assert!(xml.contains(
+            r#"<source>Save &amp; exit</source>
+        <target state="translated">Speichern &amp; beenden</target>"#
+        ));
+        assert!(xml.contains(r#"<trans-unit id="1" resname="Open">"#));
+        assert!(xml.contains(r#"<trans-unit id="3" resname="button::Open">"#));
+        assert!(xml.contains(r#"<target state="needs-review-translation">Öffnen</target>"#));
+        assert!(xml.contains(r#"<context context-type="x-gettext-msgctxt">button</context>"#));
+        assert!(xml.contains(r#"<note from="translator">verb</note>"#));
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+//!
+//! Rotating log file sink (native platforms only).
+//!
+//! [`RotatingFileSink`] appends log messages to a file and rotates it
+//! when it exceeds the configured size or when the day changes (UTC).
+//! Rotated files are renamed to `<file>.1`, `<file>.2`, ... (the most
+//! recent being `<file>.1`), optionally compressed using gzip
+//! (`<file>.1.gz`), and only the configured number of files is retained.
+//!
+//! ```ignore
+//! let sink = RotatingFileSink::new("~/.app/logs/app.log")
+//!     .with_rotation(Rotation::Size(10 * 1024 * 1024))
+//!     .with_keep(5)
+//!     .with_compression(true);
+//! workflow_log::register_sink(Arc::new(sink), LevelFilter::Debug);
+//! ```
+//!
+
+use crate::json::{format_json, unixtime_as_millis, write_timestamp, Format};
+use crate::kv::{DisplayFields, Field};
+use crate::{Level, Sink};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+const MILLIS_PER_DAY: u64 = 86_400_000;
+
+/// Resolve the `~/` prefix to the home directory.
+fn resolve_path(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home::home_dir()) {
+        (Ok(relative), Some(home)) => home.join(relative),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Log file rotation policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Rotate when the file size would exceed the supplied number of bytes.
+    Size(u64),
+    /// Rotate when the day (UTC) changes.
+    Daily,
+    /// Never rotate.
+    Never,
+}
+
+struct State {
+    file: Option<File>,
+    size: u64,
+    day: u64,
+}
+
+/// Log sink writing to a rotating log file.
+pub struct RotatingFileSink {
+    path: PathBuf,
+    rotation: Rotation,
+    keep: usize,
+    compress: bool,
+    format: Format,
+    state: Mutex<State>,
+}
+
+impl RotatingFileSink {
+    /// Create a sink writing to the supplied file (`~/` is resolved to
+    /// the home directory), rotating it when it exceeds 10 MiB and keeping
+    /// 5 rotated files. The file (and the parent folder) is created when
+    /// the first message is written.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        RotatingFileSink {
+            path: resolve_path(path.as_ref()),
+            rotation: Rotation::Size(10 * 1024 * 1024),
+            keep: 5,
+            compress: false,
+            format: Format::Text,
+            state: Mutex::new(State {
+                file: None,
+                size: 0,
+                day: 0,
+            }),
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Number of rotated files to retain.
+    pub fn with_keep(mut self, keep: usize) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    /// Compress rotated files using gzip.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Format of the log lines ([`Format::Text`] by default).
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the rotated file with the supplied index.
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        if self.compress {
+            path.push(".gz");
+        }
+        path.into()
+    }
+
+    /// Force rotation of the current log file.
+    pub fn rotate(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        self.rotate_impl(&mut state)
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        match self.state.lock().unwrap().file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.file.is_none() {
+            self.open(&mut state)?;
+        }
+
+        let len = line.len() as u64 + 1;
+        let rotate = match self.rotation {
+            Rotation::Size(max) => state.size > 0 && state.size + len > max,
+            Rotation::Daily => state.day != unixtime_as_millis() / MILLIS_PER_DAY,
+            Rotation::Never => false,
+        };
+        if rotate {
+            self.rotate_impl(&mut state)?;
+            self.open(&mut state)?;
+        }
+
+        let file = state.file.as_mut().expect("log file is open");
+        file.write_all(format!("{line}\n").as_bytes())?;
+        state.size += len;
+        Ok(())
+    }
+
+    fn open(&self, state: &mut State) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let metadata = file.metadata()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_else(unixtime_as_millis);
+
+        state.size = metadata.len();
+        state.day = if metadata.len() > 0 {
+            modified / MILLIS_PER_DAY
+        } else {
+            unixtime_as_millis() / MILLIS_PER_DAY
+        };
+        state.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate_impl(&self, state: &mut State) -> io::Result<()> {
+        if let Some(mut file) = state.file.take() {
+            file.flush()?;
+        }
+        state.size = 0;
+
+        if !self.path.exists() {
+            return Ok(());
+        }
+
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+
+        let oldest = self.rotated_path(self.keep);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.keep).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+
+        if self.compress {
+            let mut source = File::open(&self.path)?;
+            let target = File::create(self.rotated_path(1))?;
+            let mut encoder = GzEncoder::new(target, Compression::default());
+            io::copy(&mut source, &mut encoder)?;
+            encoder.finish()?.sync_all()?;
+            fs::remove_file(&self.path)
+        } else {
+            fs::rename(&self.path, self.rotated_path(1))
+        }
+    }
+
+    fn format_line(
+        &self,
+        target: Option<&str>,
+        level: Level,
+        args: &fmt::Arguments<'_>,
+        fields: &[Field<'_>],
+    ) -> String {
+        match self.format {
+            Format::Json => format_json(target, level, args, fields),
+            Format::Text => {
+                use std::fmt::Write;
+                let mut line = String::with_capacity(128);
+                let _ = write_timestamp(&mut line, unixtime_as_millis());
+                let _ = write!(line, " {:<5}", level.as_str());
+                if let Some(target) = target {
+                    let _ = write!(line, " {target}:");
+                }
+                let _ = write!(line, " {args}{}", DisplayFields(fields));
+                line
+            }
+        }
+    }
+}
+
+impl Sink for RotatingFileSink {
+    fn write(&self, target: Option<&str>, level: Level, args: &fmt::Arguments<'_>) -> bool {
+        self.write_with_fields(target, level, args, &[])
+    }
+
+    fn write_with_fields(
+        &self,
+        target: Option<&str>,
+        level: Level,
+        args: &fmt::Arguments<'_>,
+        fields: &[Field<'_>],
+    ) -> bool {
+        let line = self.format_line(target, level, args, fields);
+        if let Err(err) = self.write_line(&line) {
+            eprintln!("unable to write log file `{}`: {err}", self.path.display());
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_rotating_file_sink() -> io::Result<()> {
+        let folder = std::env::temp_dir().join(format!("workflow-log-file-{}", std::process::id()));
+        let sink = RotatingFileSink::new(folder.join("test.log"))
+            .with_rotation(Rotation::Size(64))
+            .with_keep(2)
+            .with_compression(true);
+
+        for n in 0..4 {
+            sink.write_with_fields(
+                Some("test"),
+                Level::Info,
+                &format_args!("message {n}"),
+                &[("n", crate::kv::Value::U64(n))],
+            );
+        }
+        sink.flush()?;
+
+        let current = fs::read_to_string(sink.path())?;
+        assert!(current.ends_with(" INFO  test: message 3 n=3\n"));
+        assert!(!sink.rotated_path(3).exists());
+
+        let mut rotated = String::new();
+        GzDecoder::new(File::open(sink.rotated_path(1))?).read_to_string(&mut rotated)?;
+        assert!(rotated.ends_with(" INFO  test: message 2 n=2\n"));
+        assert!(sink.rotated_path(2).exists());
+
+        assert_eq!(
+            RotatingFileSink::new("~/logs/app.log").path(),
+            home::home_dir().unwrap().join("logs/app.log")
+        );
+
+        fs::remove_dir_all(&folder)
+    }
+}
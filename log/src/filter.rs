@@ -0,0 +1,213 @@
+//!
+//! Per-target log filtering using `RUST_LOG`-style directives.
+//!
+//! A directive string is a comma-separated list of `target=level`
+//! entries, optionally containing a bare `level` that sets the default
+//! level for all other targets, for example:
+//! `workflow_rpc=trace,my_app::db=debug,info`
+//!
+//! Targets match on module path boundaries, i.e. `my_app::db` matches
+//! `my_app::db` and `my_app::db::sqlite` but not `my_app::dbx`. When
+//! multiple directives match a target, the most specific (longest)
+//! directive is used.
+//!
+
+use crate::{Level, LevelFilter};
+use std::fmt;
+use std::str::FromStr;
+
+/// Error produced when parsing an invalid filter directive string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterParseError(String);
+
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid log filter directive: {}", self.0)
+    }
+}
+
+impl std::error::Error for FilterParseError {}
+
+/// A single `target=level` filter directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    pub target: String,
+    pub level: LevelFilter,
+}
+
+impl Directive {
+    fn matches(&self, target: &str) -> bool {
+        target
+            .strip_prefix(self.target.as_str())
+            .map(|rest| rest.is_empty() || rest.starts_with("::"))
+            .unwrap_or(false)
+    }
+}
+
+/// Log filter containing the default log level and per-target directives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    default: LevelFilter,
+    // sorted by target length (most specific first)
+    directives: Vec<Directive>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::new(LevelFilter::Info)
+    }
+}
+
+impl Filter {
+    /// Create a filter with the supplied default level and no directives.
+    pub const fn new(default: LevelFilter) -> Self {
+        Filter {
+            default,
+            directives: Vec::new(),
+        }
+    }
+
+    /// Parse a `RUST_LOG`-style directive string.
+    pub fn parse(directives: &str) -> Result<Self, FilterParseError> {
+        let mut filter = Filter::new(LevelFilter::Info);
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(FilterParseError(directive.to_string()));
+                    }
+                    let level = parse_level(level.trim())
+                        .ok_or_else(|| FilterParseError(directive.to_string()))?;
+                    filter = filter.with_directive(target, level);
+                }
+                None => match parse_level(directive) {
+                    Some(level) => filter.default = level,
+                    // a bare target enables all levels for that target
+                    None => filter = filter.with_directive(directive, LevelFilter::Trace),
+                },
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// Set the level for the supplied target, replacing an existing
+    /// directive for the same target. Crate names are accepted in either
+    /// `my-crate` or `my_crate` forms.
+    pub fn with_directive(mut self, target: &str, level: LevelFilter) -> Self {
+        let target = target.replace('-', "_");
+        self.directives
+            .retain(|directive| directive.target != target);
+        self.directives.push(Directive { target, level });
+        self.directives.sort_by(|a, b| {
+            b.target
+                .len()
+                .cmp(&a.target.len())
+                .then_with(|| a.target.cmp(&b.target))
+        });
+        self
+    }
+
+    /// Set the default level for targets not matching any directive.
+    pub fn with_default(mut self, level: LevelFilter) -> Self {
+        self.default = level;
+        self
+    }
+
+    pub fn default_level(&self) -> LevelFilter {
+        self.default
+    }
+
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    /// The most verbose level enabled by this filter for any target.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|directive| directive.level)
+            .fold(self.default, std::cmp::max)
+    }
+
+    /// The level enabled for the supplied target.
+    pub fn level(&self, target: Option<&str>) -> LevelFilter {
+        target
+            .and_then(|target| {
+                self.directives
+                    .iter()
+                    .find(|directive| directive.matches(target))
+            })
+            .map(|directive| directive.level)
+            .unwrap_or(self.default)
+    }
+
+    /// Returns true if messages with the supplied target
+    /// and level should be logged.
+    pub fn enabled(&self, target: Option<&str>, level: Level) -> bool {
+        self.level(target) >= level
+    }
+}
+
+impl From<LevelFilter> for Filter {
+    fn from(level: LevelFilter) -> Self {
+        Filter::new(level)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for directive in self.directives.iter().rev() {
+            write!(
+                f,
+                "{}={},",
+                directive.target,
+                directive.level.as_str().to_lowercase()
+            )?;
+        }
+        write!(f, "{}", self.default.as_str().to_lowercase())
+    }
+}
+
+fn parse_level(level: &str) -> Option<LevelFilter> {
+    LevelFilter::from_str(level).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_directives() {
+        let filter = Filter::parse("workflow_rpc=trace, my-app::db=debug,warn").unwrap();
+        assert_eq!(filter.default_level(), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+        assert!(filter.enabled(Some("workflow_rpc::client"), Level::Trace));
+        assert!(filter.enabled(Some("my_app::db"), Level::Debug));
+        assert!(!filter.enabled(Some("my_app::dbx"), Level::Info));
+        assert!(!filter.enabled(Some("my_app"), Level::Info));
+        assert!(filter.enabled(None, Level::Warn));
+
+        let filter = filter.with_directive("my_app::db::sqlite", LevelFilter::Off);
+        assert!(!filter.enabled(Some("my_app::db::sqlite"), Level::Error));
+        assert!(filter.enabled(Some("my_app::db::mem"), Level::Debug));
+
+        assert_eq!(Filter::parse(&filter.to_string()).unwrap(), filter);
+        assert!(Filter::parse("workflow_rpc=verbose").is_err());
+        assert!(Filter::parse("=info").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//!
+//! JSON-lines log formatting.
+//!
+//! Each log message is formatted as a single-line JSON object
+//! containing the timestamp (RFC 3339, UTC), level, target, message
+//! and structured fields, suitable for ingestion by log aggregators:
+//!
+//! ```text
+//! {"timestamp":"2024-01-01T12:00:00.000Z","level":"INFO","target":"my_app::net","message":"connected","peer":"127.0.0.1:16110"}
+//! ```
+//!
+//! Native console output can be switched to this format using
+//! [`set_log_format(Format::Json)`](crate::set_log_format).
+//!
+
+use crate::kv::{Field, Value};
+use crate::Level;
+use std::fmt::{self, Write};
+
+/// Console output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Plain text with structured fields appended as `key=value` pairs.
+    #[default]
+    Text,
+    /// JSON lines (see [`format_json`]).
+    Json,
+}
+
+/// Format a log message as a single-line JSON object.
+pub fn format_json(
+    target: Option<&str>,
+    level: Level,
+    args: &fmt::Arguments<'_>,
+    fields: &[Field<'_>],
+) -> String {
+    let mut json = String::with_capacity(128);
+    // writing to a String can not fail
+    let _ = write_json(&mut json, unixtime_as_millis(), target, level, args, fields);
+    json
+}
+
+fn write_json(
+    json: &mut String,
+    millis: u64,
+    target: Option<&str>,
+    level: Level,
+    args: &fmt::Arguments<'_>,
+    fields: &[Field<'_>],
+) -> fmt::Result {
+    json.push_str("{\"timestamp\":\"");
+    write_timestamp(json, millis)?;
+    write!(json, "\",\"level\":\"{}\"", level.as_str())?;
+    if let Some(target) = target {
+        json.push_str(",\"target\":");
+        write_str(json, target);
+    }
+    json.push_str(",\"message\":");
+    write_str(json, &args.to_string());
+
+    for (key, value) in fields {
+        json.push(',');
+        write_str(json, key);
+        json.push(':');
+        match value {
+            Value::Bool(v) => write!(json, "{v}")?,
+            Value::I64(v) => write!(json, "{v}")?,
+            Value::U64(v) => write!(json, "{v}")?,
+            Value::F64(v) if v.is_finite() => write!(json, "{v}")?,
+            Value::F64(_) => json.push_str("null"),
+            Value::Str(v) => write_str(json, v),
+            Value::Display(v) => write_str(json, &v.to_string()),
+            Value::Debug(v) => write_str(json, &format!("{v:?}")),
+        }
+    }
+
+    json.push('}');
+    Ok(())
+}
+
+fn write_str(json: &mut String, text: &str) {
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Returns the current unix time in milliseconds.
+pub(crate) fn unixtime_as_millis() -> u64 {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            use wasm_bindgen::prelude::*;
+
+            #[wasm_bindgen]
+            extern "C" {
+                #[wasm_bindgen(js_namespace = Date, js_name = now)]
+                fn date_now() -> f64;
+            }
+
+            date_now() as u64
+        } else {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64
+        }
+    }
+}
+
+/// Writes RFC 3339 UTC timestamp with millisecond precision.
+pub(crate) fn write_timestamp<W: Write>(out: &mut W, millis: u64) -> fmt::Result {
+    let secs = millis / 1000;
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let rem = secs % 86400;
+    write!(
+        out,
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        millis % 1000
+    )
+}
+
+/// Converts days since unix epoch to a (year, month, day) civil date.
+/// <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_format() {
+        let addr = std::net::Ipv4Addr::LOCALHOST;
+        let fields: [Field<'_>; 3] = [
+            ("peer", Value::Display(&addr)),
+            ("retries", Value::U64(3)),
+            ("note", Value::Str("a \"quoted\"\nline")),
+        ];
+
+        let mut json = String::new();
+        write_json(
+            &mut json,
+            1_704_110_400_250,
+            Some("my_app::net"),
+            Level::Warn,
+            &format_args!("connected in {}ms", 5),
+            &fields,
+        )
+        .unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"timestamp":"2024-01-01T12:00:00.250Z","level":"WARN","target":"my_app::net","message":"connected in 5ms","peer":"127.0.0.1","retries":3,"note":"a \"quoted\"\nline"}"#
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//!
+//! Structured key/value fields attached to log messages.
+//!
+//! Fields are supplied to the log macros before the message, separated
+//! from the message by `;`. Values prefixed with `%` are captured using
+//! their [`Display`](std::fmt::Display) implementation, values prefixed
+//! with `?` are captured using [`Debug`](std::fmt::Debug) and all other
+//! values must implement [`ToValue`].
+//!
+//! ```ignore
+//! log_info!(peer = %addr, op = ?op, retries = 3; "connection established");
+//! ```
+//!
+
+use std::fmt;
+
+/// Typed value of a structured log field.
+#[derive(Clone, Copy)]
+pub enum Value<'a> {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(&'a str),
+    Display(&'a dyn fmt::Display),
+    Debug(&'a dyn fmt::Debug),
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(v) => write!(f, "{v}"),
+            Value::I64(v) => write!(f, "{v}"),
+            Value::U64(v) => write!(f, "{v}"),
+            Value::F64(v) => write!(f, "{v}"),
+            Value::Str(v) => write!(f, "{v}"),
+            Value::Display(v) => write!(f, "{v}"),
+            Value::Debug(v) => write!(f, "{v:?}"),
+        }
+    }
+}
+
+impl fmt::Debug for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(v) => write!(f, "{v:?}"),
+            Value::Display(v) => write!(f, "{:?}", v.to_string()),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+/// A structured log field (key/value pair).
+pub type Field<'a> = (&'a str, Value<'a>);
+
+/// Conversion of a value into a structured log field [`Value`].
+pub trait ToValue {
+    fn to_value(&self) -> Value<'_>;
+}
+
+impl<T> ToValue for &T
+where
+    T: ToValue + ?Sized,
+{
+    fn to_value(&self) -> Value<'_> {
+        (**self).to_value()
+    }
+}
+
+impl ToValue for bool {
+    fn to_value(&self) -> Value<'_> {
+        Value::Bool(*self)
+    }
+}
+
+impl ToValue for str {
+    fn to_value(&self) -> Value<'_> {
+        Value::Str(self)
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value<'_> {
+        Value::Str(self.as_str())
+    }
+}
+
+macro_rules! impl_to_value {
+    ($variant:ident, $as:ty, $($type:ty),*) => {
+        $(
+            impl ToValue for $type {
+                fn to_value(&self) -> Value<'_> {
+                    Value::$variant(*self as $as)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_value!(I64, i64, i8, i16, i32, i64, isize);
+impl_to_value!(U64, u64, u8, u16, u32, u64, usize);
+impl_to_value!(F64, f64, f32, f64);
+
+/// Displays fields as space-separated `key=value` pairs
+/// (each pair is preceded by a space).
+pub struct DisplayFields<'a>(pub &'a [Field<'a>]);
+
+impl fmt::Display for DisplayFields<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in self.0.iter() {
+            write!(f, " {key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Munches `key = value` field definitions producing a list of
+/// [`Field`] expressions and invokes the supplied callback macro.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_fields {
+    (@munch $cb:ident ($($cb_args:tt)*) [$($fields:expr),*] $key:ident = % $value:expr ; $($arg:tt)+) => {
+        workflow_log::$cb!($($cb_args)* [$($fields,)* (stringify!($key), workflow_log::kv::Value::Display(&$value))] $($arg)+)
+    };
+    (@munch $cb:ident ($($cb_args:tt)*) [$($fields:expr),*] $key:ident = ? $value:expr ; $($arg:tt)+) => {
+        workflow_log::$cb!($($cb_args)* [$($fields,)* (stringify!($key), workflow_log::kv::Value::Debug(&$value))] $($arg)+)
+    };
+    (@munch $cb:ident ($($cb_args:tt)*) [$($fields:expr),*] $key:ident = % $value:expr , $($rest:tt)+) => {
+        workflow_log::__log_fields!(@munch $cb ($($cb_args)*) [$($fields,)* (stringify!($key), workflow_log::kv::Value::Display(&$value))] $($rest)+)
+    };
+    (@munch $cb:ident ($($cb_args:tt)*) [$($fields:expr),*] $key:ident = ? $value:expr , $($rest:tt)+) => {
+        workflow_log::__log_fields!(@munch $cb ($($cb_args)*) [$($fields,)* (stringify!($key), workflow_log::kv::Value::Debug(&$value))] $($rest)+)
+    };
+    (@munch $cb:ident ($($cb_args:tt)*) [$($fields:expr),*] $key:ident = $value:expr ; $($arg:tt)+) => {
+        workflow_log::$cb!($($cb_args)* [$($fields,)* (stringify!($key), workflow_log::kv::ToValue::to_value(&$value))] $($arg)+)
+    };
+    (@munch $cb:ident ($($cb_args:tt)*) [$($fields:expr),*] $key:ident = $value:expr , $($rest:tt)+) => {
+        workflow_log::__log_fields!(@munch $cb ($($cb_args)*) [$($fields,)* (stringify!($key), workflow_log::kv::ToValue::to_value(&$value))] $($rest)+)
+    };
+}
+
+/// Invokes the log implementation with the munched fields.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_with_fields {
+    ($level:ident, $target:expr, [$($fields:expr),*] $($arg:tt)+) => {
+        workflow_log::impls::log_impl(
+            workflow_log::Level::$level,
+            $target,
+            &[$($fields),*],
+            &format_args!($($arg)+),
+        )
+    };
+}
+
+/// Invokes the log implementation with the munched fields
+/// for messages without an explicit target.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_with_module_fields {
+    ($level:ident, $module:expr, [$($fields:expr),*] $($arg:tt)+) => {
+        workflow_log::impls::module_log_impl(
+            workflow_log::Level::$level,
+            $module,
+            &[$($fields),*],
+            &format_args!($($arg)+),
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kv_fields() {
+        let addr = std::net::Ipv4Addr::LOCALHOST;
+        let op = Some("ping");
+        let fields: [Field<'_>; 4] = [
+            ("peer", Value::Display(&addr)),
+            ("op", Value::Debug(&op)),
+            ("retries", 3u8.to_value()),
+            ("name", "node".to_value()),
+        ];
+        assert_eq!(
+            DisplayFields(&fields).to_string(),
+            " peer=127.0.0.1 op=Some(\"ping\") retries=3 name=node"
+        );
+        assert!(matches!(fields[2].1, Value::U64(3)));
+    }
+}
@@ -27,6 +27,27 @@
 //! - `log_warn()`
 //! - `log_error!()`
 //!
+//! # Structured fields
+//!
+//! Log macros accept optional structured key/value fields preceding
+//! the message, separated from it by `;` (see [`kv`]). Fields are
+//! delivered to sinks via [`Sink::write_with_fields`] and can be
+//! printed as JSON lines in native builds using [`set_log_format`].
+//!
+//! ```ignore
+//! log_info!(peer = %addr, op = ?op; "message");
+//! ```
+//!
+//! # Filtering log output
+//!
+//! Log output can be filtered per target using `RUST_LOG`-style directives
+//! such as `workflow_rpc=trace,my_app::db=debug,info` (see [`filter`]).
+//! Messages logged without an explicit `target:` use the module path
+//! of the call site as their target. Directives can be changed at runtime
+//! using [`set_log_directives`] or [`set_log_filter`], loaded from an
+//! environment variable using `set_log_directives_from_env("RUST_LOG")`
+//! in native builds or via `setLogLevel()` in WASM.
+//!
 //! # Redirecting log output
 //!
 //! This crate allows you to configure a log sink that will receive
@@ -66,6 +87,17 @@
 //!
 //! To can disable the sink by supplying [`Option::None`] to [`workflow_log::pipe`].  
 //!
+//! Additional sinks, each with its own level filter, can be registered
+//! using [`workflow_log::register_sink`]. Built-in sinks include an
+//! in-memory ring buffer, a callback forwarder and (with the `file-sink`
+//! feature) a rotating log file sink (see [`sink`] module).
+//!
+//! # Tracing
+//!
+//! The `tracing` feature enables a bridge with the `tracing` ecosystem:
+//! a `tracing_subscriber::Layer` forwarding `tracing` events to log sinks
+//! and an option for the log macros to emit `tracing` events (see [`tracing`]).
+//!
 
 extern crate self as workflow_log;
 
@@ -77,11 +109,29 @@ pub use self::console::*;
 
 pub mod levels;
 
+#[cfg(all(
+    not(any(target_arch = "bpf", target_arch = "wasm32")),
+    feature = "file-sink"
+))]
+pub mod file;
+#[cfg(not(target_arch = "bpf"))]
+pub mod filter;
+#[cfg(not(target_arch = "bpf"))]
+pub mod json;
+pub mod kv;
+#[cfg(all(not(target_arch = "bpf"), feature = "sink"))]
+pub mod sink;
+#[cfg(all(not(target_arch = "bpf"), feature = "tracing"))]
+pub mod tracing;
+
 pub mod prelude {
     pub use super::console::*;
     pub use super::log::{
-        log_debug, log_error, log_info, log_trace, log_warn, set_log_level, Level, LevelFilter,
+        log_debug, log_enabled, log_error, log_info, log_trace, log_warn, set_log_level, Level,
+        LevelFilter,
     };
+    #[cfg(not(target_arch = "bpf"))]
+    pub use super::log::{set_log_directives, set_log_filter, Filter};
 }
 
 #[cfg(test)]
@@ -110,4 +160,21 @@ mod test {
         workflow_log::pipe(Some(my_struct));
         log_trace!("test msg");
     }
+
+    #[test]
+    fn log_fields_test() {
+        let addr = std::net::Ipv4Addr::LOCALHOST;
+        let op = Some("ping");
+        log_info!(peer = %addr, op = ?op, retries = 3; "connected to {}", addr);
+        log_warn!(target: "workflow_log::test", elapsed = 1.5; "slow response");
+        // the console format is global, as such the JSON
+        // formatter is exercised directly
+        let json = format_json(
+            None,
+            Level::Error,
+            &format_args!("done"),
+            &[("ok", kv::ToValue::to_value(&true))],
+        );
+        assert!(json.ends_with(r#""level":"ERROR","message":"done","ok":true}"#));
+    }
 }
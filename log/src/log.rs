@@ -5,7 +5,6 @@ cfg_if! {
     if #[cfg(target_arch = "bpf")] {
         pub use workflow_log::levels::{ Level, LevelFilter };
     } else {
-        use std::sync::Arc;
         pub use log::{ Level, LevelFilter };
         use downcast::{ downcast_sync, AnySync };
         pub use hexplay::{self, HexViewBuilder};
@@ -67,17 +66,107 @@ cfg_if! {
         }
 
         /// A log sink trait that can be installed into the log subsystem using the [`pipe`]
-        /// function and will receive all log messages.
+        /// or [`register_sink`] functions and will receive all log messages.
+        /// Sinks return `true` to consume the message (preventing console output).
         pub trait Sink : AnySync {
             fn write(&self, target: Option<&str>, level : Level, args : &fmt::Arguments<'_>) -> bool;
-        }
 
-        struct SinkHandler {
-            // #[allow(dead_code)]
-            sink : Arc<dyn Sink>, // + Send + Sync + 'static>,
+            /// Receives log messages carrying structured key/value fields.
+            /// The default implementation discards the fields and invokes [`Sink::write`].
+            fn write_with_fields(&self, target: Option<&str>, level : Level, args : &fmt::Arguments<'_>, fields : &[Field<'_>]) -> bool {
+                let _ = fields;
+                self.write(target, level, args)
+            }
         }
 
         downcast_sync!(dyn Sink);
+
+        #[cfg(feature = "sink")]
+        pub use crate::sink::{pipe, register_sink, unregister_sink, SinkId};
+
+        pub use crate::filter::{Directive, Filter, FilterParseError};
+        pub use crate::json::{format_json, Format};
+        use crate::kv::Field;
+        use arc_swap::ArcSwap;
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::{Arc, LazyLock};
+
+        // most verbose level enabled by the filter or any of the registered sinks
+        static MAX_LEVEL : AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+        static FILTER_MAX_LEVEL : AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+        static SINK_MAX_LEVEL : AtomicUsize = AtomicUsize::new(LevelFilter::Off as usize);
+        static HAS_DIRECTIVES : AtomicBool = AtomicBool::new(false);
+        // read on every log call, as such the filter is swapped atomically instead of being locked
+        static FILTER : LazyLock<ArcSwap<Filter>> = LazyLock::new(|| ArcSwap::from_pointee(Filter::new(LevelFilter::Info)));
+
+        fn update_max_level() {
+            let max = FILTER_MAX_LEVEL.load(Ordering::Relaxed).max(SINK_MAX_LEVEL.load(Ordering::Relaxed));
+            MAX_LEVEL.store(max, Ordering::Relaxed);
+        }
+
+        /// Set the most verbose level enabled by sinks with their own filters
+        #[allow(dead_code)]
+        pub(crate) fn set_sink_max_level(level: LevelFilter) {
+            SINK_MAX_LEVEL.store(level as usize, Ordering::Relaxed);
+            update_max_level();
+        }
+
+        /// Returns true if the supplied level is enabled for at least
+        /// one target by the current [`Filter`] or any registered sink
+        #[inline(always)]
+        pub fn log_level_enabled(level: Level) -> bool {
+            level as usize <= MAX_LEVEL.load(Ordering::Relaxed)
+        }
+
+        /// Returns true if messages with the supplied target
+        /// and level pass the current [`Filter`]
+        #[inline(always)]
+        pub(crate) fn filter_enabled(target: Option<&str>, level: Level) -> bool {
+            level as usize <= FILTER_MAX_LEVEL.load(Ordering::Relaxed)
+                && (!HAS_DIRECTIVES.load(Ordering::Relaxed) || FILTER.load().enabled(target, level))
+        }
+
+        /// Returns true if messages with the supplied target and level
+        /// pass the current [`Filter`] or the filter of any registered sink
+        #[inline(always)]
+        pub fn log_enabled(target: Option<&str>, level: Level) -> bool {
+            if !log_level_enabled(level) {
+                return false;
+            }
+            cfg_if! {
+                if #[cfg(feature = "sink")] {
+                    filter_enabled(target, level) || crate::sink::sinks_enabled(target, level)
+                } else {
+                    filter_enabled(target, level)
+                }
+            }
+        }
+
+        /// Set the default [`LevelFilter`] retaining any per-target directives
+        pub fn set_log_level(level: LevelFilter) {
+            let filter = log_filter().with_default(level);
+            set_log_filter(filter);
+        }
+
+        /// Install the supplied [`Filter`] replacing the current filter
+        pub fn set_log_filter(filter: Filter) {
+            FILTER_MAX_LEVEL.store(filter.max_level() as usize, Ordering::Relaxed);
+            HAS_DIRECTIVES.store(!filter.directives().is_empty(), Ordering::Relaxed);
+            FILTER.store(Arc::new(filter));
+            update_max_level();
+        }
+
+        /// Returns a copy of the currently installed [`Filter`]
+        pub fn log_filter() -> Filter {
+            FILTER.load().as_ref().clone()
+        }
+
+        /// Parse and install `RUST_LOG`-style filter directives
+        /// such as `workflow_rpc=trace,my_app::db=debug,info`
+        pub fn set_log_directives(directives: &str) -> Result<(), FilterParseError> {
+            set_log_filter(Filter::parse(directives)?);
+            Ok(())
+        }
     }
 }
 
@@ -87,111 +176,57 @@ cfg_if! {
         pub fn log_level_enabled(_level: Level) -> bool {
             true
         }
-    } else if #[cfg(target_arch = "wasm32")] {
-        use wasm_bindgen::prelude::*;
-
-        static mut LEVEL_FILTER : LevelFilter = LevelFilter::Info;
         #[inline(always)]
-        pub fn log_level_enabled(level: Level) -> bool {
-            unsafe { LEVEL_FILTER >= level }
-        }
-        pub fn set_log_level(level: LevelFilter) {
-            unsafe { LEVEL_FILTER = level };
+        pub fn log_enabled(_target: Option<&str>, _level: Level) -> bool {
+            true
         }
+    } else if #[cfg(target_arch = "wasm32")] {
+        use wasm_bindgen::prelude::*;
 
         #[wasm_bindgen]
         extern "C" {
-            #[wasm_bindgen(typescript_type = r###""off" | "error" | "warn" | "info" | "debug" | "trace""###)]
+            #[wasm_bindgen(typescript_type = r###""off" | "error" | "warn" | "info" | "debug" | "trace" | string"###)]
             #[derive(Debug)]
             pub type LogLevelT;
         }
 
         #[doc="Set the logger log level using a string representation."]
         #[doc="Available variants are: 'off', 'error', 'warn', 'info', 'debug', 'trace'"]
+        #[doc="Per-target directives are also accepted, for example: 'workflow_rpc=trace,my_app::db=debug,info'"]
         #[doc="@category General"]
         #[wasm_bindgen(js_name = "setLogLevel")]
         // pub fn set_log_level_str(level: &str) {
         pub fn set_log_level_wasm(level: LogLevelT) {
             if let Some(level) = level.as_string() {
-                let level = match level.as_str() {
-                    "off" => LevelFilter::Off,
-                    "error" => LevelFilter::Error,
-                    "warn" => LevelFilter::Warn,
-                    "info" => LevelFilter::Info,
-                    "debug" => LevelFilter::Debug,
-                    "trace" => LevelFilter::Trace,
-                    _ => panic!("Invalid log level: {level}"),
-                };
-                set_log_level(level);
+                if let Err(err) = set_log_directives(&level) {
+                    panic!("Invalid log level: {err}");
+                }
             } else {
                 panic!("log level must be a string, received: {level:?}");
             }
         }
 
-        cfg_if! {
-            if #[cfg(feature = "sink")] {
-                use std::sync::Mutex;
-                static SINK : Mutex<Option<SinkHandler>> = Mutex::new(None);
-                // pub fn pipe(sink : Arc<dyn Sink + Send + Sync + 'static>) {
-                pub fn pipe(sink : Option<Arc<dyn Sink>>) {
-                    match sink {
-                        Some(sink) => { *SINK.lock().unwrap() = Some(SinkHandler { sink }); },
-                        None => { *SINK.lock().unwrap() = None; }
-                    }
-                }
-                #[inline(always)]
-                fn to_sink(target: Option<&str>, level : Level, args : &fmt::Arguments<'_>) -> bool {
-                    match SINK.lock().unwrap().as_ref() {
-                        Some(handler) => {
-                            handler.sink.write(target, level, args)
-                        },
-                        None => { false }
-                    }
-                }
-            }
-        }
-
     } else {
-        use std::sync::Mutex;
+        static JSON_FORMAT : AtomicBool = AtomicBool::new(false);
 
-        lazy_static::lazy_static! {
-            static ref LEVEL_FILTER : Mutex<LevelFilter> = Mutex::new(LevelFilter::Info);
-        }
-        #[inline(always)]
-        /// Returns true if the current log level is below the
-        /// currently set [`LevelFilter`]
-        pub fn log_level_enabled(level: Level) -> bool {
-            *LEVEL_FILTER.lock().unwrap() >= level
+        /// Select the console output [`Format`]. When [`Format::Json`]
+        /// is selected, messages are printed as JSON lines.
+        pub fn set_log_format(format: Format) {
+            JSON_FORMAT.store(format == Format::Json, Ordering::Relaxed);
         }
-        /// Enable filtering of log messages using the [`LevelFilter`]
-        pub fn set_log_level(level: LevelFilter) {
-            *LEVEL_FILTER.lock().unwrap() = level;
+
+        /// Returns the current console output [`Format`]
+        pub fn log_format() -> Format {
+            if JSON_FORMAT.load(Ordering::Relaxed) { Format::Json } else { Format::Text }
         }
-        cfg_if! {
-            if #[cfg(feature = "sink")] {
-                lazy_static::lazy_static! {
-                    static ref SINK : Mutex<Option<SinkHandler>> = Mutex::new(None);
-                }
-                // pub fn pipe(sink : Option<Arc<dyn Sink + Send + Sync + 'static>>) {
-                /// Receives an Option with an `Arc`ed [`Sink`] trait reference
-                /// and installs it as a log sink / receiver.
-                /// The sink can be later disabled by invoking `pipe(None)`
-                pub fn pipe(sink : Option<Arc<dyn Sink>>) {
-                    match sink {
-                        Some(sink) => { *SINK.lock().unwrap() = Some(SinkHandler { sink }); },
-                        None => { *SINK.lock().unwrap() = None; }
-                    }
 
-                }
-                #[inline(always)]
-                fn to_sink(target : Option<&str>, level : Level, args : &fmt::Arguments<'_>) -> bool {
-                    match SINK.lock().unwrap().as_ref() {
-                        Some(handler) => {
-                            handler.sink.write(target, level, args)
-                        },
-                        None => { false }
-                    }
-                }
+        /// Parse and install filter directives from the supplied environment
+        /// variable (typically `RUST_LOG`). The current filter is retained
+        /// if the variable is not set.
+        pub fn set_log_directives_from_env(var: &str) -> Result<(), FilterParseError> {
+            match std::env::var(var) {
+                Ok(directives) => set_log_directives(&directives),
+                Err(_) => Ok(()),
             }
         }
 
@@ -203,18 +238,17 @@ cfg_if! {
 
             impl log::Log for WorkflowLogger {
                 fn enabled(&self, metadata: &Metadata) -> bool {
-                    super::log_level_enabled(metadata.level())
+                    super::log_enabled(Some(metadata.target()), metadata.level())
                 }
 
                 fn log(&self, record: &Record) {
-                    if self.enabled(record.metadata()) {
-                        match record.metadata().level() {
-                            Level::Error => { super::error_impl(record.args()); },
-                            Level::Warn => { super::warn_impl(record.args()); },
-                            Level::Info => { super::info_impl(record.args()); },
-                            Level::Debug => { super::debug_impl(record.args()); },
-                            Level::Trace => { super::trace_impl(record.args()); },
-                        }
+                    let target = Some(record.target());
+                    match record.metadata().level() {
+                        Level::Error => { super::impls::error_impl(target, record.args()); },
+                        Level::Warn => { super::impls::warn_impl(target, record.args()); },
+                        Level::Info => { super::impls::info_impl(target, record.args()); },
+                        Level::Debug => { super::impls::debug_impl(target, record.args()); },
+                        Level::Trace => { super::impls::trace_impl(target, record.args()); },
                     }
                 }
 
@@ -254,175 +288,224 @@ pub mod wasm_log {
 
 pub mod impls {
     use super::*;
+    use crate::kv::Field;
 
+    /// Log a message with structured fields. Invoked by the log
+    /// macros when fields are supplied.
     #[inline(always)]
-    #[allow(unused_variables)]
-    pub fn error_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
-        if log_level_enabled(Level::Error) {
-            #[cfg(all(not(target_arch = "bpf"), feature = "sink"))]
-            {
-                if to_sink(target, Level::Error, args) {
-                    return;
-                }
-            }
-            cfg_if! {
-                if #[cfg(target_arch = "wasm32")] {
-                    workflow_log::wasm_log::error(&args.to_string());
-                } else if #[cfg(target_arch = "bpf")] {
-                    solana_program::log::sol_log(&args.to_string());
-                } else {
-                    println!("{args}");
-                }
+    pub fn log_impl(
+        level: Level,
+        target: Option<&str>,
+        fields: &[Field<'_>],
+        args: &fmt::Arguments<'_>,
+    ) {
+        emit(level, target, target, fields, args)
+    }
+
+    /// Log a message without an explicit target. Invoked by the log
+    /// macros when the target is omitted; the module path is used only
+    /// to match the filter directives (sinks receive no target).
+    #[inline(always)]
+    pub fn module_log_impl(
+        level: Level,
+        module: &str,
+        fields: &[Field<'_>],
+        args: &fmt::Arguments<'_>,
+    ) {
+        emit(level, None, Some(module), fields, args)
+    }
+
+    #[inline(always)]
+    fn emit(
+        level: Level,
+        target: Option<&str>,
+        filter_target: Option<&str>,
+        fields: &[Field<'_>],
+        args: &fmt::Arguments<'_>,
+    ) {
+        #[cfg(all(not(target_arch = "bpf"), feature = "tracing"))]
+        if crate::tracing::tracing_output_enabled() {
+            if log_level_enabled(level) && filter_enabled(filter_target, level) {
+                crate::tracing::emit(level, target, fields, args);
             }
+            return;
         }
+
+        dispatch(level, target, filter_target, fields, args)
     }
 
+    /// Deliver the message to the registered sinks and the console.
+    /// Filters are matched against `filter_target`, while sinks and
+    /// the console receive `target`.
     #[inline(always)]
     #[allow(unused_variables)]
-    pub fn warn_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
-        if log_level_enabled(Level::Warn) {
-            #[cfg(all(not(target_arch = "bpf"), feature = "sink"))]
-            {
-                if to_sink(target, Level::Warn, args) {
-                    return;
-                }
-            }
+    pub(crate) fn dispatch(
+        level: Level,
+        target: Option<&str>,
+        filter_target: Option<&str>,
+        fields: &[Field<'_>],
+        args: &fmt::Arguments<'_>,
+    ) {
+        if log_level_enabled(level) {
             cfg_if! {
-                if #[cfg(target_arch = "wasm32")] {
-                    workflow_log::wasm_log::warn(&args.to_string());
-                } else if #[cfg(target_arch = "bpf")] {
-                    solana_program::log::sol_log(&args.to_string());
+                if #[cfg(target_arch = "bpf")] {
+                    let enabled = true;
                 } else {
-                    println!("{args}");
+                    let enabled = filter_enabled(filter_target, level);
                 }
             }
-        }
-    }
-
-    #[inline(always)]
-    #[allow(unused_variables)]
-    pub fn info_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
-        if log_level_enabled(Level::Info) {
             #[cfg(all(not(target_arch = "bpf"), feature = "sink"))]
             {
-                if to_sink(target, Level::Info, args) {
+                if crate::sink::to_sinks(target, filter_target, level, args, fields, enabled) {
                     return;
                 }
             }
+            if !enabled {
+                return;
+            }
             cfg_if! {
                 if #[cfg(target_arch = "wasm32")] {
-                    workflow_log::wasm_log::log(&args.to_string());
+                    let text = format!("{args}{}", crate::kv::DisplayFields(fields));
+                    match level {
+                        Level::Error => workflow_log::wasm_log::error(&text),
+                        Level::Warn => workflow_log::wasm_log::warn(&text),
+                        _ => workflow_log::wasm_log::log(&text),
+                    }
                 } else if #[cfg(target_arch = "bpf")] {
                     solana_program::log::sol_log(&args.to_string());
                 } else {
-                    println!("{args}");
+                    match log_format() {
+                        Format::Json => println!("{}", format_json(target, level, args, fields)),
+                        Format::Text => println!("{args}{}", crate::kv::DisplayFields(fields)),
+                    }
                 }
             }
         }
     }
 
     #[inline(always)]
-    #[allow(unused_variables)]
+    pub fn error_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
+        log_impl(Level::Error, target, &[], args)
+    }
+
+    #[inline(always)]
+    pub fn warn_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
+        log_impl(Level::Warn, target, &[], args)
+    }
+
+    #[inline(always)]
+    pub fn info_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
+        log_impl(Level::Info, target, &[], args)
+    }
+
+    #[inline(always)]
     pub fn debug_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
-        if log_level_enabled(Level::Debug) {
-            #[cfg(all(not(target_arch = "bpf"), feature = "sink"))]
-            {
-                if to_sink(target, Level::Debug, args) {
-                    return;
-                }
-            }
-            cfg_if! {
-                if #[cfg(target_arch = "wasm32")] {
-                    workflow_log::wasm_log::log(&args.to_string());
-                } else if #[cfg(target_arch = "bpf")] {
-                    solana_program::log::sol_log(&args.to_string());
-                } else {
-                    println!("{args}");
-                }
-            }
-        }
+        log_impl(Level::Debug, target, &[], args)
     }
 
     #[inline(always)]
-    #[allow(unused_variables)]
     pub fn trace_impl(target: Option<&str>, args: &fmt::Arguments<'_>) {
-        if log_level_enabled(Level::Trace) {
-            #[cfg(all(not(target_arch = "bpf"), feature = "sink"))]
-            {
-                if to_sink(target, Level::Trace, args) {
-                    return;
-                }
-            }
-            cfg_if! {
-                if #[cfg(target_arch = "wasm32")] {
-                    workflow_log::wasm_log::log(&args.to_string());
-                } else if #[cfg(target_arch = "bpf")] {
-                    solana_program::log::sol_log(&args.to_string());
-                } else {
-                    println!("{args}");
-                }
-            }
-        }
+        log_impl(Level::Trace, target, &[], args)
     }
 }
 
 /// Format and log message with [`Level::Error`]
 #[macro_export]
 macro_rules! log_error {
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_fields (Error, Some($target),) [] $key = $($rest)+)
+    );
+
+    ($key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_module_fields (Error, module_path!(),) [] $key = $($rest)+)
+    );
+
     (target: $target:expr, $($arg:tt)+) => (
-        workflow_log::impls::error_impl(Some($target),&format_args!($($t)*))
+        workflow_log::impls::error_impl(Some($target),&format_args!($($arg)+))
     );
 
     ($($t:tt)*) => (
-        workflow_log::impls::error_impl(None,&format_args!($($t)*))
+        workflow_log::impls::module_log_impl(workflow_log::Level::Error, module_path!(), &[], &format_args!($($t)*))
     )
 }
 
 /// Format and log message with [`Level::Warn`]
 #[macro_export]
 macro_rules! log_warn {
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_fields (Warn, Some($target),) [] $key = $($rest)+)
+    );
+
+    ($key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_module_fields (Warn, module_path!(),) [] $key = $($rest)+)
+    );
+
     (target: $target:expr, $($arg:tt)+) => (
-        workflow_log::impls::warn_impl(Some($target),&format_args!($($t)*))
+        workflow_log::impls::warn_impl(Some($target),&format_args!($($arg)+))
     );
 
     ($($t:tt)*) => (
-        workflow_log::impls::warn_impl(None,&format_args!($($t)*))
+        workflow_log::impls::module_log_impl(workflow_log::Level::Warn, module_path!(), &[], &format_args!($($t)*))
     )
 }
 
 /// Format and log message with [`Level::Info`]
 #[macro_export]
 macro_rules! log_info {
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_fields (Info, Some($target),) [] $key = $($rest)+)
+    );
+
+    ($key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_module_fields (Info, module_path!(),) [] $key = $($rest)+)
+    );
+
     (target: $target:expr, $($arg:tt)+) => (
-        workflow_log::impls::info_impl(Some($target),&format_args!($($t)*))
+        workflow_log::impls::info_impl(Some($target),&format_args!($($arg)+))
     );
 
     ($($t:tt)*) => (
-        workflow_log::impls::info_impl(None,&format_args!($($t)*))
+        workflow_log::impls::module_log_impl(workflow_log::Level::Info, module_path!(), &[], &format_args!($($t)*))
     )
 }
 
 /// Format and log message with [`Level::Debug`]
 #[macro_export]
 macro_rules! log_debug {
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_fields (Debug, Some($target),) [] $key = $($rest)+)
+    );
+
+    ($key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_module_fields (Debug, module_path!(),) [] $key = $($rest)+)
+    );
+
     (target: $target:expr, $($arg:tt)+) => (
-        workflow_log::impls::debug_impl(Some($target),&format_args!($($t)*))
+        workflow_log::impls::debug_impl(Some($target),&format_args!($($arg)+))
     );
 
     ($($t:tt)*) => (
-        workflow_log::impls::debug_impl(None,&format_args!($($t)*))
+        workflow_log::impls::module_log_impl(workflow_log::Level::Debug, module_path!(), &[], &format_args!($($t)*))
     )
 }
 
 /// Format and log message with [`Level::Trace`]
 #[macro_export]
 macro_rules! log_trace {
+    (target: $target:expr, $key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_fields (Trace, Some($target),) [] $key = $($rest)+)
+    );
+
+    ($key:ident = $($rest:tt)+) => (
+        workflow_log::__log_fields!(@munch __log_with_module_fields (Trace, module_path!(),) [] $key = $($rest)+)
+    );
+
     (target: $target:expr, $($arg:tt)+) => (
-        workflow_log::impls::trace_impl(Some($target),&format_args!($($t)*))
+        workflow_log::impls::trace_impl(Some($target),&format_args!($($arg)+))
     );
 
     ($($t:tt)*) => (
-        workflow_log::impls::trace_impl(None,&format_args!($($t)*))
+        workflow_log::impls::module_log_impl(workflow_log::Level::Trace, module_path!(), &[], &format_args!($($t)*))
     )
 }
 
@@ -0,0 +1,385 @@
+//!
+//! Log sink registry and built-in sinks.
+//!
+//! Multiple sinks can be active at the same time, each with its own
+//! [`Filter`]. Sinks registered using [`register_sink`] receive messages
+//! passing their own filter (regardless of the global filter), while the
+//! sink installed using [`pipe`] follows the global filter.
+//!
+//! A message is printed to the console only if it passes the global
+//! filter and none of the sinks receiving it has consumed it (by
+//! returning `true` from [`Sink::write`]).
+//!
+//! Built-in sinks:
+//! - [`MemorySink`] - an in-memory ring buffer of recent log records,
+//!   useful for displaying logs in the application UI.
+//! - [`ForwardSink`] - forwards log records to a user-supplied callback,
+//!   for example to relay logs over RPC.
+//! - [`RotatingFileSink`](crate::file::RotatingFileSink) - native rotating
+//!   log file sink (requires the `file-sink` feature).
+//!
+
+use crate::filter::Filter;
+use crate::json::unixtime_as_millis;
+use crate::kv::Field;
+use crate::{Level, LevelFilter, Sink};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Identifier of a sink registered using [`register_sink`].
+pub type SinkId = u64;
+
+// id of the sink installed using `pipe()`
+const PIPE_SINK_ID: SinkId = 0;
+
+static NEXT_SINK_ID: AtomicU64 = AtomicU64::new(1);
+
+struct SinkEntry {
+    id: SinkId,
+    sink: Arc<dyn Sink>,
+    // `None` if the sink follows the global filter
+    filter: Option<Filter>,
+}
+
+impl SinkEntry {
+    #[inline(always)]
+    fn enabled(&self, target: Option<&str>, level: Level, global: bool) -> bool {
+        match &self.filter {
+            Some(filter) => filter.enabled(target, level),
+            None => global,
+        }
+    }
+}
+
+// copy-on-write list of sinks, allowing sinks to log
+// without holding the registry lock
+static SINKS: RwLock<Option<Arc<Vec<SinkEntry>>>> = RwLock::new(None);
+
+fn sinks() -> Option<Arc<Vec<SinkEntry>>> {
+    SINKS.read().unwrap().clone()
+}
+
+fn update<F>(f: F)
+where
+    F: FnOnce(&mut Vec<SinkEntry>),
+{
+    let mut sinks = SINKS.write().unwrap();
+    let mut entries = sinks
+        .take()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| SinkEntry {
+                    id: entry.id,
+                    sink: entry.sink.clone(),
+                    filter: entry.filter.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    f(&mut entries);
+
+    let max_level = entries
+        .iter()
+        .filter_map(|entry| entry.filter.as_ref().map(Filter::max_level))
+        .fold(LevelFilter::Off, std::cmp::max);
+    crate::log::set_sink_max_level(max_level);
+
+    if !entries.is_empty() {
+        sinks.replace(Arc::new(entries));
+    }
+}
+
+/// Receives an Option with an `Arc`ed [`Sink`] trait reference
+/// and installs it as a log sink / receiver following the global
+/// log filter. The sink can be later disabled by invoking `pipe(None)`.
+/// Sinks registered using [`register_sink`] are not affected.
+pub fn pipe(sink: Option<Arc<dyn Sink>>) {
+    update(|entries| {
+        entries.retain(|entry| entry.id != PIPE_SINK_ID);
+        if let Some(sink) = sink {
+            entries.insert(
+                0,
+                SinkEntry {
+                    id: PIPE_SINK_ID,
+                    sink,
+                    filter: None,
+                },
+            );
+        }
+    });
+}
+
+/// Register an additional sink receiving messages passing the supplied
+/// filter (a [`Filter`] or a [`LevelFilter`]). Returns the sink id that
+/// can be used to remove the sink using [`unregister_sink`].
+pub fn register_sink<F>(sink: Arc<dyn Sink>, filter: F) -> SinkId
+where
+    F: Into<Filter>,
+{
+    let id = NEXT_SINK_ID.fetch_add(1, Ordering::Relaxed);
+    let filter = Some(filter.into());
+    update(|entries| entries.push(SinkEntry { id, sink, filter }));
+    id
+}
+
+/// Remove a sink registered using [`register_sink`].
+/// Returns `false` if the sink was not found.
+pub fn unregister_sink(id: SinkId) -> bool {
+    let mut found = false;
+    update(|entries| {
+        let len = entries.len();
+        entries.retain(|entry| entry.id != id);
+        found = entries.len() != len;
+    });
+    found
+}
+
+/// Returns true if any of the sinks with their own
+/// filter accept the supplied target and level.
+pub(crate) fn sinks_enabled(target: Option<&str>, level: Level) -> bool {
+    sinks()
+        .map(|entries| {
+            entries.iter().any(|entry| {
+                entry
+                    .filter
+                    .as_ref()
+                    .map(|filter| filter.enabled(target, level))
+                    .unwrap_or(false)
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Deliver the message to all sinks accepting it. Returns
+/// true if the message has been consumed by any of the sinks.
+#[inline(always)]
+pub(crate) fn to_sinks(
+    target: Option<&str>,
+    filter_target: Option<&str>,
+    level: Level,
+    args: &fmt::Arguments<'_>,
+    fields: &[Field<'_>],
+    global: bool,
+) -> bool {
+    let Some(entries) = sinks() else {
+        return false;
+    };
+
+    let mut consumed = false;
+    for entry in entries.iter() {
+        if entry.enabled(filter_target, level, global) {
+            consumed |= entry.sink.write_with_fields(target, level, args, fields);
+        }
+    }
+    consumed
+}
+
+/// An owned log record captured by [`MemorySink`] and [`ForwardSink`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// unix time in milliseconds
+    pub timestamp: u64,
+    pub level: Level,
+    pub target: Option<String>,
+    pub message: String,
+    /// structured fields formatted using their `Display` representation
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    pub fn new(
+        target: Option<&str>,
+        level: Level,
+        args: &fmt::Arguments<'_>,
+        fields: &[Field<'_>],
+    ) -> Self {
+        LogRecord {
+            timestamp: unixtime_as_millis(),
+            level,
+            target: target.map(String::from),
+            message: args.to_string(),
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::json::write_timestamp(f, self.timestamp)?;
+        write!(f, " {:<5}", self.level.as_str())?;
+        if let Some(target) = &self.target {
+            write!(f, " {target}:")?;
+        }
+        write!(f, " {}", self.message)?;
+        for (key, value) in self.fields.iter() {
+            write!(f, " {key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// In-memory ring buffer retaining the most recent log records.
+pub struct MemorySink {
+    capacity: usize,
+    records: Mutex<VecDeque<LogRecord>>,
+}
+
+impl MemorySink {
+    pub fn new(capacity: usize) -> Self {
+        MemorySink {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns a copy of the retained records (oldest first).
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.lock().unwrap().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+}
+
+impl Sink for MemorySink {
+    fn write(&self, target: Option<&str>, level: Level, args: &fmt::Arguments<'_>) -> bool {
+        self.write_with_fields(target, level, args, &[])
+    }
+
+    fn write_with_fields(
+        &self,
+        target: Option<&str>,
+        level: Level,
+        args: &fmt::Arguments<'_>,
+        fields: &[Field<'_>],
+    ) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+
+        let record = LogRecord::new(target, level, args, fields);
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+        false
+    }
+}
+
+/// Forwards log records to the supplied callback.
+pub struct ForwardSink {
+    #[allow(clippy::type_complexity)]
+    callback: Box<dyn Fn(LogRecord) + Send + Sync>,
+}
+
+impl ForwardSink {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(LogRecord) + Send + Sync + 'static,
+    {
+        ForwardSink {
+            callback: Box::new(callback),
+        }
+    }
+}
+
+impl Sink for ForwardSink {
+    fn write(&self, target: Option<&str>, level: Level, args: &fmt::Arguments<'_>) -> bool {
+        self.write_with_fields(target, level, args, &[])
+    }
+
+    fn write_with_fields(
+        &self,
+        target: Option<&str>,
+        level: Level,
+        args: &fmt::Arguments<'_>,
+        fields: &[Field<'_>],
+    ) -> bool {
+        (self.callback)(LogRecord::new(target, level, args, fields));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_debug;
+
+    #[test]
+    fn test_sink_registry() {
+        // sinks are global, as such they only accept the test targets
+        // to remain unaffected by other tests logging in parallel
+        let memory = Arc::new(MemorySink::new(2));
+        let forwarded = Arc::new(Mutex::new(Vec::new()));
+        let forward = {
+            let forwarded = forwarded.clone();
+            Arc::new(ForwardSink::new(move |record| {
+                forwarded.lock().unwrap().push(record)
+            }))
+        };
+
+        let memory_id = register_sink(
+            memory.clone(),
+            Filter::new(LevelFilter::Off).with_directive("sink_test", LevelFilter::Debug),
+        );
+        let forward_id = register_sink(
+            forward,
+            Filter::new(LevelFilter::Off).with_directive("sink_test::forward", LevelFilter::Trace),
+        );
+
+        log_debug!(target: "sink_test", "first");
+        log_debug!(target: "sink_test::forward", n = 2; "second");
+        log_debug!(target: "sink_test::other", "third");
+
+        let records = memory.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].message, "second");
+        assert_eq!(records[0].fields, vec![("n".to_string(), "2".to_string())]);
+        assert_eq!(records[1].target.as_deref(), Some("sink_test::other"));
+        assert_eq!(forwarded.lock().unwrap().len(), 1);
+
+        assert!(unregister_sink(memory_id));
+        assert!(!unregister_sink(memory_id));
+        log_debug!(target: "sink_test::forward", "fourth");
+        assert_eq!(memory.records().len(), 2);
+        assert_eq!(forwarded.lock().unwrap().len(), 2);
+        assert!(unregister_sink(forward_id));
+    }
+
+    #[test]
+    fn test_sink_untargeted() {
+        // untargeted messages are filtered by the module path
+        // but are delivered to sinks without a target
+        let memory = Arc::new(MemorySink::new(4));
+        let id = register_sink(
+            memory.clone(),
+            Filter::new(LevelFilter::Off).with_directive(module_path!(), LevelFilter::Debug),
+        );
+        log_debug!("untargeted");
+        log_debug!(n = 1; "with fields");
+        crate::log_trace!("filtered");
+        assert!(unregister_sink(id));
+
+        let records = memory.records();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|record| record.target.is_none()));
+        assert_eq!(records[1].message, "with fields");
+    }
+}
@@ -0,0 +1,413 @@
+//!
+//! Bidirectional bridge between `workflow_log` and the
+//! [`tracing`](https://crates.io/crates/tracing) ecosystem
+//! (requires the `tracing` feature).
+//!
+//! - [`layer()`] returns a [`tracing_subscriber::Layer`] forwarding
+//!   `tracing` events (including the fields of the enclosing spans)
+//!   to `workflow_log` sinks and console output.
+//! - [`set_tracing_output(true)`](set_tracing_output) makes `log_*!()`
+//!   macros emit `tracing` events instead of writing to sinks, allowing
+//!   the installed `tracing` subscriber to capture them together with
+//!   the current span context. Events retain the original target and
+//!   structured fields are recorded as `tracing` event fields, as such
+//!   they are available to subscribers and filters.
+//!
+//! Both directions can be enabled at the same time; events emitted
+//! by `log_*!()` macros and received by the layer are delivered to
+//! sinks without being re-emitted.
+//!
+//! ```ignore
+//! use tracing_subscriber::prelude::*;
+//!
+//! tracing_subscriber::registry()
+//!     .with(workflow_log::tracing::layer())
+//!     .init();
+//! ```
+//!
+
+use crate::impls::dispatch;
+use crate::kv::{DisplayFields, Field, Value};
+use crate::Level;
+use ::tracing::callsite::{self, Callsite};
+use ::tracing::field::{self as tracing_field, Field as TracingField, FieldSet, Visit};
+use ::tracing::metadata::Kind;
+use ::tracing::span::{Attributes, Id, Record};
+use ::tracing::subscriber::Interest;
+use ::tracing::{Event, Metadata, Subscriber};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Target of `tracing` events emitted by the `log_*!()` macros
+/// for messages without a target.
+pub const TARGET: &str = "workflow_log";
+/// Maximum number of structured fields recorded as `tracing` event
+/// fields; the remaining fields are appended to the message.
+pub const MAX_TRACING_FIELDS: usize = 31;
+
+static TRACING_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable emission of `tracing` events by the `log_*!()` macros.
+pub fn set_tracing_output(enabled: bool) {
+    TRACING_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if `log_*!()` macros emit `tracing` events.
+#[inline(always)]
+pub fn tracing_output_enabled() -> bool {
+    TRACING_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Callsite of `tracing` events emitted by the `log_*!()` macros.
+/// `tracing` requires static metadata (including field names), as such
+/// a callsite is created (and retained) for each distinct combination
+/// of level, target and field names.
+struct LogCallsite {
+    metadata: OnceLock<Metadata<'static>>,
+}
+
+impl Callsite for LogCallsite {
+    fn set_interest(&self, _interest: Interest) {}
+
+    fn metadata(&self) -> &Metadata<'_> {
+        self.metadata.get().expect("log callsite metadata")
+    }
+}
+
+type CallsiteKey = (Level, String, Vec<String>);
+
+fn intern(text: &str) -> &'static str {
+    Box::leak(text.to_string().into_boxed_str())
+}
+
+/// Returns the metadata of the callsite for the supplied level, target and field names.
+fn callsite_metadata(level: Level, target: &str, names: &[&str]) -> &'static Metadata<'static> {
+    static CALLSITES: OnceLock<Mutex<HashMap<CallsiteKey, &'static LogCallsite>>> = OnceLock::new();
+
+    let key = (
+        level,
+        target.to_string(),
+        names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>(),
+    );
+    let mut callsites = CALLSITES.get_or_init(Default::default).lock().unwrap();
+    if let Some(callsite) = callsites.get(&key) {
+        return callsite.metadata.get().unwrap();
+    }
+
+    let callsite: &'static LogCallsite = Box::leak(Box::new(LogCallsite {
+        metadata: OnceLock::new(),
+    }));
+    let field_names: &'static [&'static str] = Box::leak(
+        std::iter::once("message")
+            .chain(names.iter().map(|name| intern(name)))
+            .collect::<Vec<_>>()
+            .into_boxed_slice(),
+    );
+    let level = match level {
+        Level::Error => ::tracing::Level::ERROR,
+        Level::Warn => ::tracing::Level::WARN,
+        Level::Info => ::tracing::Level::INFO,
+        Level::Debug => ::tracing::Level::DEBUG,
+        Level::Trace => ::tracing::Level::TRACE,
+    };
+    let metadata = Metadata::new(
+        "log event",
+        intern(target),
+        level,
+        None,
+        None,
+        None,
+        FieldSet::new(field_names, callsite::Identifier(callsite)),
+        Kind::EVENT,
+    );
+    callsite.metadata.set(metadata).ok();
+    callsite::register(callsite);
+    callsites.insert(key, callsite);
+    callsite.metadata.get().unwrap()
+}
+
+/// Convert a field value to a `tracing` value.
+fn tracing_value<'a>(value: &'a Value<'a>) -> Box<dyn ::tracing::Value + 'a> {
+    match value {
+        Value::Bool(v) => Box::new(*v),
+        Value::I64(v) => Box::new(*v),
+        Value::U64(v) => Box::new(*v),
+        Value::F64(v) => Box::new(*v),
+        Value::Str(v) => Box::new(*v),
+        Value::Display(v) => Box::new(tracing_field::display(*v)),
+        Value::Debug(v) => Box::new(tracing_field::debug(*v)),
+    }
+}
+
+/// Emit a `tracing` event for the supplied log message.
+pub(crate) fn emit(
+    level: Level,
+    target: Option<&str>,
+    fields: &[Field<'_>],
+    args: &fmt::Arguments<'_>,
+) {
+    let target = target.filter(|target| !target.is_empty()).unwrap_or(TARGET);
+    let (fields, overflow) = fields.split_at(fields.len().min(MAX_TRACING_FIELDS));
+    let names = fields.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    let metadata = callsite_metadata(level, target, &names);
+
+    if *metadata.level() > ::tracing::level_filters::LevelFilter::current()
+        || !::tracing::dispatcher::get_default(|dispatch| dispatch.enabled(metadata))
+    {
+        return;
+    }
+
+    let message = if overflow.is_empty() {
+        None
+    } else {
+        Some(format!("{args}{}", DisplayFields(overflow)))
+    };
+    let message: &dyn ::tracing::Value = match &message {
+        Some(message) => message,
+        None => args,
+    };
+    let values = fields
+        .iter()
+        .map(|(_, value)| tracing_value(value))
+        .collect::<Vec<_>>();
+
+    let field_set = metadata.fields();
+    let tracing_fields = field_set.iter().collect::<Vec<_>>();
+    let mut entries: [(&TracingField, Option<&dyn ::tracing::Value>); MAX_TRACING_FIELDS + 1] =
+        [(&tracing_fields[0], None); MAX_TRACING_FIELDS + 1];
+    entries[0].1 = Some(message);
+    for (index, value) in values.iter().enumerate() {
+        entries[index + 1] = (&tracing_fields[index + 1], Some(value.as_ref()));
+    }
+    Event::dispatch(metadata, &field_set.value_set(&entries));
+}
+
+fn level_from_tracing(level: &::tracing::Level) -> Level {
+    match *level {
+        ::tracing::Level::ERROR => Level::Error,
+        ::tracing::Level::WARN => Level::Warn,
+        ::tracing::Level::INFO => Level::Info,
+        ::tracing::Level::DEBUG => Level::Debug,
+        ::tracing::Level::TRACE => Level::Trace,
+    }
+}
+
+enum OwnedValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+}
+
+impl OwnedValue {
+    fn as_value(&self) -> Value<'_> {
+        match self {
+            OwnedValue::Bool(v) => Value::Bool(*v),
+            OwnedValue::I64(v) => Value::I64(*v),
+            OwnedValue::U64(v) => Value::U64(*v),
+            OwnedValue::F64(v) => Value::F64(*v),
+            OwnedValue::Str(v) => Value::Str(v),
+        }
+    }
+}
+
+/// Collects event fields, separating the message.
+#[derive(Default)]
+struct EventVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, OwnedValue)>,
+}
+
+impl EventVisitor {
+    fn record(&mut self, field: &TracingField, value: OwnedValue) {
+        match (field.name(), value) {
+            ("message", OwnedValue::Str(message)) => self.message = Some(message),
+            (name, value) => self.fields.push((name, value)),
+        }
+    }
+}
+
+impl Visit for EventVisitor {
+    fn record_bool(&mut self, field: &TracingField, value: bool) {
+        self.record(field, OwnedValue::Bool(value));
+    }
+
+    fn record_i64(&mut self, field: &TracingField, value: i64) {
+        self.record(field, OwnedValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &TracingField, value: u64) {
+        self.record(field, OwnedValue::U64(value));
+    }
+
+    fn record_f64(&mut self, field: &TracingField, value: f64) {
+        self.record(field, OwnedValue::F64(value));
+    }
+
+    fn record_str(&mut self, field: &TracingField, value: &str) {
+        self.record(field, OwnedValue::Str(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &TracingField, value: &dyn fmt::Debug) {
+        self.record(field, OwnedValue::Str(format!("{value:?}")));
+    }
+}
+
+/// Span fields formatted as `key=value` pairs, stored in span extensions.
+struct SpanFields(String);
+
+struct SpanVisitor<'a>(&'a mut String);
+
+impl Visit for SpanVisitor<'_> {
+    fn record_str(&mut self, field: &TracingField, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &TracingField, value: &dyn fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        let _ = write!(self.0, "{}={value:?}", field.name());
+    }
+}
+
+/// [`tracing_subscriber::Layer`] forwarding `tracing` events to `workflow_log`.
+#[derive(Default)]
+pub struct WorkflowLogLayer {
+    _private: (),
+}
+
+/// Create a [`WorkflowLogLayer`].
+pub fn layer() -> WorkflowLogLayer {
+    WorkflowLogLayer::default()
+}
+
+impl<S> Layer<S> for WorkflowLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = String::new();
+            attrs.record(&mut SpanVisitor(&mut fields));
+            span.extensions_mut().insert(SpanFields(fields));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+                values.record(&mut SpanVisitor(fields));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = level_from_tracing(metadata.level());
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        // enclosing spans formatted as `outer{a=1}:inner`
+        let mut spans = String::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if !spans.is_empty() {
+                    spans.push(':');
+                }
+                spans.push_str(span.name());
+                if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>() {
+                    if !fields.is_empty() {
+                        let _ = write!(spans, "{{{fields}}}");
+                    }
+                }
+            }
+        }
+
+        let target = metadata.target();
+        let target = (!target.is_empty()).then_some(target);
+
+        let mut fields: Vec<Field<'_>> = Vec::with_capacity(visitor.fields.len() + 1);
+        if !spans.is_empty() {
+            fields.push(("span", Value::Str(&spans)));
+        }
+        fields.extend(
+            visitor
+                .fields
+                .iter()
+                .map(|(name, value)| (*name, value.as_value())),
+        );
+
+        let message = visitor.message.as_deref().unwrap_or_default();
+        dispatch(level, target, target, &fields, &format_args!("{message}"));
+    }
+}
+
+#[cfg(all(test, feature = "sink"))]
+mod tests {
+    use super::*;
+    use crate::sink::{register_sink, unregister_sink, MemorySink};
+    use crate::LevelFilter;
+    use std::sync::Arc;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_tracing_bridge() {
+        let memory = Arc::new(MemorySink::new(8));
+        let id = register_sink(
+            memory.clone(),
+            crate::Filter::new(LevelFilter::Off)
+                .with_directive("workflow_log::tracing", LevelFilter::Info),
+        );
+
+        let subscriber = tracing_subscriber::registry().with(layer());
+        ::tracing::subscriber::with_default(subscriber, || {
+            let span = ::tracing::info_span!("request", id = 7);
+            let _guard = span.enter();
+            ::tracing::info!(peer = "127.0.0.1", "connected");
+
+            emit(
+                Level::Info,
+                Some(module_path!()),
+                &[("retries", Value::U64(3))],
+                &format_args!("via tracing"),
+            );
+        });
+
+        let records = memory.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].message, "connected");
+        assert_eq!(
+            records[0].fields,
+            vec![
+                ("span".to_string(), "request{id=7}".to_string()),
+                ("peer".to_string(), "127.0.0.1".to_string())
+            ]
+        );
+        assert_eq!(
+            records[1].target.as_deref(),
+            Some("workflow_log::tracing::tests")
+        );
+        assert_eq!(records[1].message, "via tracing");
+        assert_eq!(
+            records[1].fields,
+            vec![
+                ("span".to_string(), "request{id=7}".to_string()),
+                ("retries".to_string(), "3".to_string())
+            ]
+        );
+
+        assert!(unregister_sink(id));
+    }
+}
@@ -61,6 +61,41 @@ extern "C" {
 
     #[wasm_bindgen(catch, js_name = statSync, method)]
     fn fs_stat_sync(this: &Fs, path: &str) -> std::result::Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = openSync, method)]
+    fn fs_open_sync(this: &Fs, path: &str, flags: &str) -> std::result::Result<u32, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = writeFileSync, method)]
+    fn fs_write_fd_sync(
+        this: &Fs,
+        fd: u32,
+        data: JsValue,
+        options: Object,
+    ) -> std::result::Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_name = fsyncSync, method)]
+    fn fs_fsync_sync(this: &Fs, fd: u32) -> std::result::Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_name = closeSync, method)]
+    fn fs_close_sync(this: &Fs, fd: u32) -> std::result::Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_name = copyFileSync, method)]
+    fn fs_copy_file_sync(this: &Fs, from: &str, to: &str) -> std::result::Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_name = watch, method)]
+    fn fs_watch(
+        this: &Fs,
+        path: &str,
+        options: Object,
+        listener: &js_sys::Function,
+    ) -> std::result::Result<FsWatcher, JsValue>;
+
+    #[wasm_bindgen(extends = Object)]
+    #[derive(Clone)]
+    pub type FsWatcher;
+
+    #[wasm_bindgen(method)]
+    pub fn close(this: &FsWatcher);
 }
 
 unsafe impl Send for Fs {}
@@ -124,3 +159,37 @@ pub fn rename_sync(from: &str, to: &str) -> std::result::Result<(), JsValue> {
 pub fn stat_sync(path: &str) -> std::result::Result<JsValue, JsValue> {
     FS.fs_stat_sync(path)
 }
+
+#[inline(always)]
+pub fn open_sync(path: &str, flags: &str) -> std::result::Result<u32, JsValue> {
+    FS.fs_open_sync(path, flags)
+}
+
+#[inline(always)]
+pub fn write_fd_sync(fd: u32, data: JsValue, options: Object) -> std::result::Result<(), JsValue> {
+    FS.fs_write_fd_sync(fd, data, options)
+}
+
+#[inline(always)]
+pub fn fsync_sync(fd: u32) -> std::result::Result<(), JsValue> {
+    FS.fs_fsync_sync(fd)
+}
+
+#[inline(always)]
+pub fn close_sync(fd: u32) -> std::result::Result<(), JsValue> {
+    FS.fs_close_sync(fd)
+}
+
+#[inline(always)]
+pub fn copy_file_sync(from: &str, to: &str) -> std::result::Result<(), JsValue> {
+    FS.fs_copy_file_sync(from, to)
+}
+
+#[inline(always)]
+pub fn watch(
+    path: &str,
+    options: Object,
+    listener: &js_sys::Function,
+) -> std::result::Result<FsWatcher, JsValue> {
+    FS.fs_watch(path, options, listener)
+}
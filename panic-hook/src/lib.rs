@@ -12,6 +12,13 @@
 //! a full-screen DIV element dumping the stack info in it.  This is useful when debugging on devices
 //! without access to console output.
 //!
+//! ## Crash reports
+//!
+//! [`CrashReporter`] captures panic reports (including the backtrace and
+//! recent log lines) and persists them so that the application can
+//! retrieve them on the next launch using [`take_last_crash_report()`].
+//! See the [`report`] module for details.
+//!
 //! ## Error.stackTraceLimit
 //!
 //! Many browsers only capture the top 10 frames of a stack trace. In rust programs this is less likely to be enough. To see more frames, you can set the non-standard value `Error.stackTraceLimit`. For more information see the [MDN Web Docs](https://developer.mozilla.org/en-US/docs/Web/JavaScript/Microsoft_Extensions/Error.stackTraceLimit) or [v8 docs](https://v8.dev/docs/stack-trace-api).
@@ -22,6 +29,9 @@ extern crate cfg_if;
 
 use std::panic;
 
+pub mod report;
+pub use report::{take_last_crash_report, CrashReport, CrashReporter, ReportStorage};
+
 cfg_if! {
     if #[cfg(target_arch = "wasm32")] {
         extern crate wasm_bindgen;
@@ -0,0 +1,372 @@
+//!
+//! Panic report capture and persistence.
+//!
+//! [`CrashReporter`] installs a panic hook capturing a [`CrashReport`]
+//! containing the panic message, location, backtrace, thread name,
+//! application version and the most recent log lines (retained in a
+//! `workflow_log` ring buffer sink). The report is persisted using the
+//! supplied [`ReportStorage`], allowing the application to retrieve it
+//! using [`take_last_crash_report()`] on the next launch and offer the
+//! user to submit it.
+//!
+//! `workflow_store::crash::CrashReportStore` (available with the
+//! `crash-report` feature of `workflow-store`) provides a [`ReportStorage`]
+//! implementation storing the report in a file on native platforms and
+//! in the localstorage in the browser.
+//!
+//! The previously installed panic hook (such as the one installed by
+//! [`set_once()`](crate::set_once)) is invoked after the report has been
+//! captured.
+//!
+//! ```ignore
+//! CrashReporter::new()
+//!     .with_app_version(env!("CARGO_PKG_VERSION"))
+//!     .with_log_lines(100)
+//!     .with_storage(Arc::new(CrashReportStore::new("~/.app/crash.json")))
+//!     .install();
+//!
+//! if let Some(report) = take_last_crash_report() {
+//!     // offer to submit the report
+//! }
+//! ```
+//!
+
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fmt;
+use std::panic::{self, PanicHookInfo};
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+use workflow_log::{
+    log_error, register_sink, sink::MemorySink, unregister_sink, LevelFilter, SinkId,
+};
+
+/// Information captured when the application panics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub thread: Option<String>,
+    pub app_version: Option<String>,
+    /// unix time in milliseconds
+    pub timestamp: u64,
+    /// most recent log lines (oldest first)
+    pub logs: Vec<String>,
+}
+
+impl CrashReport {
+    fn capture(info: &PanicHookInfo<'_>, app_version: Option<String>, logs: Vec<String>) -> Self {
+        let payload = info.payload();
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Box<dyn Any>".to_string()
+        };
+
+        CrashReport {
+            message,
+            location: info.location().map(|location| location.to_string()),
+            backtrace: backtrace(),
+            thread: std::thread::current().name().map(String::from),
+            app_version,
+            timestamp: unixtime_as_millis(),
+            logs,
+        }
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "panicked")?;
+        if let Some(thread) = &self.thread {
+            write!(f, " in thread '{thread}'")?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        writeln!(f, ":\n{}", self.message)?;
+        if let Some(app_version) = &self.app_version {
+            writeln!(f, "\nVersion: {app_version}")?;
+        }
+        writeln!(f, "\nBacktrace:\n{}", self.backtrace)?;
+        if !self.logs.is_empty() {
+            writeln!(f, "\nLogs:")?;
+            for line in self.logs.iter() {
+                writeln!(f, "{line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Persistent storage for [`CrashReport`]. Methods are invoked
+/// from the panic hook and as such must be synchronous.
+pub trait ReportStorage: Send + Sync {
+    /// Store the report, replacing any previously stored report.
+    fn store(&self, report: &CrashReport) -> Result<(), String>;
+    /// Load and remove the stored report.
+    fn take(&self) -> Result<Option<CrashReport>, String>;
+}
+
+struct Config {
+    app_version: Option<String>,
+    memory: Option<(SinkId, Arc<MemorySink>)>,
+    storage: Option<Arc<dyn ReportStorage>>,
+}
+
+static CONFIG: Mutex<Option<Config>> = Mutex::new(None);
+// report captured during the current session
+static LAST_REPORT: Mutex<Option<CrashReport>> = Mutex::new(None);
+
+thread_local! {
+    static PANICKING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // the hook must remain functional if a previous panic poisoned the lock
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+///
+/// # CrashReporter
+///
+/// Builder configuring and installing the crash report panic hook.
+///
+pub struct CrashReporter {
+    app_version: Option<String>,
+    log_lines: usize,
+    log_level: LevelFilter,
+    storage: Option<Arc<dyn ReportStorage>>,
+}
+
+impl Default for CrashReporter {
+    fn default() -> Self {
+        CrashReporter {
+            app_version: None,
+            log_lines: 64,
+            log_level: LevelFilter::Info,
+            storage: None,
+        }
+    }
+}
+
+impl CrashReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_app_version(mut self, app_version: &str) -> Self {
+        self.app_version = Some(app_version.to_string());
+        self
+    }
+
+    /// Number of recent log lines included in the report
+    /// (64 by default, `0` disables log capture).
+    pub fn with_log_lines(mut self, log_lines: usize) -> Self {
+        self.log_lines = log_lines;
+        self
+    }
+
+    /// Maximum level of the log lines retained for
+    /// the report ([`LevelFilter::Info`] by default).
+    pub fn with_log_level(mut self, log_level: LevelFilter) -> Self {
+        self.log_level = log_level;
+        self
+    }
+
+    /// Storage used to persist the report. If not supplied, the report
+    /// is only available during the current session.
+    pub fn with_storage(mut self, storage: Arc<dyn ReportStorage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Install the crash report panic hook. The hook is installed only
+    /// once; subsequent invocations replace the reporter configuration.
+    pub fn install(self) {
+        let memory = (self.log_lines > 0).then(|| {
+            let sink = Arc::new(MemorySink::new(self.log_lines));
+            (register_sink(sink.clone(), self.log_level), sink)
+        });
+
+        let config = Config {
+            app_version: self.app_version,
+            memory,
+            storage: self.storage,
+        };
+
+        let previous = lock(&CONFIG).replace(config);
+        if let Some((id, _)) = previous.and_then(|config| config.memory) {
+            unregister_sink(id);
+        }
+
+        static SET_HOOK: Once = Once::new();
+        SET_HOOK.call_once(|| {
+            let next = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                hook(info);
+                next(info);
+            }));
+        });
+    }
+}
+
+fn hook(info: &PanicHookInfo<'_>) {
+    // a panic while capturing the report must not recurse
+    if PANICKING.with(|panicking| panicking.replace(true)) {
+        return;
+    }
+
+    let (app_version, memory, storage) = match lock(&CONFIG).as_ref() {
+        Some(config) => (
+            config.app_version.clone(),
+            config.memory.as_ref().map(|(_, sink)| sink.clone()),
+            config.storage.clone(),
+        ),
+        None => (None, None, None),
+    };
+
+    let logs = memory
+        .map(|sink| {
+            sink.records()
+                .iter()
+                .map(|record| record.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let report = CrashReport::capture(info, app_version, logs);
+    if let Some(storage) = storage {
+        if let Err(err) = storage.store(&report) {
+            log_error!("unable to store crash report: {err}");
+        }
+    }
+    lock(&LAST_REPORT).replace(report);
+
+    PANICKING.with(|panicking| panicking.set(false));
+}
+
+/// Returns the crash report captured during the current session or,
+/// if none, the report persisted by the configured [`ReportStorage`]
+/// (typically during the previous session). The returned report is
+/// removed from the storage, as such it is returned only once; a stored
+/// report that differs from the current session report is retained
+/// and returned by the subsequent call.
+pub fn take_last_crash_report() -> Option<CrashReport> {
+    let storage = lock(&CONFIG)
+        .as_ref()
+        .and_then(|config| config.storage.clone());
+
+    let current = lock(&LAST_REPORT).take();
+    let Some(storage) = storage else {
+        return current;
+    };
+
+    let stored = storage.take().unwrap_or_else(|err| {
+        log_error!("unable to load crash report: {err}");
+        None
+    });
+
+    match (current, stored) {
+        (Some(current), Some(stored)) if stored != current => {
+            if let Err(err) = storage.store(&stored) {
+                log_error!("unable to store crash report: {err}");
+            }
+            Some(current)
+        }
+        (current, stored) => current.or(stored),
+    }
+}
+
+fn backtrace() -> String {
+    cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            super::Error::new().stack()
+        } else {
+            std::backtrace::Backtrace::force_capture().to_string()
+        }
+    }
+}
+
+fn unixtime_as_millis() -> u64 {
+    cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            use wasm_bindgen::prelude::*;
+
+            #[wasm_bindgen]
+            extern "C" {
+                #[wasm_bindgen(js_namespace = Date, js_name = now)]
+                fn date_now() -> f64;
+            }
+
+            date_now() as u64
+        } else {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use workflow_log::log_info;
+
+    #[derive(Default)]
+    struct MemoryStorage(Mutex<Option<CrashReport>>);
+
+    impl ReportStorage for MemoryStorage {
+        fn store(&self, report: &CrashReport) -> Result<(), String> {
+            lock(&self.0).replace(report.clone());
+            Ok(())
+        }
+
+        fn take(&self) -> Result<Option<CrashReport>, String> {
+            Ok(lock(&self.0).take())
+        }
+    }
+
+    #[test]
+    fn test_crash_report() {
+        let storage = Arc::new(MemoryStorage::default());
+        CrashReporter::new()
+            .with_app_version("1.2.3")
+            .with_log_lines(2)
+            .with_storage(storage.clone())
+            .install();
+
+        log_info!("first");
+        log_info!("second");
+        log_info!("third");
+
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("failure {}", 42))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+
+        let stored = lock(&storage.0).clone().expect("stored report");
+        assert_eq!(stored.message, "failure 42");
+        assert_eq!(stored.thread.as_deref(), Some("worker"));
+        assert_eq!(stored.app_version.as_deref(), Some("1.2.3"));
+        assert!(stored.location.as_deref().unwrap().contains("report.rs"));
+        assert_eq!(stored.logs.len(), 2);
+        assert!(stored.logs[1].ends_with("third"));
+
+        // a stored report of another session is retained
+        let previous = CrashReport {
+            message: "previous".to_string(),
+            ..stored.clone()
+        };
+        lock(&storage.0).replace(previous.clone());
+        assert_eq!(take_last_crash_report(), Some(stored));
+        assert_eq!(take_last_crash_report(), Some(previous));
+        assert_eq!(take_last_crash_report(), None);
+    }
+}
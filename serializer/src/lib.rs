@@ -3,10 +3,14 @@ pub mod payload;
 pub mod result;
 pub mod serializer;
 pub mod tests;
+pub mod versioned;
 
 pub mod prelude {
     pub use crate::serializer::{Deserializer, Serializable, Serializer};
-    pub use crate::{deserialize, load, payload, reader, serialize, store, version, writer};
+    pub use crate::versioned::Versioned;
+    pub use crate::{
+        deserialize, load, payload, reader, serialize, store, version, versioned, writer,
+    };
     pub use borsh::{BorshDeserialize, BorshSerialize};
 }
 
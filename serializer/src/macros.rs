@@ -71,3 +71,61 @@ macro_rules! deserialize {
         $crate::payload::de::Payload::<$type>::deserialize($reader).map(|x| x.into_inner())
     };
 }
+
+/// Implement versioned [`crate::serializer::Serializer`] and [`crate::serializer::Deserializer`]
+/// for a Borsh-serializable type, registering previous versions of the type that are
+/// automatically upgraded (using [`From`] implementations between consecutive versions)
+/// during deserialization. See [`crate::versioned`] module for details.
+///
+/// ```ignore
+/// versioned! {
+///     Settings = 3 {
+///         1 => SettingsV1,
+///         2 => SettingsV2,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! versioned {
+    (@load $type:ty, $version:ident, $reader:ident; $ver:literal => $old:ty $(, $rver:literal => $rold:ty)*) => {
+        if $version == $ver {
+            let value = <$old as $crate::borsh::BorshDeserialize>::deserialize_reader($reader)?;
+            return Ok($crate::versioned!(@upgrade value $(, $rold)*, $type));
+        }
+        $crate::versioned!(@load $type, $version, $reader; $($rver => $rold),*);
+    };
+    (@load $type:ty, $version:ident, $reader:ident;) => {};
+    (@upgrade $value:ident, $next:ty $(, $rest:ty)*) => {{
+        let $value: $next = ::core::convert::From::from($value);
+        $crate::versioned!(@upgrade $value $(, $rest)*)
+    }};
+    (@upgrade $value:ident) => {
+        $value
+    };
+    ($type:ty = $current:literal $({ $($ver:literal => $old:ty),* $(,)? })?) => {
+        impl $crate::versioned::Versioned for $type {
+            const VERSION: $crate::versioned::Version = $current;
+        }
+
+        impl $crate::serializer::Serializer for $type {
+            fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+                <$crate::versioned::Version as $crate::borsh::BorshSerialize>::serialize(&$current, writer)?;
+                <$type as $crate::borsh::BorshSerialize>::serialize(self, writer)
+            }
+        }
+
+        impl $crate::serializer::Deserializer for $type {
+            fn deserialize<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+                let version = <$crate::versioned::Version as $crate::borsh::BorshDeserialize>::deserialize_reader(reader)?;
+                if version == $current {
+                    return <$type as $crate::borsh::BorshDeserialize>::deserialize_reader(reader);
+                }
+                if version > $current {
+                    return Err($crate::versioned::future_version_error(stringify!($type), version, $current));
+                }
+                $($crate::versioned!(@load $type, version, reader; $($ver => $old),*);)?
+                Err($crate::versioned::unknown_version_error(stringify!($type), version))
+            }
+        }
+    };
+}
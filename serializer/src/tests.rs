@@ -93,4 +93,94 @@ mod tests {
 
         Ok(())
     }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct SettingsV1 {
+        name: String,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct SettingsV2 {
+        name: String,
+        enabled: bool,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct Settings {
+        name: String,
+        enabled: bool,
+        retries: u32,
+    }
+
+    impl From<SettingsV1> for SettingsV2 {
+        fn from(v1: SettingsV1) -> Self {
+            SettingsV2 {
+                name: v1.name,
+                enabled: true,
+            }
+        }
+    }
+
+    impl From<SettingsV2> for Settings {
+        fn from(v2: SettingsV2) -> Self {
+            Settings {
+                name: v2.name,
+                enabled: v2.enabled,
+                retries: 3,
+            }
+        }
+    }
+
+    crate::versioned! {
+        Settings = 3 {
+            1 => SettingsV1,
+            2 => SettingsV2,
+        }
+    }
+
+    #[test]
+    fn test_serializer_migrations() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::versioned::Versioned;
+
+        let settings = Settings {
+            name: String::from("test"),
+            enabled: false,
+            retries: 5,
+        };
+        let mut buffer = Vec::new();
+        borsh::BorshSerialize::serialize(&Serializable(settings), &mut buffer)?;
+        let deserialized: Serializable<Settings> =
+            borsh::BorshDeserialize::deserialize(&mut buffer.as_slice())?;
+        assert_eq!(deserialized.retries, 5);
+
+        let mut buffer = Vec::new();
+        store!(u32, &1, &mut buffer)?;
+        store!(
+            SettingsV1,
+            &SettingsV1 {
+                name: String::from("v1")
+            },
+            &mut buffer
+        )?;
+        let settings = <Settings as Deserializer>::try_from_slice(&buffer)?;
+        assert_eq!(
+            settings,
+            Settings {
+                name: String::from("v1"),
+                enabled: true,
+                retries: 3,
+            }
+        );
+
+        let mut buffer = Vec::new();
+        store!(u32, &(Settings::VERSION + 1), &mut buffer)?;
+        let err = <Settings as Deserializer>::try_from_slice(&buffer).unwrap_err();
+        assert_eq!(err.kind(), crate::result::IoErrorKind::InvalidData);
+
+        let mut buffer = Vec::new();
+        store!(u32, &0, &mut buffer)?;
+        assert!(<Settings as Deserializer>::try_from_slice(&buffer).is_err());
+
+        Ok(())
+    }
 }
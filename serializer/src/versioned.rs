@@ -0,0 +1,63 @@
+//!
+//! Schema-versioned serialization with automatic migrations.
+//!
+//! The [`versioned!`](crate::versioned) macro implements [`Serializer`](crate::serializer::Serializer)
+//! and [`Deserializer`](crate::serializer::Deserializer) for a Borsh-serializable type,
+//! prefixing the serialized data with the type version. Previous versions of the type
+//! are registered as separate Borsh-serializable types together with the version they
+//! correspond to. Upgrade functions are supplied as [`From`] implementations between
+//! consecutive versions (v1 → v2 → ... → current), as such, deserializing data stored
+//! with an older version automatically upgrades it to the current version.
+//!
+//! ```ignore
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct SettingsV1 { name: String }
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct Settings { name: String, enabled: bool }
+//!
+//! impl From<SettingsV1> for Settings {
+//!     fn from(v1: SettingsV1) -> Self {
+//!         Settings { name: v1.name, enabled: true }
+//!     }
+//! }
+//!
+//! versioned! {
+//!     Settings = 2 {
+//!         1 => SettingsV1,
+//!     }
+//! }
+//! ```
+//!
+//! Deserializing data with a version greater than the current version
+//! results in an [`std::io::ErrorKind::InvalidData`] error.
+//!
+
+use crate::result::{IoError, IoErrorKind};
+
+/// Type of the version prefix stored by the [`versioned!`](crate::versioned) macro.
+pub type Version = u32;
+
+/// Implemented by the [`versioned!`](crate::versioned) macro,
+/// provides the current version of the type.
+pub trait Versioned {
+    const VERSION: Version;
+}
+
+/// Error produced when the serialized data has been created
+/// by a newer version of the type than currently supported.
+pub fn future_version_error(type_name: &str, version: Version, current: Version) -> IoError {
+    IoError::new(
+        IoErrorKind::InvalidData,
+        format!("{type_name}: version {version} is newer than the supported version {current}"),
+    )
+}
+
+/// Error produced when the serialized data has been created
+/// with a version that has no registered migration.
+pub fn unknown_version_error(type_name: &str, version: Version) -> IoError {
+    IoError::new(
+        IoErrorKind::InvalidData,
+        format!("{type_name}: unknown version {version}"),
+    )
+}
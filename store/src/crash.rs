@@ -0,0 +1,126 @@
+//!
+//! Crash report persistence for [`workflow_panic_hook`]
+//! (requires the `crash-report` feature).
+//!
+//! [`CrashReportStore`] implements [`ReportStorage`] storing the
+//! [`CrashReport`] as JSON in a file on native platforms and NodeJS,
+//! or in the localstorage when running in the browser.
+//!
+//! ```ignore
+//! CrashReporter::new()
+//!     .with_storage(Arc::new(CrashReportStore::new("~/.app/crash.json")))
+//!     .install();
+//! ```
+//!
+
+use crate::fs::{self, Options};
+use crate::result::Result;
+use std::path::{Path, PathBuf};
+use workflow_panic_hook::{CrashReport, ReportStorage};
+
+///
+/// # CrashReportStore
+///
+/// A file (or localstorage entry when running in the browser)
+/// holding the last [`CrashReport`].
+///
+pub struct CrashReportStore {
+    filename: PathBuf,
+    options: Options,
+}
+
+impl CrashReportStore {
+    /// Create a new crash report store for the given filename.
+    /// If using within the web browser environment, a local
+    /// storage key with the name of the file will be used.
+    pub fn new<P: AsRef<Path>>(filename: P) -> Self {
+        CrashReportStore {
+            filename: filename.as_ref().to_path_buf(),
+            options: Options::default(),
+        }
+    }
+
+    /// Specify the localstorage key to be used in the web browser environment.
+    pub fn with_local_storage_key(mut self, key: &str) -> Self {
+        self.options = Options::with_local_storage_key(key);
+        self
+    }
+
+    pub fn filename(&self) -> &Path {
+        &self.filename
+    }
+
+    fn path(&self) -> Result<PathBuf> {
+        fs::resolve_path(&self.filename.to_string_lossy())
+    }
+
+    /// Store the report, replacing any previously stored report.
+    pub fn store_sync(&self, report: &CrashReport) -> Result<()> {
+        let path = self.path()?;
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all_sync(parent)?;
+        }
+        fs::write_json_with_options_sync(&path, self.options.clone(), report)
+    }
+
+    /// Load the stored report (if any) without removing it.
+    pub fn load_sync(&self) -> Result<Option<CrashReport>> {
+        let path = self.path()?;
+        if !fs::exists_with_options_sync(&path, self.options.clone())? {
+            return Ok(None);
+        }
+        fs::read_json_with_options_sync(&path, self.options.clone()).map(Some)
+    }
+
+    /// Load and remove the stored report.
+    pub fn take_sync(&self) -> Result<Option<CrashReport>> {
+        let report = self.load_sync()?;
+        if report.is_some() {
+            fs::remove_with_options_sync(self.path()?, self.options.clone())?;
+        }
+        Ok(report)
+    }
+}
+
+impl ReportStorage for CrashReportStore {
+    fn store(&self, report: &CrashReport) -> std::result::Result<(), String> {
+        self.store_sync(report).map_err(|err| err.to_string())
+    }
+
+    fn take(&self) -> std::result::Result<Option<CrashReport>, String> {
+        self.take_sync().map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_report_store() -> Result<()> {
+        let folder =
+            std::env::temp_dir().join(format!("workflow-store-crash-{}", std::process::id()));
+        let store = CrashReportStore::new(folder.join("crash.json"));
+        assert_eq!(store.take_sync()?, None);
+
+        let report = CrashReport {
+            message: "failure".to_string(),
+            location: Some("src/main.rs:1:1".to_string()),
+            backtrace: String::new(),
+            thread: Some("main".to_string()),
+            app_version: Some("1.0.0".to_string()),
+            timestamp: 1_704_110_400_000,
+            logs: vec!["line".to_string()],
+        };
+        ReportStorage::store(&store, &report).unwrap();
+        assert_eq!(store.load_sync()?.as_ref(), Some(&report));
+        assert_eq!(ReportStorage::take(&store).unwrap(), Some(report));
+        assert_eq!(store.take_sync()?, None);
+
+        std::fs::remove_dir_all(&folder)?;
+        Ok(())
+    }
+}
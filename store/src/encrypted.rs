@@ -0,0 +1,296 @@
+//!
+//! Encrypted key/value storage built on top of the [`fs`](crate::fs) module.
+//!
+//! [`EncryptedStore`] transparently encrypts data using `XChaCha20Poly1305`
+//! with a key derived from the user-supplied password (`argon2(sha256)`).
+//! Since the underlying storage is provided by the [`fs`](crate::fs) module,
+//! the same API is available on native platforms, NodeJS and in the
+//! browser (where the encrypted data is stored in the localstorage).
+//!
+//! The stored data is prefixed with a small header containing a magic
+//! signature and the format version, followed by the ciphertext and the
+//! nonce. `XChaCha20Poly1305` is an authenticated cipher, as such any
+//! modification of the stored data (or an invalid password) results
+//! in an [`Error::Decryption`] error.
+//!
+
+use crate::error::Error;
+use crate::fs::{self, Options};
+use crate::result::Result;
+use crate::store::Store;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use workflow_encryption::chacha20poly1305::{decrypt_slice, encrypt_slice};
+use workflow_encryption::secret::Secret;
+
+/// Magic signature prefixing encrypted store data.
+pub const MAGIC: &[u8; 4] = b"WFES";
+/// Current encrypted store format version.
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+///
+/// # EncryptedStore
+///
+/// A file (or localstorage entry when running in the browser)
+/// whose contents are encrypted with a password-derived key.
+///
+/// ```ignore
+/// let store = EncryptedStore::new("~/.app/settings.bin", Secret::from("password"))?;
+/// store.write_json(&settings).await?;
+/// let settings : Settings = store.read_json().await?;
+/// ```
+///
+pub struct EncryptedStore {
+    filename: PathBuf,
+    options: Options,
+    secret: Secret,
+}
+
+impl EncryptedStore {
+    /// Create a new encrypted store for the given filename
+    /// (`~/` is resolved to the home directory).
+    /// If using within the web browser environment, a local
+    /// storage key with the name of the file will be used.
+    pub fn new<P: AsRef<Path>>(filename: P, secret: Secret) -> Result<Self> {
+        let filename = fs::resolve_path(&filename.as_ref().to_string_lossy())?;
+        Ok(EncryptedStore {
+            filename,
+            options: Options::default(),
+            secret,
+        })
+    }
+
+    /// Create a new encrypted store using the filename resolved
+    /// by the supplied [`Store`] for the current operating environment.
+    pub fn from_store(store: &Store, secret: Secret) -> Result<Self> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let key = store.filename();
+                Ok(EncryptedStore {
+                    filename: PathBuf::from(&key),
+                    options: Options::with_local_storage_key(&key),
+                    secret,
+                })
+            } else {
+                let filename : PathBuf = crate::store::parse(store.filename()).into();
+                EncryptedStore::new(filename, secret)
+            }
+        }
+    }
+
+    /// Specify the localstorage key to be used in the web browser environment.
+    pub fn with_local_storage_key(mut self, key: &str) -> Self {
+        self.options = Options::with_local_storage_key(key);
+        self
+    }
+
+    pub fn filename(&self) -> &Path {
+        &self.filename
+    }
+
+    /// Check if the underlying file (or localstorage entry) exists.
+    pub async fn exists(&self) -> Result<bool> {
+        fs::exists_with_options(&self.filename, self.options.clone()).await
+    }
+
+    /// Check if the underlying file (or localstorage entry) exists.
+    pub fn exists_sync(&self) -> Result<bool> {
+        fs::exists_with_options_sync(&self.filename, self.options.clone())
+    }
+
+    /// Read and decrypt the stored data.
+    pub async fn read(&self) -> Result<Secret> {
+        let data = fs::read_binary_with_options(&self.filename, self.options.clone()).await?;
+        self.decrypt(&data, &self.secret)
+    }
+
+    /// Read and decrypt the stored data.
+    pub fn read_sync(&self) -> Result<Secret> {
+        let data = fs::read_binary_with_options_sync(&self.filename, self.options.clone())?;
+        self.decrypt(&data, &self.secret)
+    }
+
+    /// Encrypt and store the supplied data.
+    pub async fn write(&self, data: &[u8]) -> Result<()> {
+        let data = self.encrypt(data, &self.secret)?;
+        fs::write_binary_with_options(&self.filename, self.options.clone(), &data).await
+    }
+
+    /// Encrypt and store the supplied data.
+    pub fn write_sync(&self, data: &[u8]) -> Result<()> {
+        let data = self.encrypt(data, &self.secret)?;
+        fs::write_binary_with_options_sync(&self.filename, self.options.clone(), &data)
+    }
+
+    /// Read and decrypt the stored data as a UTF-8 string.
+    pub async fn read_to_string(&self) -> Result<String> {
+        let data = self.read().await?;
+        Ok(data.as_str()?.to_string())
+    }
+
+    /// Read and decrypt the stored data as a UTF-8 string.
+    pub fn read_to_string_sync(&self) -> Result<String> {
+        let data = self.read_sync()?;
+        Ok(data.as_str()?.to_string())
+    }
+
+    /// Encrypt and store the supplied string.
+    pub async fn write_string(&self, text: &str) -> Result<()> {
+        self.write(text.as_bytes()).await
+    }
+
+    /// Encrypt and store the supplied string.
+    pub fn write_string_sync(&self, text: &str) -> Result<()> {
+        self.write_sync(text.as_bytes())
+    }
+
+    /// Read, decrypt and deserialize the stored data using `serde-json`.
+    pub async fn read_json<T>(&self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let data = self.read().await?;
+        Ok(serde_json::from_slice(data.as_ref())?)
+    }
+
+    /// Read, decrypt and deserialize the stored data using `serde-json`.
+    pub fn read_json_sync<T>(&self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let data = self.read_sync()?;
+        Ok(serde_json::from_slice(data.as_ref())?)
+    }
+
+    /// Serialize the supplied value using `serde-json`, encrypt and store it.
+    pub async fn write_json<T>(&self, value: &T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let json = Secret::new(serde_json::to_vec(value)?);
+        self.write(json.as_ref()).await
+    }
+
+    /// Serialize the supplied value using `serde-json`, encrypt and store it.
+    pub fn write_json_sync<T>(&self, value: &T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let json = Secret::new(serde_json::to_vec(value)?);
+        self.write_sync(json.as_ref())
+    }
+
+    /// Remove the underlying file (or localstorage entry).
+    pub async fn remove(&self) -> Result<()> {
+        fs::remove_with_options(&self.filename, self.options.clone()).await
+    }
+
+    /// Remove the underlying file (or localstorage entry).
+    pub fn remove_sync(&self) -> Result<()> {
+        fs::remove_with_options_sync(&self.filename, self.options.clone())
+    }
+
+    /// Verify the integrity of the stored data. Returns an error
+    /// if the data is not an encrypted store, has been modified
+    /// or can not be decrypted with the current password.
+    pub async fn verify(&self) -> Result<()> {
+        self.read().await.map(|_| ())
+    }
+
+    /// Verify the integrity of the stored data. Returns an error
+    /// if the data is not an encrypted store, has been modified
+    /// or can not be decrypted with the current password.
+    pub fn verify_sync(&self) -> Result<()> {
+        self.read_sync().map(|_| ())
+    }
+
+    /// Change the password used by this store, re-encrypting
+    /// the stored data with the key derived from the new password.
+    pub async fn change_secret(&mut self, secret: Secret) -> Result<()> {
+        let data = self.read().await?;
+        let data = self.encrypt(data.as_ref(), &secret)?;
+        fs::write_binary_with_options(&self.filename, self.options.clone(), &data).await?;
+        self.secret = secret;
+        Ok(())
+    }
+
+    /// Change the password used by this store, re-encrypting
+    /// the stored data with the key derived from the new password.
+    pub fn change_secret_sync(&mut self, secret: Secret) -> Result<()> {
+        let data = self.read_sync()?;
+        let data = self.encrypt(data.as_ref(), &secret)?;
+        fs::write_binary_with_options_sync(&self.filename, self.options.clone(), &data)?;
+        self.secret = secret;
+        Ok(())
+    }
+
+    fn encrypt(&self, data: &[u8], secret: &Secret) -> Result<Vec<u8>> {
+        let encrypted = encrypt_slice(data, secret)?;
+        let mut buffer = Vec::with_capacity(HEADER_LEN + encrypted.len());
+        buffer.extend_from_slice(MAGIC);
+        buffer.push(VERSION);
+        buffer.extend(encrypted);
+        Ok(buffer)
+    }
+
+    fn decrypt(&self, data: &[u8], secret: &Secret) -> Result<Secret> {
+        if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+            return Err(Error::EncryptedStoreHeader(self.display_name()));
+        }
+
+        let version = data[MAGIC.len()];
+        if version != VERSION {
+            return Err(Error::EncryptedStoreVersion(version));
+        }
+
+        decrypt_slice(&data[HEADER_LEN..], secret)
+            .map_err(|_| Error::Decryption(self.display_name()))
+    }
+
+    fn display_name(&self) -> String {
+        self.filename.to_string_lossy().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_store() -> Result<()> {
+        let filename = std::env::temp_dir().join(format!(
+            "workflow-store-encrypted-{}.bin",
+            std::process::id()
+        ));
+
+        let mut store = EncryptedStore::new(&filename, Secret::from("password"))?;
+        store.write_string_sync("hello world")?;
+        assert_ne!(std::fs::read(&filename)?, b"hello world");
+        assert_eq!(store.read_to_string_sync()?, "hello world");
+
+        store.change_secret_sync(Secret::from("new password"))?;
+        assert_eq!(store.read_to_string_sync()?, "hello world");
+
+        let old = EncryptedStore::new(&filename, Secret::from("password"))?;
+        assert!(matches!(old.verify_sync(), Err(Error::Decryption(_))));
+
+        let mut data = std::fs::read(&filename)?;
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        std::fs::write(&filename, data)?;
+        assert!(matches!(store.verify_sync(), Err(Error::Decryption(_))));
+
+        std::fs::write(&filename, b"plain text")?;
+        assert!(matches!(
+            store.verify_sync(),
+            Err(Error::EncryptedStoreHeader(_))
+        ));
+
+        store.remove_sync()?;
+        assert!(!store.exists_sync()?);
+        Ok(())
+    }
+}
@@ -52,6 +52,24 @@ pub enum Error {
 
     #[error("This operation is not supported")]
     NotSupported,
+
+    #[error("Watch error: {0}")]
+    Watch(String),
+
+    #[error("File is locked: {0}")]
+    Locked(String),
+
+    #[error(transparent)]
+    Encryption(#[from] workflow_encryption::error::Error),
+
+    #[error("Not an encrypted store: {0}")]
+    EncryptedStoreHeader(String),
+
+    #[error("Unsupported encrypted store version {0}")]
+    EncryptedStoreVersion(u8),
+
+    #[error("Unable to decrypt {0} (invalid password or corrupted data)")]
+    Decryption(String),
 }
 
 impl From<Error> for JsValue {
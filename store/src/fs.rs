@@ -6,6 +6,7 @@
 //! - Rust std file I/O (fs::xxx)
 //! - NodeJS file I/O (fs::read_file_sync)
 //! - Browser local storage
+//! - Browser IndexedDB (selectable via [`Options::with_backend`])
 //!
 //! By default, all I/O functions will use the name of the file as a key
 //! for localstorage. If you want to manually specify the localstorage key.
@@ -19,10 +20,15 @@ use js_sys::Uint8Array;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use wasm_bindgen::prelude::*;
 use workflow_core::dirs;
 use workflow_core::runtime;
 
+pub use crate::watch::{
+    watch, watch_with_options, WatchEvent, WatchEventKind, WatchOptions, Watcher,
+};
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(extends = Uint8Array)]
@@ -43,18 +49,79 @@ pub fn local_storage() -> web_sys::Storage {
         .expect("localStorage is not available")
 }
 
-#[derive(Default)]
+/// Storage backend used in the web browser environment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Browser localstorage (binary data is hex-encoded).
+    #[default]
+    LocalStorage,
+    /// Browser IndexedDB (see [`idb`](crate::idb)). Binary data is stored
+    /// natively and files are keyed by their full path (or by the
+    /// [`Options::local_storage_key`] if supplied), which allows
+    /// `readdir`, `rename` and `metadata` to be supported. Synchronous
+    /// functions are not available with this backend.
+    IndexedDb,
+}
+
+/// Options for file I/O operations.
+///
+/// By default, writes performed on native platforms and NodeJS are atomic:
+/// data is written to a temporary file in the destination folder, flushed
+/// to disk and then renamed over the destination file. This ensures that
+/// the destination file is never left partially written. Browser writes
+/// (localstorage) are always all-or-nothing.
+#[derive(Clone)]
 pub struct Options {
+    /// Browser storage key used instead of the file name (localstorage)
+    /// or the file path (IndexedDB)
     pub local_storage_key: Option<String>,
+    /// Storage backend used in the web browser environment
+    pub backend: Backend,
+    /// Write files atomically (native and NodeJS only)
+    pub atomic: bool,
+    /// Number of previous file versions preserved when writing atomically
+    /// or appending (`<filename>.bak`, `<filename>.bak.1`, ... see
+    /// [`backup_filename`]), `0` disables backups (native and NodeJS only)
+    pub backups: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            local_storage_key: None,
+            backend: Backend::default(),
+            atomic: true,
+            backups: 0,
+        }
+    }
 }
 
 impl Options {
     pub fn with_local_storage_key(key: &str) -> Self {
         Options {
             local_storage_key: Some(key.to_string()),
+            ..Default::default()
         }
     }
 
+    /// Select the storage backend used in the web browser environment.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Enable or disable atomic writes.
+    pub fn with_atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /// Number of backup generations rotated on atomic writes and appends.
+    pub fn with_backups(mut self, backups: usize) -> Self {
+        self.backups = backups;
+        self
+    }
+
     pub fn local_storage_key(&self, filename: &Path) -> String {
         self.local_storage_key
             .clone()
@@ -62,6 +129,42 @@ impl Options {
     }
 }
 
+/// Returns the path of a backup file created during atomic writes when
+/// [`Options::backups`] is enabled: `<filename>.bak` for the most recent
+/// backup (generation `0`) and `<filename>.bak.<generation>` for older ones.
+pub fn backup_filename<P: AsRef<Path>>(filename: P, generation: usize) -> PathBuf {
+    match generation {
+        0 => append_extension(filename.as_ref(), "bak"),
+        _ => append_extension(filename.as_ref(), &format!("bak.{generation}")),
+    }
+}
+
+/// Returns the path of the lock file used by [`FileLock`] (`<filename>.lock`).
+pub fn lock_filename<P: AsRef<Path>>(filename: P) -> PathBuf {
+    append_extension(filename.as_ref(), "lock")
+}
+
+fn append_extension(filename: &Path, extension: &str) -> PathBuf {
+    let mut filename = filename.as_os_str().to_os_string();
+    filename.push(".");
+    filename.push(extension);
+    PathBuf::from(filename)
+}
+
+static TEMP_FILE_SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn temp_filename(filename: &Path) -> PathBuf {
+    let seq = TEMP_FILE_SEQ.fetch_add(1, Ordering::Relaxed);
+    cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            let id = (js_sys::Math::random() * u32::MAX as f64) as u32;
+        } else {
+            let id = std::process::id();
+        }
+    }
+    append_extension(filename, &format!("{id:x}-{seq:x}.tmp"))
+}
+
 cfg_if! {
     if #[cfg(target_arch = "wasm32")] {
         use workflow_core::hex::*;
@@ -69,12 +172,29 @@ cfg_if! {
         use workflow_node as node;
         use js_sys::Object;
         use workflow_chrome::storage::LocalStorage as ChromeStorage;
+        use workflow_core::task::call_async_no_send;
+        use crate::idb;
+
+        /// Path of the IndexedDB record of the file (the local
+        /// storage key if one is supplied in the options).
+        fn idb_path(filename: &Path, options: &Options) -> PathBuf {
+            match &options.local_storage_key {
+                Some(key) => PathBuf::from(key),
+                None => filename.to_path_buf(),
+            }
+        }
 
+        fn indexed_db_sync_error(alternative: &str) -> Error {
+            Error::Custom(format!("IndexedDB api is asynchronous, you can use {alternative}() instead."))
+        }
 
         pub async fn exists_with_options<P : AsRef<Path>>(filename: P, options : Options) -> Result<bool> {
             if runtime::is_node() || runtime::is_nw() {
                 let filename = filename.as_ref().to_platform_string();
                 Ok(node::fs::exists_sync(filename.as_ref())?)
+            } else if options.backend == Backend::IndexedDb {
+                let filename = idb_path(filename.as_ref(), &options);
+                call_async_no_send!(async move { idb::store().await?.exists(filename).await })
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 if runtime::is_chrome_extension(){
@@ -89,6 +209,8 @@ cfg_if! {
             if runtime::is_node() || runtime::is_nw() {
                 let filename = filename.as_ref().to_platform_string();
                 Ok(node::fs::exists_sync(filename.as_ref())?)
+            } else if options.backend == Backend::IndexedDb {
+                Err(indexed_db_sync_error("exists_with_options"))
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 if runtime::is_chrome_extension(){
@@ -107,6 +229,9 @@ cfg_if! {
                 let js_value = node::fs::read_file_sync(&filename, options)?;
                 let text = js_value.as_string().ok_or(Error::DataIsNotAString(filename))?;
                 Ok(text)
+            } else if options.backend == Backend::IndexedDb {
+                let filename = idb_path(filename.as_ref(), &options);
+                call_async_no_send!(async move { idb::store().await?.read_to_string(filename).await })
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 if runtime::is_chrome_extension(){
@@ -131,6 +256,8 @@ cfg_if! {
                 let js_value = node::fs::read_file_sync(&filename, options)?;
                 let text = js_value.as_string().ok_or(Error::DataIsNotAString(filename))?;
                 Ok(text)
+            } else if options.backend == Backend::IndexedDb {
+                Err(indexed_db_sync_error("read_to_string_with_options"))
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 if runtime::is_chrome_extension(){
@@ -150,6 +277,9 @@ cfg_if! {
                 let buffer = node::fs::read_file_sync(&filename, options)?;
                 let data = buffer.dyn_into::<Uint8Array>()?;
                 Ok(data.to_vec())
+            } else if options.backend == Backend::IndexedDb {
+                let filename = idb_path(filename.as_ref(), &options);
+                call_async_no_send!(async move { idb::store().await?.read(filename).await })
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 let data = if runtime::is_chrome_extension(){
@@ -174,6 +304,8 @@ cfg_if! {
                 let buffer = node::fs::read_file_sync(&filename, options)?;
                 let data = buffer.dyn_into::<Uint8Array>()?;
                 Ok(data.to_vec())
+            } else if options.backend == Backend::IndexedDb {
+                Err(indexed_db_sync_error("read_binary_with_options"))
             } else if runtime::is_chrome_extension(){
                     Err(Error::Custom("localStorage api is unavailable, you can use read_binary_with_options() for chrome.storage.local api.".to_string()))
             } else {
@@ -187,13 +319,69 @@ cfg_if! {
             }
         }
 
+        fn write_node(filename: &Path, options: &Options, data: JsValue, write_options: Object) -> Result<()> {
+            let target = filename.to_platform_string();
+            if !options.atomic {
+                node::fs::write_file_sync(&target, data, write_options)?;
+                return Ok(());
+            }
+
+            let temp = temp_filename(filename).to_platform_string();
+            let result = (|| -> Result<()> {
+                let fd = node::fs::open_sync(&temp, "w")?;
+                let written = node::fs::write_fd_sync(fd, data, write_options)
+                    .and_then(|_| node::fs::fsync_sync(fd));
+                node::fs::close_sync(fd)?;
+                written?;
+
+                rotate_backups_node(filename, options)?;
+                node::fs::rename_sync(&temp, &target)?;
+                Ok(())
+            })();
+
+            if result.is_err() {
+                node::fs::unlink_sync(&temp).ok();
+            }
+
+            result
+        }
+
+        /// Shift the existing backups by one generation and copy
+        /// the current file contents to the most recent backup.
+        fn rotate_backups_node(filename: &Path, options: &Options) -> Result<()> {
+            let target = filename.to_platform_string();
+            if options.backups == 0 || !node::fs::exists_sync(&target)? {
+                return Ok(());
+            }
+            for generation in (1..options.backups).rev() {
+                let previous = backup_filename(filename, generation - 1).to_platform_string();
+                if node::fs::exists_sync(&previous)? {
+                    node::fs::rename_sync(&previous, &backup_filename(filename, generation).to_platform_string())?;
+                }
+            }
+            node::fs::copy_file_sync(&target, &backup_filename(filename, 0).to_platform_string())?;
+            Ok(())
+        }
+
+        fn write_string_node(filename: &Path, options: &Options, text: &str) -> Result<()> {
+            let write_options = Object::new();
+            Reflect::set(&write_options, &"encoding".into(), &"utf-8".into())?;
+            write_node(filename, options, JsValue::from(text), write_options)
+        }
+
+        fn write_binary_node(filename: &Path, options: &Options, data: &[u8]) -> Result<()> {
+            let uint8_array = Uint8Array::from(data);
+            let buffer = Buffer::from_uint8_array(&uint8_array);
+            write_node(filename, options, buffer.into(), Object::new())
+        }
+
         pub async fn write_string_with_options<P : AsRef<Path>>(filename: P, options: Options, text : &str) -> Result<()> {
             if runtime::is_node() || runtime::is_nw() {
-                let filename = filename.as_ref().to_platform_string();
-                let options = Object::new();
-                Reflect::set(&options, &"encoding".into(), &"utf-8".into())?;
-                let data = JsValue::from(text);
-                node::fs::write_file_sync(&filename, data, options)?;
+                write_string_node(filename.as_ref(), &options, text)?;
+            } else if options.backend == Backend::IndexedDb {
+                let filename = idb_path(filename.as_ref(), &options);
+                let data = text.as_bytes().to_vec();
+                call_async_no_send!(async move { idb::store().await?.write(filename, &data).await })?;
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 if runtime::is_chrome_extension(){
@@ -206,13 +394,36 @@ cfg_if! {
             Ok(())
         }
 
+        pub async fn append_string_with_options<P : AsRef<Path>>(filename: P, options: Options, text : &str) -> Result<()> {
+            if runtime::is_node() || runtime::is_nw() {
+                let _lock = lock(filename.as_ref()).await?;
+                let target = filename.as_ref().to_platform_string();
+                rotate_backups_node(filename.as_ref(), &options)?;
+                let fd = node::fs::open_sync(&target, "a")?;
+                let write_options = Object::new();
+                Reflect::set(&write_options, &"encoding".into(), &"utf-8".into())?;
+                let written = node::fs::write_fd_sync(fd, JsValue::from(text), write_options);
+                node::fs::close_sync(fd)?;
+                written?;
+                Ok(())
+            } else {
+                // browser storage backends do not support appending
+                let filename = filename.as_ref();
+                let mut content = if exists_with_options(filename, options.clone()).await? {
+                    read_to_string_with_options(filename, options.clone()).await?
+                } else {
+                    String::new()
+                };
+                content.push_str(text);
+                write_string_with_options(filename, options, &content).await
+            }
+        }
+
         pub fn write_string_with_options_sync<P : AsRef<Path>>(filename: P, options: Options, text : &str) -> Result<()> {
             if runtime::is_node() || runtime::is_nw() {
-                let filename = filename.as_ref().to_platform_string();
-                let options = Object::new();
-                Reflect::set(&options, &"encoding".into(), &"utf-8".into())?;
-                let data = JsValue::from(text);
-                node::fs::write_file_sync(&filename, data, options)?;
+                write_string_node(filename.as_ref(), &options, text)?;
+            } else if options.backend == Backend::IndexedDb {
+                return Err(indexed_db_sync_error("write_string_with_options"));
             } else if runtime::is_chrome_extension(){
                 return Err(Error::Custom("localStorage api is unavailable, you can use write_string_with_options() for chrome.storage.local api.".to_string()));
             }else{
@@ -224,11 +435,11 @@ cfg_if! {
 
         pub async fn write_binary_with_options<P : AsRef<Path>>(filename: P, options: Options, data : &[u8]) -> Result<()> {
             if runtime::is_node() || runtime::is_nw() {
-                let filename = filename.as_ref().to_platform_string();
-                let options = Object::new();
-                let uint8_array = Uint8Array::from(data);
-                let buffer = Buffer::from_uint8_array(&uint8_array);
-                node::fs::write_file_sync(&filename, buffer.into(), options)?;
+                write_binary_node(filename.as_ref(), &options, data)?;
+            } else if options.backend == Backend::IndexedDb {
+                let filename = idb_path(filename.as_ref(), &options);
+                let data = data.to_vec();
+                call_async_no_send!(async move { idb::store().await?.write(filename, &data).await })?;
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 if runtime::is_chrome_extension(){
@@ -242,11 +453,9 @@ cfg_if! {
 
         pub fn write_binary_with_options_sync<P : AsRef<Path>>(filename: P, options: Options, data : &[u8]) -> Result<()> {
             if runtime::is_node() || runtime::is_nw() {
-                let filename = filename.as_ref().to_platform_string();
-                let options = Object::new();
-                let uint8_array = Uint8Array::from(data);
-                let buffer = Buffer::from_uint8_array(&uint8_array);
-                node::fs::write_file_sync(&filename, buffer.into(), options)?;
+                write_binary_node(filename.as_ref(), &options, data)?;
+            } else if options.backend == Backend::IndexedDb {
+                return Err(indexed_db_sync_error("write_binary_with_options"));
             } else if runtime::is_chrome_extension(){
                 return Err(Error::Custom("localStorage api is unavailable, you can use write_binary_with_options() for chrome.storage.local api.".to_string()));
             }else{
@@ -261,6 +470,9 @@ cfg_if! {
             if runtime::is_node() || runtime::is_nw() {
                 let filename = filename.as_ref().to_platform_string();
                 node::fs::unlink_sync(&filename)?;
+            } else if options.backend == Backend::IndexedDb {
+                let filename = idb_path(filename.as_ref(), &options);
+                call_async_no_send!(async move { idb::store().await?.remove(filename).await })?;
             } else {
                 let key_name = options.local_storage_key(filename.as_ref());
                 if runtime::is_chrome_extension(){
@@ -276,6 +488,8 @@ cfg_if! {
             if runtime::is_node() || runtime::is_nw() {
                 let filename = filename.as_ref().to_platform_string();
                 node::fs::unlink_sync(&filename)?;
+            } else if options.backend == Backend::IndexedDb {
+                return Err(indexed_db_sync_error("remove_with_options"));
             } else if runtime::is_chrome_extension(){
                 return Err(Error::Custom("localStorage api is unavailable, you can use remove_with_options() for chrome.storage.local api.".to_string()));
             }else{
@@ -285,18 +499,22 @@ cfg_if! {
             Ok(())
         }
 
-        pub async fn rename<P : AsRef<Path>>(from: P, to: P) -> Result<()> {
+        pub async fn rename_with_options<P : AsRef<Path>>(from: P, to: P, options: Options) -> Result<()> {
             if runtime::is_node() || runtime::is_nw() {
                 let from = from.as_ref().to_platform_string();
                 let to = to.as_ref().to_platform_string();
                 node::fs::rename_sync(&from,&to)?;
                 Ok(())
+            } else if options.backend == Backend::IndexedDb {
+                let from = from.as_ref().to_path_buf();
+                let to = to.as_ref().to_path_buf();
+                call_async_no_send!(async move { idb::store().await?.rename(from, to).await })
             } else {
                 Err(Error::NotSupported)
             }
         }
 
-        pub fn rename_sync<P : AsRef<Path>>(from: P, to: P) -> Result<()> {
+        pub fn rename_with_options_sync<P : AsRef<Path>>(from: P, to: P, _options: Options) -> Result<()> {
             if runtime::is_node() || runtime::is_nw() {
                 let from = from.as_ref().to_platform_string();
                 let to = to.as_ref().to_platform_string();
@@ -307,6 +525,50 @@ cfg_if! {
             }
         }
 
+        pub async fn metadata_with_options<P : AsRef<Path>>(filename: P, options: Options) -> Result<Metadata> {
+            if runtime::is_node() || runtime::is_nw() {
+                metadata_with_options_sync(filename, options)
+            } else if options.backend == Backend::IndexedDb {
+                let filename = idb_path(filename.as_ref(), &options);
+                call_async_no_send!(async move { idb::store().await?.metadata(filename).await })
+            } else {
+                Err(Error::NotSupported)
+            }
+        }
+
+        pub fn metadata_with_options_sync<P : AsRef<Path>>(filename: P, _options: Options) -> Result<Metadata> {
+            if runtime::is_node() || runtime::is_nw() {
+                let filename = filename.as_ref().to_platform_string();
+                node::fs::stat_sync(&filename)?.try_into()
+            } else {
+                Err(Error::NotSupported)
+            }
+        }
+
+        /// Migrate a file stored in the localstorage to IndexedDB. The
+        /// localstorage key is resolved using the supplied `options`.
+        /// If `binary` is true, the data is assumed to be hex-encoded
+        /// (as written by [`write_binary_with_options`]). On success, the
+        /// localstorage entry is removed. Returns `false` if the entry
+        /// does not exist in the localstorage.
+        pub async fn migrate_from_local_storage<P : AsRef<Path>>(filename: P, options: Options, binary: bool) -> Result<bool> {
+            let key_name = options.local_storage_key(filename.as_ref());
+            let Some(text) = local_storage().get_item(&key_name)? else {
+                return Ok(false);
+            };
+
+            let data = if binary {
+                Vec::<u8>::from_hex(&text)?
+            } else {
+                text.into_bytes()
+            };
+
+            let filename = idb_path(filename.as_ref(), &options);
+            call_async_no_send!(async move { idb::store().await?.write(filename, &data).await })?;
+            local_storage().remove_item(&key_name)?;
+            Ok(true)
+        }
+
         pub async fn create_dir_all<P : AsRef<Path>>(filename: P) -> Result<()> {
             create_dir_all_sync(filename)
         }
@@ -346,7 +608,7 @@ cfg_if! {
             Ok(entries)
         }
 
-        pub async fn readdir<P>(path: P, metadata : bool) -> Result<Vec<DirEntry>>
+        pub async fn readdir_with_options<P>(path: P, metadata : bool, options: Options) -> Result<Vec<DirEntry>>
         where P : AsRef<Path> + Send + 'static
         {
             // this is a hack to bypass JsFuture being !Send
@@ -369,6 +631,8 @@ cfg_if! {
                 });
 
                 Ok(receiver.recv().await.unwrap().unwrap()?)
+            } else if options.backend == Backend::IndexedDb {
+                call_async_no_send!(async move { idb::store().await?.readdir(path, metadata).await })
             } else if runtime::is_chrome_extension(){
                 let entries = ChromeStorage::keys().await?
                     .into_iter()
@@ -390,6 +654,165 @@ cfg_if! {
             }
         }
 
+        ///
+        /// Advisory lock associated with a file. Under NodeJS the lock is
+        /// represented by an exclusively created `<filename>.lock` file
+        /// (see [`lock_filename`]) containing the pid of the owning process,
+        /// that is removed when [`FileLock`] is dropped. A lock file owned
+        /// by a process that is no longer running (or, if the owner is not
+        /// known, older than [`STALE_LOCK_AGE`]) is considered stale and is
+        /// replaced.
+        /// In the browser, localstorage access is serialized and
+        /// writes are all-or-nothing, as such the lock is a no-op.
+        ///
+        pub struct FileLock {
+            filename: PathBuf,
+            lock_file: Option<String>,
+        }
+
+        impl FileLock {
+            /// Attempt to acquire the lock for the given file without blocking.
+            /// Returns `Ok(None)` if the lock is held by another process
+            /// (or another [`FileLock`] within this process).
+            pub fn try_lock<P : AsRef<Path>>(filename: P) -> Result<Option<FileLock>> {
+                let filename = filename.as_ref().to_path_buf();
+                if runtime::is_node() || runtime::is_nw() {
+                    let lock_file = lock_filename(&filename).to_platform_string();
+                    let mut reclaimed = false;
+                    loop {
+                        match node::fs::open_sync(&lock_file, "wx") {
+                            Ok(fd) => {
+                                let lock = FileLock { filename, lock_file : Some(lock_file) };
+                                let pid = current_pid().map(|pid| pid.to_string()).unwrap_or_default();
+                                let written = node::fs::write_fd_sync(fd, JsValue::from(pid), Object::new());
+                                node::fs::close_sync(fd)?;
+                                written?;
+                                return Ok(Some(lock));
+                            }
+                            Err(err) => {
+                                let err = Error::from(err);
+                                if err.code() != Some("EEXIST") {
+                                    return Err(err);
+                                }
+                                if reclaimed || !is_stale_lock(&lock_file) || !reclaim_stale_lock(&lock_file)? {
+                                    return Ok(None);
+                                }
+                                reclaimed = true;
+                            }
+                        }
+                    }
+                } else {
+                    Ok(Some(FileLock { filename, lock_file : None }))
+                }
+            }
+
+            /// Acquire the lock for the given file. Since the current
+            /// thread can not be blocked in the WASM32 environment,
+            /// this function returns [`Error::Locked`] if the lock is
+            /// held elsewhere. Use [`lock`] to wait for the lock.
+            pub fn lock_sync<P : AsRef<Path>>(filename: P) -> Result<FileLock> {
+                let display = filename.as_ref().to_string_lossy().to_string();
+                FileLock::try_lock(filename)?.ok_or(Error::Locked(display))
+            }
+
+            pub fn filename(&self) -> &Path {
+                &self.filename
+            }
+        }
+
+        impl Drop for FileLock {
+            fn drop(&mut self) {
+                if let Some(lock_file) = self.lock_file.take() {
+                    node::fs::unlink_sync(&lock_file).ok();
+                }
+            }
+        }
+
+        /// Returns true if the lock file is owned by a process that is no
+        /// longer running or, if the owner is not known (the lock file is
+        /// being created), was not modified within [`STALE_LOCK_AGE`].
+        fn is_stale_lock(lock_file: &str) -> bool {
+            let read_options = Object::new();
+            Reflect::set(&read_options, &"encoding".into(), &"utf-8".into()).ok();
+            let owner = node::fs::read_file_sync(lock_file, read_options)
+                .ok()
+                .and_then(|text| text.as_string())
+                .and_then(|text| text.trim().parse::<u32>().ok());
+            match owner {
+                Some(pid) => !is_process_running(pid),
+                None => is_older_than(lock_file, STALE_LOCK_AGE),
+            }
+        }
+
+        /// Remove a stale lock file. The removal is guarded by an exclusively
+        /// created `<filename>.lock.takeover` file and the lock file is checked
+        /// again while the guard is held, as such a lock acquired by another
+        /// process in the meantime is never removed. Returns false if the
+        /// guard is held by another process or the lock is no longer stale.
+        fn reclaim_stale_lock(lock_file: &str) -> Result<bool> {
+            let guard = format!("{lock_file}.takeover");
+            match node::fs::open_sync(&guard, "wx") {
+                Ok(fd) => node::fs::close_sync(fd)?,
+                Err(err) => {
+                    let err = Error::from(err);
+                    if err.code() != Some("EEXIST") {
+                        return Err(err);
+                    }
+                    // the guard is held only momentarily, an old guard
+                    // has been left behind by a crashed process
+                    if is_older_than(&guard, STALE_LOCK_AGE) {
+                        node::fs::unlink_sync(&guard).ok();
+                    }
+                    return Ok(false);
+                }
+            }
+
+            let stale = is_stale_lock(lock_file);
+            if stale {
+                node::fs::unlink_sync(lock_file).ok();
+            }
+            node::fs::unlink_sync(&guard).ok();
+            Ok(stale)
+        }
+
+        fn is_older_than(filename: &str, age: std::time::Duration) -> bool {
+            let modified = node::fs::stat_sync(filename)
+                .ok()
+                .and_then(|metadata| Metadata::try_from(metadata).ok())
+                .and_then(|metadata| metadata.modified());
+            let now = workflow_core::time::unixtime_as_millis_u64() / 1000;
+            modified.is_some_and(|modified| now.saturating_sub(modified) > age.as_secs())
+        }
+
+        fn node_process() -> Option<JsValue> {
+            Reflect::get(&js_sys::global(), &"process".into())
+                .ok()
+                .filter(|process| process.is_object())
+        }
+
+        fn current_pid() -> Option<u32> {
+            let pid = Reflect::get(&node_process()?, &"pid".into()).ok()?;
+            pid.as_f64().map(|pid| pid as u32)
+        }
+
+        /// Returns false if the process with the given pid does not exist
+        /// (`process.kill(pid, 0)` fails with `ESRCH`).
+        fn is_process_running(pid: u32) -> bool {
+            let Some(process) = node_process() else {
+                return true;
+            };
+            let kill = Reflect::get(&process, &"kill".into())
+                .ok()
+                .and_then(|kill| kill.dyn_into::<js_sys::Function>().ok());
+            match kill {
+                Some(kill) => match kill.call2(&process, &pid.into(), &0.into()) {
+                    Ok(_) => true,
+                    Err(err) => Error::from(err).code() != Some("ESRCH"),
+                },
+                None => true,
+            }
+        }
+
         // -----------------------------------------
 
     } else {  // cfg_if - native platforms
@@ -420,20 +843,95 @@ cfg_if! {
             Ok(std::fs::read(filename)?)
         }
 
-        pub async fn write_string_with_options<P : AsRef<Path>>(filename: P, _options: Options, text : &str) -> Result<()> {
-            Ok(std::fs::write(filename, text)?)
+        fn write_atomic(filename: &Path, options: &Options, data: &[u8]) -> Result<()> {
+            use std::io::Write;
+
+            let temp = temp_filename(filename);
+            let result = (|| -> Result<()> {
+                let mut file = std::fs::File::create(&temp)?;
+                if let Ok(metadata) = std::fs::metadata(filename) {
+                    file.set_permissions(metadata.permissions())?;
+                }
+                file.write_all(data)?;
+                file.sync_all()?;
+                drop(file);
+
+                rotate_backups(filename, options)?;
+                std::fs::rename(&temp, filename)?;
+                sync_parent_dir(filename);
+                Ok(())
+            })();
+
+            if result.is_err() {
+                std::fs::remove_file(&temp).ok();
+            }
+
+            result
+        }
+
+        /// Shift the existing backups by one generation and copy
+        /// the current file contents to the most recent backup.
+        fn rotate_backups(filename: &Path, options: &Options) -> Result<()> {
+            if options.backups == 0 || !filename.exists() {
+                return Ok(());
+            }
+            for generation in (1..options.backups).rev() {
+                let previous = backup_filename(filename, generation - 1);
+                if previous.exists() {
+                    std::fs::rename(&previous, backup_filename(filename, generation))?;
+                }
+            }
+            std::fs::copy(filename, backup_filename(filename, 0))?;
+            Ok(())
+        }
+
+        // Flushes the directory entry after rename; this is a no-op
+        // on platforms that do not support opening directories.
+        fn sync_parent_dir(filename: &Path) {
+            #[cfg(unix)]
+            if let Some(dir) = filename.parent() {
+                let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+                if let Ok(dir) = std::fs::File::open(dir) {
+                    dir.sync_all().ok();
+                }
+            }
+            #[cfg(not(unix))]
+            let _ = filename;
+        }
+
+        fn write_with_options(filename: &Path, options: &Options, data: &[u8]) -> Result<()> {
+            if options.atomic {
+                write_atomic(filename, options, data)
+            } else {
+                Ok(std::fs::write(filename, data)?)
+            }
+        }
+
+        pub async fn write_string_with_options<P : AsRef<Path>>(filename: P, options: Options, text : &str) -> Result<()> {
+            write_with_options(filename.as_ref(), &options, text.as_bytes())
         }
 
-        pub fn write_string_with_options_sync<P : AsRef<Path>>(filename: P, _options: Options, text : &str) -> Result<()> {
-            Ok(std::fs::write(filename, text)?)
+        pub fn write_string_with_options_sync<P : AsRef<Path>>(filename: P, options: Options, text : &str) -> Result<()> {
+            write_with_options(filename.as_ref(), &options, text.as_bytes())
         }
 
-        pub async fn write_binary_with_options<P : AsRef<Path>>(filename: P, _options: Options, data : &[u8]) -> Result<()> {
-            Ok(std::fs::write(filename, data)?)
+        pub async fn append_string_with_options<P : AsRef<Path>>(filename: P, options: Options, text : &str) -> Result<()> {
+            use std::io::Write;
+            let filename = filename.as_ref();
+            let _lock = lock(filename).await?;
+            rotate_backups(filename, &options)?;
+            let mut file = std::fs::OpenOptions::new().create(true).append(true).open(filename)?;
+            file.write_all(text.as_bytes())?;
+            file.sync_all()?;
+            Ok(())
         }
 
-        pub fn write_binary_with_options_sync<P : AsRef<Path>>(filename: P, _options: Options, data : &[u8]) -> Result<()> {
-            Ok(std::fs::write(filename, data)?)
+        pub async fn write_binary_with_options<P : AsRef<Path>>(filename: P, options: Options, data : &[u8]) -> Result<()> {
+            write_with_options(filename.as_ref(), &options, data)
+        }
+
+        pub fn write_binary_with_options_sync<P : AsRef<Path>>(filename: P, options: Options, data : &[u8]) -> Result<()> {
+            write_with_options(filename.as_ref(), &options, data)
         }
 
         pub async fn remove_with_options<P : AsRef<Path>>(filename: P, _options: Options) -> Result<()> {
@@ -446,16 +944,24 @@ cfg_if! {
             Ok(())
         }
 
-        pub async fn rename<P : AsRef<Path>>(from: P, to: P) -> Result<()> {
+        pub async fn rename_with_options<P : AsRef<Path>>(from: P, to: P, _options: Options) -> Result<()> {
             std::fs::rename(from,to)?;
             Ok(())
         }
 
-        pub fn rename_sync<P : AsRef<Path>>(from: P, to: P) -> Result<()> {
+        pub fn rename_with_options_sync<P : AsRef<Path>>(from: P, to: P, _options: Options) -> Result<()> {
             std::fs::rename(from,to)?;
             Ok(())
         }
 
+        pub async fn metadata_with_options<P : AsRef<Path>>(filename: P, _options: Options) -> Result<Metadata> {
+            Ok(std::fs::metadata(filename)?.into())
+        }
+
+        pub fn metadata_with_options_sync<P : AsRef<Path>>(filename: P, _options: Options) -> Result<Metadata> {
+            Ok(std::fs::metadata(filename)?.into())
+        }
+
         pub async fn create_dir_all<P : AsRef<Path>>(dir: P) -> Result<()> {
             std::fs::create_dir_all(dir)?;
             Ok(())
@@ -466,7 +972,7 @@ cfg_if! {
             Ok(())
         }
 
-        pub async fn readdir<P : AsRef<Path>>(path: P, metadata : bool) -> Result<Vec<DirEntry>> {
+        pub async fn readdir_with_options<P : AsRef<Path>>(path: P, metadata : bool, _options: Options) -> Result<Vec<DirEntry>> {
             let entries = std::fs::read_dir(path.as_ref())?;
 
             if metadata {
@@ -483,16 +989,103 @@ cfg_if! {
             }
         }
 
+        ///
+        /// Advisory lock associated with a file. The lock is held on a separate
+        /// `<filename>.lock` file (see [`lock_filename`]) so that it remains
+        /// valid across atomic writes that replace the target file.
+        /// The lock is released (and the lock file removed) when [`FileLock`]
+        /// is dropped, or by the operating system if the process terminates.
+        ///
+        pub struct FileLock {
+            filename: PathBuf,
+            file: Option<std::fs::File>,
+        }
+
+        impl FileLock {
+            /// Attempt to acquire the lock for the given file without blocking.
+            /// Returns `Ok(None)` if the lock is held by another process
+            /// (or another [`FileLock`] within this process).
+            pub fn try_lock<P : AsRef<Path>>(filename: P) -> Result<Option<FileLock>> {
+                let filename = filename.as_ref().to_path_buf();
+                loop {
+                    let file = Self::open(&filename)?;
+                    match file.try_lock() {
+                        Ok(()) if Self::is_current(&file, &filename) => {
+                            return Ok(Some(FileLock { filename, file: Some(file) }))
+                        }
+                        // the lock file has been removed by the previous owner
+                        Ok(()) => continue,
+                        Err(std::fs::TryLockError::WouldBlock) => return Ok(None),
+                        Err(std::fs::TryLockError::Error(err)) => return Err(err.into()),
+                    }
+                }
+            }
+
+            /// Acquire the lock for the given file, blocking the current
+            /// thread until the lock becomes available.
+            pub fn lock_sync<P : AsRef<Path>>(filename: P) -> Result<FileLock> {
+                let filename = filename.as_ref().to_path_buf();
+                loop {
+                    let file = Self::open(&filename)?;
+                    file.lock()?;
+                    if Self::is_current(&file, &filename) {
+                        return Ok(FileLock { filename, file: Some(file) });
+                    }
+                }
+            }
+
+            fn open(filename: &Path) -> Result<std::fs::File> {
+                Ok(std::fs::OpenOptions::new()
+                    .create(true)
+                    .truncate(false)
+                    .write(true)
+                    .open(lock_filename(filename))?)
+            }
+
+            /// Returns true if the locked file is still the lock file of `filename`
+            /// (i.e. it has not been removed by the previous lock owner after
+            /// it was opened).
+            fn is_current(file: &std::fs::File, filename: &Path) -> bool {
+                cfg_if! {
+                    if #[cfg(unix)] {
+                        use std::os::unix::fs::MetadataExt;
+                        match (file.metadata(), std::fs::metadata(lock_filename(filename))) {
+                            (Ok(locked), Ok(current)) => locked.dev() == current.dev() && locked.ino() == current.ino(),
+                            _ => false,
+                        }
+                    } else {
+                        // files opened by another process can not be removed
+                        let _ = (file, filename);
+                        true
+                    }
+                }
+            }
+
+            pub fn filename(&self) -> &Path {
+                &self.filename
+            }
+        }
+
+        impl Drop for FileLock {
+            fn drop(&mut self) {
+                if let Some(file) = self.file.take() {
+                    // remove the lock file while the lock is held
+                    std::fs::remove_file(lock_filename(&self.filename)).ok();
+                    drop(file);
+                }
+            }
+        }
+
     }
 
 }
 
 #[derive(Clone, Debug)]
 pub struct Metadata {
-    created: Option<u64>,
-    modified: Option<u64>,
-    accessed: Option<u64>,
-    len: Option<u64>,
+    pub(crate) created: Option<u64>,
+    pub(crate) modified: Option<u64>,
+    pub(crate) accessed: Option<u64>,
+    pub(crate) len: Option<u64>,
 }
 
 impl Metadata {
@@ -574,7 +1167,7 @@ impl TryFrom<JsValue> for Metadata {
 #[derive(Clone, Debug)]
 pub struct DirEntry {
     file_name: String,
-    metadata: Option<Metadata>,
+    pub(crate) metadata: Option<Metadata>,
 }
 
 impl DirEntry {
@@ -623,6 +1216,77 @@ impl From<String> for DirEntry {
     }
 }
 
+/// Acquire an advisory lock for the given file, waiting
+/// until the lock becomes available. See [`FileLock`].
+pub async fn lock<P: AsRef<Path>>(filename: P) -> Result<FileLock> {
+    loop {
+        if let Some(lock) = FileLock::try_lock(filename.as_ref())? {
+            return Ok(lock);
+        }
+        workflow_core::task::sleep(LOCK_RETRY_INTERVAL).await;
+    }
+}
+
+/// Acquire an advisory lock for the given file, waiting at most `timeout`
+/// for the lock to become available. Returns [`Error::Locked`] if the
+/// lock could not be acquired within the timeout.
+pub async fn lock_with_timeout<P: AsRef<Path>>(
+    filename: P,
+    timeout: std::time::Duration,
+) -> Result<FileLock> {
+    let start = workflow_core::time::Instant::now();
+    loop {
+        if let Some(lock) = FileLock::try_lock(filename.as_ref())? {
+            return Ok(lock);
+        }
+        if start.elapsed() >= timeout {
+            return Err(Error::Locked(
+                filename.as_ref().to_string_lossy().to_string(),
+            ));
+        }
+        workflow_core::task::sleep(LOCK_RETRY_INTERVAL).await;
+    }
+}
+
+const LOCK_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Age after which a NodeJS lock file whose owner is not known is
+/// considered to be left behind by a crashed process (see [`FileLock`]).
+pub const STALE_LOCK_AGE: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Rename a file or a folder. If using within the web browser
+/// environment, this operation is supported only by the
+/// IndexedDB backend (see [`Backend`]).
+pub async fn rename<P: AsRef<Path>>(from: P, to: P) -> Result<()> {
+    rename_with_options(from, to, Options::default()).await
+}
+
+/// Rename a file or a folder. Not supported in the web browser environment.
+pub fn rename_sync<P: AsRef<Path>>(from: P, to: P) -> Result<()> {
+    rename_with_options_sync(from, to, Options::default())
+}
+
+/// Obtain file metadata. If using within the web browser
+/// environment, this operation is supported only by the
+/// IndexedDB backend (see [`Backend`]).
+pub async fn metadata<P: AsRef<Path>>(filename: P) -> Result<Metadata> {
+    metadata_with_options(filename, Options::default()).await
+}
+
+/// Obtain file metadata. Not supported in the web browser environment.
+pub fn metadata_sync<P: AsRef<Path>>(filename: P) -> Result<Metadata> {
+    metadata_with_options_sync(filename, Options::default())
+}
+
+/// List folder contents. If using within the web browser environment
+/// with localstorage, all localstorage keys are returned.
+pub async fn readdir<P>(path: P, metadata: bool) -> Result<Vec<DirEntry>>
+where
+    P: AsRef<Path> + Send + 'static,
+{
+    readdir_with_options(path, metadata, Options::default()).await
+}
+
 /// Check if a file exists
 pub async fn exists<P: AsRef<Path>>(filename: P) -> Result<bool> {
     exists_with_options(filename, Options::default()).await
@@ -675,6 +1339,16 @@ pub fn write_string_sync(filename: &Path, text: &str) -> Result<()> {
     write_string_with_options_sync(filename, Options::default(), text)
 }
 
+/// Append a string to a text file, creating the file if it does not
+/// exist. If using within the web browser environment, the string
+/// is appended to the local storage key with the name of the file.
+/// On native platforms and NodeJS, the advisory lock of the file
+/// (see [`FileLock`]) is held while appending, as such this function
+/// must not be called while the lock is held by the caller.
+pub async fn append_string(filename: &Path, text: &str) -> Result<()> {
+    append_string_with_options(filename, Options::default(), text).await
+}
+
 /// Write a `Vec<u8>` to a binary file. If using within the web browser
 /// environment, a local storage key with the name of the file
 /// will be used and the data will be hex-encoded.
@@ -886,3 +1560,78 @@ where
     let path = path.replace(from, to);
     PathBuf::from(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_write_backup_and_lock() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("workflow-store-fs-{}", std::process::id()));
+        create_dir_all_sync(&dir)?;
+        let filename = dir.join("settings.json");
+
+        let options = Options::default().with_backups(2);
+        for version in ["v1", "v2", "v3", "v4"] {
+            write_string_with_options_sync(&filename, options.clone(), version)?;
+        }
+        assert_eq!(read_to_string_sync(&filename)?, "v4");
+        assert_eq!(read_to_string_sync(&backup_filename(&filename, 0))?, "v3");
+        assert_eq!(read_to_string_sync(&backup_filename(&filename, 1))?, "v2");
+
+        // no temporary files (or backups beyond the limit) should be left behind
+        let entries = std::fs::read_dir(&dir)?.count();
+        assert_eq!(entries, 3);
+
+        let lock = FileLock::try_lock(&filename)?.expect("lock");
+        assert!(FileLock::try_lock(&filename)?.is_none());
+        drop(lock);
+        assert!(!lock_filename(&filename).exists());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_timeout() -> Result<()> {
+        let filename =
+            std::env::temp_dir().join(format!("workflow-store-lock-{}.json", std::process::id()));
+        let guard = lock(&filename).await?;
+        assert!(matches!(
+            lock_with_timeout(&filename, std::time::Duration::from_millis(100)).await,
+            Err(Error::Locked(_))
+        ));
+        drop(guard);
+        let guard = lock_with_timeout(&filename, std::time::Duration::from_millis(100)).await?;
+        drop(guard);
+        assert!(!lock_filename(&filename).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append() -> Result<()> {
+        let filename =
+            std::env::temp_dir().join(format!("workflow-store-append-{}.txt", std::process::id()));
+        let options = Options::default().with_backups(1);
+        append_string_with_options(&filename, options.clone(), "a\n").await?;
+        append_string_with_options(&filename, options, "b\n").await?;
+        assert_eq!(read_to_string_sync(&filename)?, "a\nb\n");
+        assert_eq!(read_to_string_sync(&backup_filename(&filename, 0))?, "a\n");
+
+        // appends wait for the file lock
+        let guard = lock(&filename).await?;
+        let append = append_string(&filename, "c\n");
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), append)
+                .await
+                .is_err()
+        );
+        drop(guard);
+        append_string(&filename, "c\n").await?;
+        assert_eq!(read_to_string_sync(&filename)?, "a\nb\nc\n");
+
+        std::fs::remove_file(&filename)?;
+        std::fs::remove_file(backup_filename(&filename, 0))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+//!
+//! IndexedDB storage backend used by the [`fs`](crate::fs) module in the
+//! web browser environment when [`Backend::IndexedDb`](crate::fs::Backend)
+//! is selected via [`Options`](crate::fs::Options).
+//!
+//! Unlike localstorage, IndexedDB is asynchronous, is not limited to
+//! ~5MB and allows binary data to be stored natively. Each file is stored
+//! as a record containing the data and its creation/modification time
+//! under a key derived from the normalized file path (see [`key`](crate::kv::key)).
+//!
+
+use crate::error::Error;
+use crate::kv::{KvBackend, KvStore, Record};
+use crate::result::Result;
+use async_trait::async_trait;
+use js_sys::{Object, Promise, Reflect, Uint8Array};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Event, IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransaction,
+    IdbTransactionMode,
+};
+
+/// Default IndexedDB database name.
+pub const DATABASE_NAME: &str = "workflow-store";
+/// IndexedDB object store containing file records.
+pub const OBJECT_STORE: &str = "files";
+const DATABASE_VERSION: u32 = 1;
+
+thread_local! {
+    static DATABASE: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+/// Resolves when the supplied request completes, returning the request result.
+async fn request(request: &IdbRequest) -> Result<JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let result = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+
+    match result {
+        Ok(_) => Ok(request.result()?),
+        Err(_) => Err(request_error(request)),
+    }
+}
+
+fn request_error(request: &IdbRequest) -> Error {
+    match request.error() {
+        Ok(Some(err)) => Error::Custom(format!("IndexedDB: {}", err.message())),
+        _ => Error::Custom("IndexedDB: request failed".to_string()),
+    }
+}
+
+/// Resolves when the supplied transaction is committed (or fails
+/// if the transaction is aborted).
+async fn transaction(transaction: &IdbTransaction) -> Result<()> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        transaction.set_oncomplete(Some(&resolve));
+        transaction.set_onerror(Some(&reject));
+        transaction.set_onabort(Some(&reject));
+    });
+    let result = JsFuture::from(promise).await;
+    transaction.set_oncomplete(None);
+    transaction.set_onerror(None);
+    transaction.set_onabort(None);
+
+    result.map(|_| ()).map_err(|_| match transaction.error() {
+        Some(err) => Error::Custom(format!("IndexedDB: {}", err.message())),
+        None => Error::Custom("IndexedDB: transaction failed".to_string()),
+    })
+}
+
+fn record_to_js(record: &Record) -> Result<JsValue> {
+    let object = Object::new();
+    Reflect::set(
+        &object,
+        &"data".into(),
+        &Uint8Array::from(record.data.as_slice()),
+    )?;
+    Reflect::set(&object, &"created".into(), &(record.created as f64).into())?;
+    Reflect::set(
+        &object,
+        &"modified".into(),
+        &(record.modified as f64).into(),
+    )?;
+    Ok(object.into())
+}
+
+fn record_from_js(value: JsValue) -> Result<Option<Record>> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(None);
+    }
+
+    let data = Reflect::get(&value, &"data".into())?
+        .dyn_into::<Uint8Array>()
+        .map_err(|_| Error::DataIsNotABuffer(OBJECT_STORE.to_string()))?
+        .to_vec();
+    let created = Reflect::get(&value, &"created".into())?
+        .as_f64()
+        .unwrap_or_default() as u64;
+    let modified = Reflect::get(&value, &"modified".into())?
+        .as_f64()
+        .unwrap_or_default() as u64;
+
+    Ok(Some(Record {
+        data,
+        created,
+        modified,
+    }))
+}
+
+/// IndexedDB [`KvBackend`] implementation.
+#[derive(Clone)]
+pub struct IdbBackend {
+    db: IdbDatabase,
+}
+
+impl IdbBackend {
+    /// Open (creating if needed) the IndexedDB database with the given name.
+    pub async fn open(name: &str) -> Result<Self> {
+        let factory = web_sys::window()
+            .ok_or(Error::NotSupported)?
+            .indexed_db()?
+            .ok_or(Error::NotSupported)?;
+        let open_request = factory.open_with_u32(name, DATABASE_VERSION)?;
+
+        let onupgradeneeded = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+            let Some(target) = event.target() else {
+                return;
+            };
+            let open_request = target.unchecked_into::<IdbOpenDbRequest>();
+            if let Ok(db) = open_request.result() {
+                let db = db.unchecked_into::<IdbDatabase>();
+                if !db.object_store_names().contains(OBJECT_STORE) {
+                    db.create_object_store(OBJECT_STORE).ok();
+                }
+            }
+        });
+        open_request.set_onupgradeneeded(Some(onupgradeneeded.as_ref().unchecked_ref()));
+        let result = request(&open_request).await;
+        open_request.set_onupgradeneeded(None);
+
+        Ok(IdbBackend {
+            db: result?.unchecked_into(),
+        })
+    }
+
+    fn object_store(&self, mode: IdbTransactionMode) -> Result<(IdbTransaction, IdbObjectStore)> {
+        let transaction = self.db.transaction_with_str_and_mode(OBJECT_STORE, mode)?;
+        let store = transaction.object_store(OBJECT_STORE)?;
+        Ok((transaction, store))
+    }
+}
+
+#[async_trait(?Send)]
+impl KvBackend for IdbBackend {
+    async fn get(&self, key: &str) -> Result<Option<Record>> {
+        let (_, store) = self.object_store(IdbTransactionMode::Readonly)?;
+        let value = request(&store.get(&key.into())?).await?;
+        record_from_js(value)
+    }
+
+    async fn set(&self, key: &str, record: Record) -> Result<()> {
+        let (tx, store) = self.object_store(IdbTransactionMode::Readwrite)?;
+        store.put_with_key(&record_to_js(&record)?, &key.into())?;
+        transaction(&tx).await
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let (tx, store) = self.object_store(IdbTransactionMode::Readwrite)?;
+        store.delete(&key.into())?;
+        transaction(&tx).await
+    }
+
+    async fn keys(&self) -> Result<Vec<String>> {
+        let (_, store) = self.object_store(IdbTransactionMode::Readonly)?;
+        let keys = request(&store.get_all_keys()?).await?;
+        Ok(keys
+            .dyn_into::<js_sys::Array>()?
+            .iter()
+            .filter_map(|key| key.as_string())
+            .collect())
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let (tx, store) = self.object_store(IdbTransactionMode::Readwrite)?;
+        let get = store.get(&from.into())?;
+
+        // the transaction is committed once control returns to the event
+        // loop, as such the put and delete requests are issued from the
+        // success callback of the get request (while it is still active)
+        let missing = Rc::new(Cell::new(false));
+        let onsuccess = {
+            let (get, tx, missing) = (get.clone(), tx.clone(), missing.clone());
+            let (from, to) = (JsValue::from(from), JsValue::from(to));
+            Closure::<dyn FnMut(Event)>::new(move |_: Event| {
+                let value = get.result().unwrap_or(JsValue::UNDEFINED);
+                if value.is_undefined() {
+                    missing.set(true);
+                    tx.abort().ok();
+                } else if store.put_with_key(&value, &to).is_err() || store.delete(&from).is_err() {
+                    tx.abort().ok();
+                }
+            })
+        };
+        get.set_onsuccess(Some(onsuccess.as_ref().unchecked_ref()));
+        let result = transaction(&tx).await;
+        get.set_onsuccess(None);
+
+        if missing.get() {
+            return Err(Error::NotFound(from.to_string()));
+        }
+        result
+    }
+}
+
+/// Returns a [`KvStore`] backed by the default IndexedDB database.
+/// The database connection is opened on first use and cached.
+pub async fn store() -> Result<KvStore<IdbBackend>> {
+    if let Some(db) = DATABASE.with(|db| db.borrow().clone()) {
+        return Ok(KvStore::new(IdbBackend { db }));
+    }
+
+    let backend = IdbBackend::open(DATABASE_NAME).await?;
+    DATABASE.with(|db| db.borrow_mut().replace(backend.db.clone()));
+    Ok(KvStore::new(backend))
+}
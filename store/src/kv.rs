@@ -0,0 +1,281 @@
+//!
+//! Key/value storage layer used by browser storage backends.
+//!
+//! [`KvStore`] implements file-system semantics (paths, directory
+//! listing, rename, metadata) on top of a flat key/value [`KvBackend`].
+//! Keys are normalized unix-style paths, as such `readdir()` of a folder
+//! returns all keys sharing the folder prefix (sub-folders are returned
+//! as entries without metadata).
+//!
+//! [`MemoryBackend`] is an in-memory backend that is available on all
+//! platforms and can be used to exercise the storage logic in tests.
+//!
+
+use crate::error::Error;
+use crate::fs::{normalize, DirEntry, Metadata};
+use crate::result::Result;
+use async_trait::async_trait;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Mutex;
+use workflow_core::time::unixtime_as_millis_u64;
+
+/// A single stored entry.
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub data: Vec<u8>,
+    /// creation time (unix time in milliseconds)
+    pub created: u64,
+    /// last modification time (unix time in milliseconds)
+    pub modified: u64,
+}
+
+impl Record {
+    pub fn new(data: Vec<u8>) -> Self {
+        let now = unixtime_as_millis_u64();
+        Record {
+            data,
+            created: now,
+            modified: now,
+        }
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        Metadata {
+            created: Some(self.created / 1000),
+            modified: Some(self.modified / 1000),
+            accessed: None,
+            len: Some(self.data.len() as u64),
+        }
+    }
+}
+
+/// Flat key/value storage backend.
+#[async_trait(?Send)]
+pub trait KvBackend {
+    async fn get(&self, key: &str) -> Result<Option<Record>>;
+    async fn set(&self, key: &str, record: Record) -> Result<()>;
+    async fn remove(&self, key: &str) -> Result<()>;
+    async fn keys(&self) -> Result<Vec<String>>;
+
+    /// Move a record from one key to another. Backends supporting
+    /// transactions should override this to perform the move atomically.
+    async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let record = self
+            .get(from)
+            .await?
+            .ok_or_else(|| Error::NotFound(from.to_string()))?;
+        self.set(to, record).await?;
+        self.remove(from).await
+    }
+}
+
+/// Converts a path to a storage key.
+pub fn key<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = normalize(path)?;
+    let key = path.to_string_lossy().replace('\\', "/");
+    Ok(key.trim_end_matches('/').to_string())
+}
+
+fn folder_prefix(key: &str) -> String {
+    if key.is_empty() {
+        String::new()
+    } else {
+        format!("{key}/")
+    }
+}
+
+/// File-system semantics on top of a [`KvBackend`].
+pub struct KvStore<B> {
+    backend: B,
+}
+
+impl<B> KvStore<B>
+where
+    B: KvBackend,
+{
+    pub fn new(backend: B) -> Self {
+        KvStore { backend }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub async fn exists<P: AsRef<Path>>(&self, path: P) -> Result<bool> {
+        Ok(self.backend.get(&key(path)?).await?.is_some())
+    }
+
+    pub async fn read<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>> {
+        let key = key(path)?;
+        let record = self.backend.get(&key).await?;
+        Ok(record.ok_or(Error::NotFound(key))?.data)
+    }
+
+    pub async fn read_to_string<P: AsRef<Path>>(&self, path: P) -> Result<String> {
+        let key = key(path)?;
+        let data = self.read(&key).await?;
+        String::from_utf8(data).map_err(|_| Error::DataIsNotAString(key))
+    }
+
+    pub async fn write<P: AsRef<Path>>(&self, path: P, data: &[u8]) -> Result<()> {
+        let key = key(path)?;
+        let mut record = Record::new(data.to_vec());
+        if let Some(existing) = self.backend.get(&key).await? {
+            record.created = existing.created;
+        }
+        self.backend.set(&key, record).await
+    }
+
+    pub async fn remove<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let key = key(path)?;
+        if self.backend.get(&key).await?.is_none() {
+            return Err(Error::NotFound(key));
+        }
+        self.backend.remove(&key).await
+    }
+
+    /// Rename a file or a folder (all keys sharing the folder prefix).
+    pub async fn rename<P: AsRef<Path>>(&self, from: P, to: P) -> Result<()> {
+        let from = key(from)?;
+        let to = key(to)?;
+
+        if self.backend.get(&from).await?.is_some() {
+            return self.backend.rename(&from, &to).await;
+        }
+
+        let from_prefix = folder_prefix(&from);
+        let to_prefix = folder_prefix(&to);
+        let keys = self
+            .backend
+            .keys()
+            .await?
+            .into_iter()
+            .filter(|k| k.starts_with(&from_prefix))
+            .collect::<Vec<_>>();
+
+        if keys.is_empty() {
+            return Err(Error::NotFound(from));
+        }
+
+        for key in keys {
+            let target = format!("{to_prefix}{}", &key[from_prefix.len()..]);
+            self.backend.rename(&key, &target).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn metadata<P: AsRef<Path>>(&self, path: P) -> Result<Metadata> {
+        let key = key(path)?;
+        let record = self.backend.get(&key).await?;
+        Ok(record.ok_or(Error::NotFound(key))?.metadata())
+    }
+
+    /// List the contents of a folder. Sub-folders are
+    /// returned as entries without metadata.
+    pub async fn readdir<P: AsRef<Path>>(&self, path: P, metadata: bool) -> Result<Vec<DirEntry>> {
+        let prefix = folder_prefix(&key(path)?);
+
+        let mut files = BTreeSet::new();
+        let mut folders = BTreeSet::new();
+        for key in self.backend.keys().await? {
+            if let Some(name) = key.strip_prefix(&prefix) {
+                match name.split_once('/') {
+                    Some((folder, _)) => {
+                        folders.insert(folder.to_string());
+                    }
+                    None => {
+                        files.insert(name.to_string());
+                    }
+                }
+            }
+        }
+
+        let mut entries = folders.into_iter().map(DirEntry::from).collect::<Vec<_>>();
+        for file in files {
+            let mut entry = DirEntry::from(file);
+            if metadata {
+                let key = format!("{prefix}{}", entry.file_name());
+                entry.metadata = self.backend.get(&key).await?.map(|r| r.metadata());
+            }
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+}
+
+/// In-memory [`KvBackend`] implementation.
+#[derive(Default)]
+pub struct MemoryBackend {
+    records: Mutex<BTreeMap<String, Record>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait(?Send)]
+impl KvBackend for MemoryBackend {
+    async fn get(&self, key: &str) -> Result<Option<Record>> {
+        Ok(self.records.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, record: Record) -> Result<()> {
+        self.records.lock().unwrap().insert(key.to_string(), record);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.records.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn keys(&self) -> Result<Vec<String>> {
+        Ok(self.records.lock().unwrap().keys().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task::block_on;
+
+    #[test]
+    fn test_kv_store() -> Result<()> {
+        block_on(async {
+            let store = KvStore::new(MemoryBackend::new());
+
+            store.write("~/.app/settings.json", b"{}").await?;
+            store.write("~/.app/data/wallet.bin", &[0, 1, 2]).await?;
+            assert!(store.exists("~/.app/./settings.json").await?);
+            assert_eq!(store.read("~/.app/data/wallet.bin").await?, vec![0, 1, 2]);
+            assert_eq!(store.read_to_string("~/.app/settings.json").await?, "{}");
+            assert_eq!(
+                store.metadata("~/.app/data/wallet.bin").await?.len(),
+                Some(3)
+            );
+
+            let entries = store.readdir("~/.app", true).await?;
+            let names = entries.iter().map(|e| e.file_name()).collect::<Vec<_>>();
+            assert_eq!(names, vec!["data", "settings.json"]);
+            assert!(entries[0].metadata().is_none());
+            assert_eq!(entries[1].metadata().unwrap().len(), Some(2));
+
+            store.rename("~/.app/data", "~/.app/archive").await?;
+            assert!(!store.exists("~/.app/data/wallet.bin").await?);
+            assert!(store.exists("~/.app/archive/wallet.bin").await?);
+
+            store.remove("~/.app/settings.json").await?;
+            assert!(matches!(
+                store.remove("~/.app/settings.json").await,
+                Err(Error::NotFound(_))
+            ));
+
+            Ok(())
+        })
+    }
+}
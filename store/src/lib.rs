@@ -21,6 +21,14 @@ cfg_if! {
         pub mod error;
         pub mod result;
         pub mod fs;
+        #[cfg(feature = "crash-report")]
+        pub mod crash;
+        pub mod encrypted;
+        pub mod kv;
         pub mod store;
+        pub mod watch;
     }
 }
+
+#[cfg(target_arch = "wasm32")]
+pub mod idb;
@@ -1,2 +1,5 @@
+#[cfg(feature = "crash-report")]
+pub use crate::crash::CrashReportStore;
+pub use crate::encrypted::EncryptedStore;
 pub use crate::fs;
 pub use crate::store;
@@ -0,0 +1,466 @@
+//!
+//! File change notifications.
+//!
+//! [`watch()`] returns a [`Watcher`] producing an async stream of
+//! [`WatchEvent`]s when the watched file (or files within the watched
+//! folder) are created, modified or removed.
+//!
+//! - native platforms use the OS-specific watcher (inotify, FSEvents,
+//!   ReadDirectoryChangesW) via the [`notify`](https://crates.io/crates/notify) crate
+//! - NodeJS uses `fs.watch()`
+//! - in the browser, localstorage `storage` events are used. Please note
+//!   that these events are only delivered for changes made by other
+//!   windows or tabs sharing the same origin.
+//!
+//! Events are debounced: bursts of events for the same path are coalesced
+//! and delivered once no further events have been received during the
+//! debounce interval. When watching a file, the parent folder is monitored,
+//! which allows changes made via atomic writes (rename) to be detected.
+//!
+
+use crate::error::Error;
+use crate::result::Result;
+use cfg_if::cfg_if;
+use futures::{select, FutureExt, Stream};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use workflow_core::channel::{unbounded, Receiver, Sender};
+use workflow_core::task::{sleep, spawn};
+
+/// Default debounce interval.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchEventKind {
+    Create,
+    Modify,
+    Remove,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchEvent {
+    pub kind: WatchEventKind,
+    pub path: PathBuf,
+}
+
+impl WatchEvent {
+    pub fn new(kind: WatchEventKind, path: PathBuf) -> Self {
+        WatchEvent { kind, path }
+    }
+}
+
+/// Options for [`watch_with_options()`].
+#[derive(Clone, Debug)]
+pub struct WatchOptions {
+    /// Events for the same path received within this interval are coalesced
+    pub debounce: Duration,
+    /// Watch sub-folders when watching a folder (native and NodeJS only)
+    pub recursive: bool,
+    /// Localstorage key to watch in the web browser environment
+    /// (defaults to the file name, as in [`Options`](crate::fs::Options))
+    pub local_storage_key: Option<String>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        WatchOptions {
+            debounce: DEFAULT_DEBOUNCE,
+            recursive: false,
+            local_storage_key: None,
+        }
+    }
+}
+
+impl WatchOptions {
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn with_local_storage_key(mut self, key: &str) -> Self {
+        self.local_storage_key = Some(key.to_string());
+        self
+    }
+}
+
+/// Coalesce two consecutive events for the same path.
+/// Returns `None` if the events cancel each other out.
+fn coalesce(previous: WatchEventKind, next: WatchEventKind) -> Option<WatchEventKind> {
+    use WatchEventKind::*;
+    match (previous, next) {
+        (Create, Remove) => None,
+        (Create, _) => Some(Create),
+        (Remove, Create) => Some(Modify),
+        (_, next) => Some(next),
+    }
+}
+
+/// Accumulates events preserving the order in which paths were first seen.
+#[derive(Default)]
+struct Pending {
+    events: Vec<WatchEvent>,
+}
+
+impl Pending {
+    fn push(&mut self, event: WatchEvent) {
+        if let Some(index) = self.events.iter().position(|e| e.path == event.path) {
+            match coalesce(self.events[index].kind, event.kind) {
+                Some(kind) => self.events[index].kind = kind,
+                None => {
+                    self.events.remove(index);
+                }
+            }
+        } else {
+            self.events.push(event);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    fn take(&mut self) -> Vec<WatchEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+async fn debounce(raw: Receiver<WatchEvent>, sender: Sender<WatchEvent>, interval: Duration) {
+    let mut pending = Pending::default();
+    loop {
+        let closed = if pending.is_empty() {
+            match raw.recv().await {
+                Ok(event) => {
+                    pending.push(event);
+                    false
+                }
+                Err(_) => true,
+            }
+        } else {
+            select! {
+                event = raw.recv().fuse() => match event {
+                    Ok(event) => {
+                        pending.push(event);
+                        continue;
+                    }
+                    Err(_) => true,
+                },
+                _ = sleep(interval).fuse() => false,
+            }
+        };
+
+        for event in pending.take() {
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+
+        if closed {
+            break;
+        }
+    }
+}
+
+///
+/// File change watcher returned by [`watch()`]. Implements [`Stream`].
+/// Watching stops when the watcher is dropped.
+///
+pub struct Watcher {
+    receiver: Pin<Box<Receiver<WatchEvent>>>,
+    path: PathBuf,
+    _inner: inner::Inner,
+}
+
+impl Watcher {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Receive the next event. Returns `None` if the watcher has been closed.
+    pub async fn recv(&self) -> Option<WatchEvent> {
+        self.receiver.recv().await.ok()
+    }
+}
+
+impl Stream for Watcher {
+    type Item = WatchEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.as_mut().poll_next(cx)
+    }
+}
+
+/// Watch a file or a folder for changes using default [`WatchOptions`].
+pub fn watch<P: AsRef<Path>>(path: P) -> Result<Watcher> {
+    watch_with_options(path, WatchOptions::default())
+}
+
+/// Watch a file or a folder for changes.
+pub fn watch_with_options<P: AsRef<Path>>(path: P, options: WatchOptions) -> Result<Watcher> {
+    let path = path.as_ref().to_path_buf();
+    let (raw_sender, raw_receiver) = unbounded();
+    let (sender, receiver) = unbounded();
+    let inner = inner::Inner::try_new(&path, &options, raw_sender)?;
+    spawn(debounce(raw_receiver, sender, options.debounce));
+
+    Ok(Watcher {
+        receiver: Box::pin(receiver),
+        path,
+        _inner: inner,
+    })
+}
+
+cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+
+        mod inner {
+            use super::*;
+            use crate::fs::ToPlatform;
+            use js_sys::{Object, Reflect};
+            use wasm_bindgen::prelude::*;
+            use web_sys::StorageEvent;
+            use workflow_core::runtime;
+            use workflow_node as node;
+
+            pub enum Inner {
+                Node {
+                    watcher: node::fs::FsWatcher,
+                    _listener: Closure<dyn FnMut(JsValue, JsValue)>,
+                },
+                Browser {
+                    listener: Closure<dyn FnMut(StorageEvent)>,
+                },
+            }
+
+            impl Inner {
+                pub fn try_new(path: &Path, options: &WatchOptions, sender: Sender<WatchEvent>) -> Result<Self> {
+                    if runtime::is_node() || runtime::is_nw() {
+                        Self::try_new_node(path, options, sender)
+                    } else if runtime::is_chrome_extension() {
+                        Err(Error::NotSupported)
+                    } else {
+                        Self::try_new_browser(path, options, sender)
+                    }
+                }
+
+                fn try_new_node(path: &Path, options: &WatchOptions, sender: Sender<WatchEvent>) -> Result<Self> {
+                    let is_dir = node::fs::stat_sync(&path.to_platform_string())
+                        .ok()
+                        .and_then(|stat| Reflect::get(&stat, &"isDirectory".into()).ok())
+                        .and_then(|f| f.dyn_into::<js_sys::Function>().ok())
+                        .map(|f| f.call0(&JsValue::UNDEFINED).ok().and_then(|v| v.as_bool()).unwrap_or(false))
+                        .unwrap_or(false);
+
+                    let (folder, target) = if is_dir {
+                        (path.to_path_buf(), None)
+                    } else {
+                        let folder = path.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."));
+                        (folder, Some(path.to_path_buf()))
+                    };
+
+                    let root = folder.clone();
+                    let listener = Closure::<dyn FnMut(JsValue, JsValue)>::new(move |event: JsValue, filename: JsValue| {
+                        let Some(filename) = filename.as_string() else {
+                            return;
+                        };
+                        let path = root.join(filename);
+                        if target.as_ref().is_some_and(|target| target != &path) {
+                            return;
+                        }
+                        let kind = if event.as_string().as_deref() == Some("change") {
+                            WatchEventKind::Modify
+                        } else if node::fs::exists_sync(&path.to_platform_string()).unwrap_or(false) {
+                            WatchEventKind::Create
+                        } else {
+                            WatchEventKind::Remove
+                        };
+                        sender.try_send(WatchEvent::new(kind, path)).ok();
+                    });
+
+                    let watch_options = Object::new();
+                    Reflect::set(&watch_options, &"recursive".into(), &(is_dir && options.recursive).into())?;
+                    let watcher = node::fs::watch(&folder.to_platform_string(), watch_options, listener.as_ref().unchecked_ref())?;
+
+                    Ok(Inner::Node { watcher, _listener: listener })
+                }
+
+                fn try_new_browser(path: &Path, options: &WatchOptions, sender: Sender<WatchEvent>) -> Result<Self> {
+                    let key = crate::fs::Options {
+                        local_storage_key: options.local_storage_key.clone(),
+                        ..Default::default()
+                    }.local_storage_key(path);
+
+                    let path = path.to_path_buf();
+                    let listener = Closure::<dyn FnMut(StorageEvent)>::new(move |event: StorageEvent| {
+                        // `key` is null when the storage is cleared
+                        if event.key().is_some_and(|k| k != key) {
+                            return;
+                        }
+                        let kind = match (event.old_value(), event.new_value()) {
+                            (_, None) => WatchEventKind::Remove,
+                            (None, Some(_)) => WatchEventKind::Create,
+                            (Some(_), Some(_)) => WatchEventKind::Modify,
+                        };
+                        sender.try_send(WatchEvent::new(kind, path.clone())).ok();
+                    });
+
+                    let window = web_sys::window().ok_or(Error::NotSupported)?;
+                    window.add_event_listener_with_callback("storage", listener.as_ref().unchecked_ref())?;
+
+                    Ok(Inner::Browser { listener })
+                }
+            }
+
+            impl Drop for Inner {
+                fn drop(&mut self) {
+                    match self {
+                        Inner::Node { watcher, .. } => watcher.close(),
+                        Inner::Browser { listener } => {
+                            if let Some(window) = web_sys::window() {
+                                window.remove_event_listener_with_callback("storage", listener.as_ref().unchecked_ref()).ok();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+    } else {
+
+        mod inner {
+            use super::*;
+            use notify::event::{ModifyKind, RenameMode};
+            use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+            use std::collections::HashSet;
+
+            pub struct Inner {
+                _watcher: RecommendedWatcher,
+            }
+
+            impl Inner {
+                pub fn try_new(path: &Path, options: &WatchOptions, sender: Sender<WatchEvent>) -> Result<Self> {
+                    let (folder, target, mode) = if path.is_dir() {
+                        let mode = if options.recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+                        (path.to_path_buf(), None, mode)
+                    } else {
+                        let folder = match path.parent() {
+                            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                            _ => PathBuf::from("."),
+                        };
+                        let file_name = path.file_name().map(|f| f.to_os_string());
+                        (folder, file_name, RecursiveMode::NonRecursive)
+                    };
+
+                    // paths known to exist, used to report a file replaced
+                    // by a rename (i.e. an atomic write) as modified
+                    let mut existing: HashSet<PathBuf> = match target.as_ref() {
+                        Some(file_name) => {
+                            let path = folder.join(file_name);
+                            path.exists().then_some(path).into_iter().collect()
+                        }
+                        None => std::fs::read_dir(&folder)?
+                            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                            .collect(),
+                    };
+
+                    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                        let Ok(event) = event else {
+                            return;
+                        };
+
+                        for path in event.paths {
+                            if let Some(target) = target.as_ref() {
+                                if path.file_name() != Some(target.as_os_str()) {
+                                    continue;
+                                }
+                            }
+
+                            // `RenameMode::Both` is ignored as watcher backends
+                            // also report rename source and target separately
+                            let kind = match event.kind {
+                                EventKind::Create(_) => WatchEventKind::Create,
+                                EventKind::Remove(_) => WatchEventKind::Remove,
+                                EventKind::Modify(ModifyKind::Name(RenameMode::From)) => WatchEventKind::Remove,
+                                EventKind::Modify(ModifyKind::Name(RenameMode::To)) if existing.contains(&path) => WatchEventKind::Modify,
+                                EventKind::Modify(ModifyKind::Name(RenameMode::To)) => WatchEventKind::Create,
+                                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => return,
+                                EventKind::Modify(_) | EventKind::Any => WatchEventKind::Modify,
+                                _ => return,
+                            };
+
+                            if kind == WatchEventKind::Remove {
+                                existing.remove(&path);
+                            } else {
+                                existing.insert(path.clone());
+                            }
+                            sender.try_send(WatchEvent::new(kind, path)).ok();
+                        }
+                    }).map_err(|err| Error::Watch(err.to_string()))?;
+
+                    watcher.watch(&folder, mode).map_err(|err| Error::Watch(err.to_string()))?;
+
+                    Ok(Inner { _watcher: watcher })
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WatchEventKind::*;
+
+    #[test]
+    fn test_watch_event_coalescing() {
+        let mut pending = Pending::default();
+        pending.push(WatchEvent::new(Create, "a".into()));
+        pending.push(WatchEvent::new(Modify, "a".into()));
+        pending.push(WatchEvent::new(Modify, "b".into()));
+        pending.push(WatchEvent::new(Remove, "c".into()));
+        pending.push(WatchEvent::new(Create, "c".into()));
+        pending.push(WatchEvent::new(Create, "d".into()));
+        pending.push(WatchEvent::new(Remove, "d".into()));
+        pending.push(WatchEvent::new(Remove, "b".into()));
+
+        assert_eq!(
+            pending.take(),
+            vec![
+                WatchEvent::new(Create, "a".into()),
+                WatchEvent::new(Remove, "b".into()),
+                WatchEvent::new(Modify, "c".into()),
+            ]
+        );
+        assert!(pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_watch_file() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("workflow-store-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let filename = dir.join("config.json");
+
+        let watcher = watch(&filename)?;
+        crate::fs::write_string_sync(&filename, "{}")?;
+        let event = watcher.recv().await.expect("event");
+        assert_eq!(event, WatchEvent::new(Create, filename.clone()));
+
+        // atomic writes replace the file using a rename
+        crate::fs::write_string_sync(&filename, "{\"version\":2}")?;
+        let event = watcher.recv().await.expect("event");
+        assert_eq!(event, WatchEvent::new(Modify, filename.clone()));
+
+        std::fs::remove_file(&filename)?;
+        let event = watcher.recv().await.expect("event");
+        assert_eq!(event, WatchEvent::new(Remove, filename.clone()));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,772 @@
+//!
+//! Declarative command-line argument specification for [`Handler`](crate::cli::Handler)
+//! implementations.
+//!
+//! A [`Command`] describes positional arguments, flags, typed options and
+//! subcommands of a handler. When returned from [`Handler::args`](crate::cli::Handler::args),
+//! [`HandlerCli`](crate::cli::HandlerCli) validates the command line before
+//! invoking the handler, responds to `-h`/`--help` with the generated help
+//! text and completes subcommands, option names and option values.
+//!
+//! ```rust
+//! use workflow_terminal::args::{Arg, Command, ValueKind};
+//!
+//! let spec = Command::new("peer")
+//!     .with_about("Manage peers")
+//!     .with_subcommand(
+//!         Command::new("add")
+//!             .with_about("Add a peer")
+//!             .with_arg(Arg::positional("address").with_required(true))
+//!             .with_arg(Arg::option("timeout").with_short('t').with_kind(ValueKind::Unsigned).with_default("10"))
+//!             .with_arg(Arg::flag("verbose").with_short('v')),
+//!     );
+//!
+//! let argv = ["add", "-v", "10.0.0.1", "--timeout=30"].map(String::from);
+//! let matches = spec.parse(&argv).unwrap();
+//! let add = matches.subcommand().unwrap();
+//! assert_eq!(add.name(), "add");
+//! assert_eq!(add.value("address"), Some("10.0.0.1"));
+//! assert_eq!(add.get::<u64>("timeout").unwrap(), Some(30));
+//! assert!(add.flag("verbose"));
+//! ```
+//!
+
+use crate::error::Error;
+use crate::result::Result;
+use crate::CrLf;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Type of an argument value, validated during parsing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ValueKind {
+    #[default]
+    String,
+    /// signed integer (`i64`)
+    Integer,
+    /// unsigned integer (`u64`)
+    Unsigned,
+    /// floating point number (`f64`)
+    Float,
+    /// `true`/`false`, `yes`/`no`, `on`/`off` or `1`/`0`
+    Bool,
+    /// one of the supplied values
+    Choice(Vec<String>),
+}
+
+impl ValueKind {
+    pub fn choice<S: ToString>(values: &[S]) -> Self {
+        ValueKind::Choice(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    fn validate(&self, value: &str) -> std::result::Result<(), String> {
+        let valid = match self {
+            ValueKind::String => true,
+            ValueKind::Integer => value.parse::<i64>().is_ok(),
+            ValueKind::Unsigned => value.parse::<u64>().is_ok(),
+            ValueKind::Float => value.parse::<f64>().is_ok(),
+            ValueKind::Bool => parse_bool(value).is_some(),
+            ValueKind::Choice(choices) => choices.iter().any(|choice| choice == value),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("expected {}", self.describe()))
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            ValueKind::String => "a string".to_string(),
+            ValueKind::Integer => "an integer".to_string(),
+            ValueKind::Unsigned => "an unsigned integer".to_string(),
+            ValueKind::Float => "a number".to_string(),
+            ValueKind::Bool => "a boolean".to_string(),
+            ValueKind::Choice(choices) => format!("one of: {}", choices.join(", ")),
+        }
+    }
+
+    fn placeholder(&self, name: &str) -> String {
+        match self {
+            ValueKind::String => format!("<{name}>"),
+            ValueKind::Integer => "<int>".to_string(),
+            ValueKind::Unsigned => "<uint>".to_string(),
+            ValueKind::Float => "<float>".to_string(),
+            ValueKind::Bool => "<bool>".to_string(),
+            ValueKind::Choice(choices) => format!("<{}>", choices.join("|")),
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgType {
+    Positional,
+    Flag,
+    Option,
+}
+
+/// Specification of a single argument: a positional argument,
+/// a flag (`--verbose`) or an option taking a value (`--timeout 10`).
+#[derive(Debug, Clone)]
+pub struct Arg {
+    name: String,
+    arg_type: ArgType,
+    short: Option<char>,
+    help: String,
+    kind: ValueKind,
+    required: bool,
+    multiple: bool,
+    default: Option<String>,
+}
+
+impl Arg {
+    fn new(name: &str, arg_type: ArgType) -> Self {
+        Arg {
+            name: name.to_string(),
+            arg_type,
+            short: None,
+            help: String::new(),
+            kind: ValueKind::default(),
+            required: false,
+            multiple: false,
+            default: None,
+        }
+    }
+
+    /// Positional argument (matched in the order of declaration).
+    pub fn positional(name: &str) -> Self {
+        Self::new(name, ArgType::Positional)
+    }
+
+    /// Boolean flag (`--name` or `-n`).
+    pub fn flag(name: &str) -> Self {
+        Self::new(name, ArgType::Flag)
+    }
+
+    /// Option taking a value (`--name value`, `--name=value` or `-n value`).
+    pub fn option(name: &str) -> Self {
+        Self::new(name, ArgType::Option)
+    }
+
+    /// Set short (single character) name of a flag or an option.
+    pub fn with_short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    /// Set help text
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = help.to_string();
+        self
+    }
+
+    /// Set value type
+    pub fn with_kind(mut self, kind: ValueKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Require the argument to be present
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Allow the argument to be supplied multiple times (a variadic
+    /// positional argument must be declared last).
+    pub fn with_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    /// Set default value used when the argument is not supplied
+    pub fn with_default(mut self, default: &str) -> Self {
+        self.default = Some(default.to_string());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> String {
+        match self.arg_type {
+            ArgType::Positional => {
+                let name = format!("<{}>", self.name);
+                match (self.required, self.multiple) {
+                    (true, false) => name,
+                    (true, true) => format!("{name}..."),
+                    (false, false) => format!("[{name}]"),
+                    (false, true) => format!("[{name}...]"),
+                }
+            }
+            ArgType::Flag | ArgType::Option => {
+                let mut signature = match self.short {
+                    Some(short) => format!("-{short}, --{}", self.name),
+                    None => format!("    --{}", self.name),
+                };
+                if self.arg_type == ArgType::Option {
+                    signature.push(' ');
+                    signature.push_str(&self.kind.placeholder(&self.name));
+                }
+                signature
+            }
+        }
+    }
+
+    fn validate(&self, value: &str) -> std::result::Result<(), String> {
+        self.kind.validate(value).map_err(|err| {
+            let name = match self.arg_type {
+                ArgType::Positional => format!("<{}>", self.name),
+                _ => format!("--{}", self.name),
+            };
+            format!("invalid value '{value}' for {name}: {err}")
+        })
+    }
+}
+
+/// Specification of a command (or a subcommand).
+#[derive(Debug, Clone)]
+pub struct Command {
+    name: String,
+    about: String,
+    args: Vec<Arg>,
+    subcommands: Vec<Command>,
+}
+
+impl Command {
+    pub fn new(name: &str) -> Self {
+        Command {
+            name: name.to_string(),
+            about: String::new(),
+            args: Vec::new(),
+            subcommands: Vec::new(),
+        }
+    }
+
+    /// Set a short description of the command
+    pub fn with_about(mut self, about: &str) -> Self {
+        self.about = about.to_string();
+        self
+    }
+
+    /// Add an argument
+    pub fn with_arg(mut self, arg: Arg) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    /// Add a subcommand. If the command also accepts positional
+    /// arguments, the first positional word not matching any of
+    /// the subcommands is treated as a positional argument.
+    pub fn with_subcommand(mut self, subcommand: Command) -> Self {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn about(&self) -> &str {
+        &self.about
+    }
+
+    fn positionals(&self) -> impl Iterator<Item = &Arg> {
+        self.args
+            .iter()
+            .filter(|arg| arg.arg_type == ArgType::Positional)
+    }
+
+    fn named(&self) -> impl Iterator<Item = &Arg> {
+        self.args
+            .iter()
+            .filter(|arg| arg.arg_type != ArgType::Positional)
+    }
+
+    fn find_long(&self, name: &str) -> Option<&Arg> {
+        self.named().find(|arg| arg.name == name)
+    }
+
+    fn find_short(&self, short: char) -> Option<&Arg> {
+        self.named().find(|arg| arg.short == Some(short))
+    }
+
+    fn find_subcommand(&self, name: &str) -> Option<&Command> {
+        self.subcommands
+            .iter()
+            .find(|subcommand| subcommand.name == name)
+    }
+
+    /// Parse command arguments (excluding the command name itself).
+    /// Returns [`Error::Usage`] containing the help text if `-h` or
+    /// `--help` is supplied and [`Error::InvalidArguments`] if the
+    /// arguments do not match the specification.
+    pub fn parse(&self, argv: &[String]) -> Result<Matches> {
+        self.parse_impl(&self.name, argv)
+    }
+
+    fn parse_impl(&self, path: &str, argv: &[String]) -> Result<Matches> {
+        let error = |message: String| {
+            Error::InvalidArguments(
+                format!("{path}: {message}\nusage: {}", self.usage(path)).crlf(),
+            )
+        };
+
+        let mut matches = Matches::new(&self.name);
+        let positionals = self.positionals().collect::<Vec<_>>();
+        let mut positional_index = 0;
+        let mut positional_seen = false;
+        let mut options_end = false;
+
+        let mut index = 0;
+        while index < argv.len() {
+            let arg = argv[index].as_str();
+            index += 1;
+
+            if !options_end && arg == "--" {
+                options_end = true;
+            } else if !options_end && (arg == "-h" || arg == "--help") {
+                return Err(Error::Usage(self.help_impl(path)));
+            } else if !options_end && arg.starts_with("--") {
+                let (name, inline) = match arg[2..].split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (&arg[2..], None),
+                };
+                let spec = self
+                    .find_long(name)
+                    .ok_or_else(|| error(format!("unknown option '--{name}'")))?;
+                if spec.arg_type == ArgType::Flag {
+                    if inline.is_some() {
+                        return Err(error(format!("flag '--{name}' does not take a value")));
+                    }
+                    matches.flags.insert(spec.name.clone());
+                } else {
+                    let value = match inline {
+                        Some(value) => value,
+                        None => {
+                            index += 1;
+                            argv.get(index - 1)
+                                .ok_or_else(|| error(format!("missing value for '--{name}'")))?
+                        }
+                    };
+                    matches.insert(spec, value).map_err(error)?;
+                }
+            } else if !options_end && is_short(arg) {
+                let mut chars = arg[1..].chars();
+                while let Some(short) = chars.next() {
+                    let spec = self
+                        .find_short(short)
+                        .ok_or_else(|| error(format!("unknown option '-{short}'")))?;
+                    if spec.arg_type == ArgType::Flag {
+                        matches.flags.insert(spec.name.clone());
+                    } else {
+                        let rest = chars.as_str();
+                        let value = if !rest.is_empty() {
+                            rest
+                        } else {
+                            index += 1;
+                            argv.get(index - 1)
+                                .ok_or_else(|| error(format!("missing value for '-{short}'")))?
+                        };
+                        matches.insert(spec, value).map_err(error)?;
+                        break;
+                    }
+                }
+            } else if let Some(subcommand) = self.find_subcommand(arg).filter(|_| !positional_seen)
+            {
+                let path = format!("{path} {}", subcommand.name);
+                matches.subcommand = Some(Box::new(subcommand.parse_impl(&path, &argv[index..])?));
+                break;
+            } else if positionals.is_empty() && !self.subcommands.is_empty() {
+                return Err(error(format!("unknown command '{arg}'")));
+            } else {
+                let spec = positionals
+                    .get(positional_index)
+                    .ok_or_else(|| error(format!("unexpected argument '{arg}'")))?;
+                matches.insert(spec, arg).map_err(error)?;
+                positional_seen = true;
+                if !spec.multiple {
+                    positional_index += 1;
+                }
+            }
+        }
+
+        if !self.subcommands.is_empty() && positionals.is_empty() && matches.subcommand.is_none() {
+            return Err(error("missing command".to_string()));
+        }
+
+        for spec in self.args.iter() {
+            if matches.values.contains_key(&spec.name) {
+                continue;
+            }
+            if let Some(default) = spec.default.as_ref() {
+                matches
+                    .values
+                    .insert(spec.name.clone(), vec![default.clone()]);
+            } else if spec.required {
+                let name = match spec.arg_type {
+                    ArgType::Positional => format!("argument <{}>", spec.name),
+                    _ => format!("option '--{}'", spec.name),
+                };
+                return Err(error(format!("missing required {name}")));
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Single line usage summary
+    pub fn usage(&self, path: &str) -> String {
+        let mut usage = path.to_string();
+        if self.named().next().is_some() {
+            usage.push_str(" [options]");
+        }
+        if !self.subcommands.is_empty() {
+            usage.push_str(" <command>");
+        }
+        for arg in self.positionals() {
+            usage.push(' ');
+            usage.push_str(&arg.signature());
+        }
+        usage
+    }
+
+    /// Help text listing arguments, options and subcommands
+    pub fn help(&self) -> String {
+        self.help_impl(&self.name)
+    }
+
+    fn help_impl(&self, path: &str) -> String {
+        let help_flag = Arg::flag("help").with_short('h').with_help("display help");
+        let named = self.named().chain([&help_flag]).collect::<Vec<_>>();
+        let positionals = self.positionals().collect::<Vec<_>>();
+
+        let describe = |arg: &Arg| {
+            let mut help = arg.help.clone();
+            if let Some(default) = arg.default.as_ref() {
+                if !help.is_empty() {
+                    help.push(' ');
+                }
+                let _ = write!(help, "(default: {default})");
+            }
+            help
+        };
+
+        let width = named
+            .iter()
+            .chain(positionals.iter())
+            .map(|arg| arg.signature().len())
+            .chain(
+                self.subcommands
+                    .iter()
+                    .map(|subcommand| subcommand.name.len()),
+            )
+            .max()
+            .unwrap_or(0)
+            + 2;
+
+        // writing to a String can not fail
+        let mut text = String::new();
+        if !self.about.is_empty() {
+            let _ = writeln!(text, "{}\n", self.about);
+        }
+        let _ = writeln!(text, "usage: {}", self.usage(path));
+
+        if !positionals.is_empty() {
+            let _ = writeln!(text, "\narguments:");
+            for arg in positionals {
+                let _ = writeln!(text, "  {:width$}{}", arg.signature(), describe(arg));
+            }
+        }
+
+        let _ = writeln!(text, "\noptions:");
+        for arg in named {
+            let _ = writeln!(text, "  {:width$}{}", arg.signature(), describe(arg));
+        }
+
+        if !self.subcommands.is_empty() {
+            let _ = writeln!(text, "\ncommands:");
+            for subcommand in self.subcommands.iter() {
+                let _ = writeln!(text, "  {:width$}{}", subcommand.name, subcommand.about);
+            }
+        }
+
+        text.trim_end().to_string()
+    }
+
+    /// Completion candidates for the `partial` word following the supplied
+    /// (complete) arguments: subcommand names, option names (when `partial`
+    /// starts with `-`) or values of options with a [`ValueKind::Choice`] type.
+    pub fn complete(&self, argv: &[String], partial: &str) -> Vec<String> {
+        let mut command = self;
+        let mut expects_value = None;
+        let mut positionals = 0;
+
+        for arg in argv {
+            if expects_value.take().is_some() {
+                continue;
+            }
+
+            if let Some(name) = arg.strip_prefix("--") {
+                if !name.contains('=') {
+                    expects_value = command
+                        .find_long(name)
+                        .filter(|spec| spec.arg_type == ArgType::Option);
+                }
+            } else if is_short(arg) {
+                let short = arg.chars().last().unwrap_or_default();
+                if arg.len() == 2 {
+                    expects_value = command
+                        .find_short(short)
+                        .filter(|spec| spec.arg_type == ArgType::Option);
+                }
+            } else if positionals == 0 && !command.subcommands.is_empty() {
+                match command.find_subcommand(arg) {
+                    Some(subcommand) => command = subcommand,
+                    None if command.positionals().next().is_some() => positionals += 1,
+                    None => return Vec::new(),
+                }
+            } else {
+                positionals += 1;
+            }
+        }
+
+        let mut candidates = if let Some(spec) = expects_value {
+            match &spec.kind {
+                ValueKind::Choice(choices) => choices.clone(),
+                ValueKind::Bool => vec!["true".to_string(), "false".to_string()],
+                _ => Vec::new(),
+            }
+        } else if partial.starts_with('-') {
+            command
+                .named()
+                .map(|arg| format!("--{}", arg.name))
+                .chain(["--help".to_string()])
+                .collect()
+        } else if positionals == 0 {
+            command
+                .subcommands
+                .iter()
+                .map(|subcommand| subcommand.name.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        candidates.retain(|candidate| candidate.starts_with(partial));
+        candidates.sort();
+        candidates
+    }
+}
+
+fn is_short(arg: &str) -> bool {
+    arg.len() > 1 && arg.starts_with('-') && !arg.starts_with("--") && arg.parse::<f64>().is_err()
+}
+
+/// Arguments matched by [`Command::parse`].
+#[derive(Debug, Clone, Default)]
+pub struct Matches {
+    name: String,
+    flags: HashSet<String>,
+    values: HashMap<String, Vec<String>>,
+    subcommand: Option<Box<Matches>>,
+}
+
+impl Matches {
+    fn new(name: &str) -> Self {
+        Matches {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn insert(&mut self, spec: &Arg, value: &str) -> std::result::Result<(), String> {
+        spec.validate(value)?;
+        let values = self.values.entry(spec.name.clone()).or_default();
+        if !values.is_empty() && !spec.multiple {
+            return Err(match spec.arg_type {
+                ArgType::Positional => format!("unexpected argument '{value}'"),
+                _ => format!("option '--{}' supplied more than once", spec.name),
+            });
+        }
+        values.push(value.to_string());
+        Ok(())
+    }
+
+    /// Name of the matched command
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if the flag is present
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    /// First value of a positional argument or an option
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .get(name)
+            .and_then(|values| values.first())
+            .map(String::as_str)
+    }
+
+    /// All values of a positional argument or an option
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.values
+            .get(name)
+            .map(|values| values.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// First value of a positional argument or an option converted to `T`
+    pub fn get<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.value(name)
+            .map(|value| {
+                value.parse::<T>().map_err(|err| {
+                    Error::InvalidArguments(format!("invalid value '{value}' for {name}: {err}"))
+                })
+            })
+            .transpose()
+    }
+
+    /// First value of a boolean argument (see [`ValueKind::Bool`])
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.value(name).and_then(parse_bool)
+    }
+
+    /// Matches of the subcommand
+    pub fn subcommand(&self) -> Option<&Matches> {
+        self.subcommand.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(cmd: &str) -> Vec<String> {
+        cmd.split_whitespace().map(String::from).collect()
+    }
+
+    fn spec() -> Command {
+        Command::new("peer")
+            .with_about("Manage peers")
+            .with_subcommand(
+                Command::new("add")
+                    .with_about("Add a peer")
+                    .with_arg(
+                        Arg::positional("address")
+                            .with_required(true)
+                            .with_help("peer address"),
+                    )
+                    .with_arg(Arg::positional("tags").with_multiple(true))
+                    .with_arg(
+                        Arg::option("timeout")
+                            .with_short('t')
+                            .with_kind(ValueKind::Unsigned)
+                            .with_default("10"),
+                    )
+                    .with_arg(
+                        Arg::option("network")
+                            .with_kind(ValueKind::choice(&["mainnet", "testnet"])),
+                    )
+                    .with_arg(Arg::flag("verbose").with_short('v')),
+            )
+            .with_subcommand(Command::new("list").with_about("List peers"))
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let spec = spec();
+
+        let matches = spec.parse(&argv("add -vt 5 10.0.0.1 a b --network=testnet"))?;
+        let add = matches.subcommand().unwrap();
+        assert_eq!(add.value("address"), Some("10.0.0.1"));
+        assert_eq!(add.values("tags"), ["a", "b"]);
+        assert_eq!(add.get::<u64>("timeout")?, Some(5));
+        assert_eq!(add.value("network"), Some("testnet"));
+        assert!(add.flag("verbose"));
+
+        let add = spec.parse(&argv("add 10.0.0.1"))?.subcommand.unwrap();
+        assert_eq!(add.get::<u64>("timeout")?, Some(10));
+        assert!(!add.flag("verbose"));
+
+        let errors = [
+            ("add", "missing required argument <address>"),
+            (
+                "add 10.0.0.1 --timeout x",
+                "invalid value 'x' for --timeout",
+            ),
+            ("add 10.0.0.1 --network foo", "one of: mainnet, testnet"),
+            ("add 10.0.0.1 --port 1", "unknown option '--port'"),
+            ("remove", "unknown command 'remove'"),
+            ("", "missing command"),
+        ];
+        for (cmd, expected) in errors {
+            match spec.parse(&argv(cmd)) {
+                Err(Error::InvalidArguments(err)) => {
+                    assert!(err.contains(expected), "{cmd}: {err}")
+                }
+                _ => panic!("{cmd}: expected error"),
+            }
+        }
+
+        match spec.parse(&argv("add --help")) {
+            Err(Error::Usage(help)) => {
+                assert!(help
+                    .starts_with("Add a peer\n\nusage: peer add [options] <address> [<tags>...]"));
+                assert!(help.contains("-t, --timeout <uint>"));
+                assert!(help.contains("(default: 10)"));
+            }
+            _ => panic!("expected usage"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_complete() {
+        let spec = spec();
+        assert_eq!(spec.complete(&[], ""), ["add", "list"]);
+        assert_eq!(spec.complete(&[], "l"), ["list"]);
+        assert_eq!(spec.complete(&argv("add"), "--n"), ["--network"]);
+        assert_eq!(
+            spec.complete(&argv("add --network"), ""),
+            ["mainnet", "testnet"]
+        );
+        assert!(spec.complete(&argv("add 10.0.0.1"), "").is_empty());
+        assert!(spec.complete(&argv("remove"), "").is_empty());
+    }
+
+    #[test]
+    fn test_subcommands_and_positionals() -> Result<()> {
+        let spec = Command::new("wallet")
+            .with_subcommand(Command::new("list"))
+            .with_arg(Arg::positional("name"));
+
+        let matches = spec.parse(&argv("list"))?;
+        assert_eq!(matches.subcommand().map(Matches::name), Some("list"));
+        let matches = spec.parse(&argv("main"))?;
+        assert!(matches.subcommand().is_none());
+        assert_eq!(matches.value("name"), Some("main"));
+        assert!(spec.parse(&argv(""))?.subcommand().is_none());
+        assert!(spec.complete(&argv("main"), "").is_empty());
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 //!
-//! Clear line helper (ANSI escape codes to clear the terminal line)
+//! Clear line and screen helpers (ANSI escape codes to clear the terminal)
 //!
 
 use std::fmt;
@@ -43,3 +43,24 @@ impl AsRef<str> for ClearScreen {
         "\x1B[2J"
     }
 }
+
+/// Clears the screen from the cursor position to the end of the screen
+pub struct ClearBelow;
+
+impl fmt::Display for ClearBelow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\x1B[J")
+    }
+}
+
+impl AsRef<[u8]> for ClearBelow {
+    fn as_ref(&self) -> &'static [u8] {
+        "\x1B[J".as_bytes()
+    }
+}
+
+impl AsRef<str> for ClearBelow {
+    fn as_ref(&self) -> &'static str {
+        "\x1B[J"
+    }
+}
@@ -2,15 +2,18 @@
 //! Cli trait for implementing a user-side command-line processor.
 //!
 
+use crate::args::{Arg, Command, Matches, ValueKind};
 use crate::error::Error;
 use crate::parse;
+use crate::pipeline::{Filter, Pipeline};
 pub use crate::result::Result;
+use crate::table::Table;
 use crate::terminal::Terminal;
 use async_trait::async_trait;
 use downcast::{downcast_sync, AnySync};
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex, MutexGuard},
+    sync::{Arc, Mutex, MutexGuard, Weak},
 };
 pub use workflow_terminal_macros::{declare_handler, register_handlers, Handler};
 
@@ -26,6 +29,11 @@ pub trait Cli: Sync + Send {
         cmd: String,
     ) -> Result<Option<Vec<String>>>;
     fn prompt(&self) -> Option<String>;
+    /// Returns true if the command must not be recorded in
+    /// the command history (e.g. contains a password or a key).
+    fn is_secret(&self, _cmd: &str) -> bool {
+        false
+    }
 }
 
 pub trait Context: Sync + Send + AnySync {
@@ -54,31 +62,279 @@ pub trait Handler: Sync + Send + AnySync {
     fn dyn_help(&self, _ctx: &Arc<dyn Context>) -> String {
         "".to_owned()
     }
+    /// Optional declarative argument specification. When supplied,
+    /// [`HandlerCli`] validates arguments before invoking [`Handler::handle_matches`]
+    /// with the typed arguments (see [`handle_args`]), displays the generated
+    /// help on `-h`/`--help` and completes subcommands and option names.
+    fn args(&self, _ctx: &Arc<dyn Context>) -> Option<Command> {
+        None
+    }
     async fn complete(&self, _ctx: &Arc<dyn Context>, _cmd: &str) -> Result<Option<Vec<String>>> {
         Ok(None)
     }
+    /// Returns true if the invocation must not be recorded in the
+    /// command history (see [`HandlerCli::is_secret`]).
+    fn secret(&self, _ctx: &Arc<dyn Context>, _argv: &[String]) -> bool {
+        false
+    }
     async fn start(self: Arc<Self>, _ctx: &Arc<dyn Context>) -> Result<()> {
         Ok(())
     }
     async fn stop(self: Arc<Self>, _ctx: &Arc<dyn Context>) -> Result<()> {
         Ok(())
     }
+    /// Handle the command. Handlers supplying an argument specification
+    /// ([`Handler::args`]) and implementing [`Handler::handle_matches`]
+    /// can delegate to [`handle_args`].
     async fn handle(
         self: Arc<Self>,
         ctx: &Arc<dyn Context>,
         argv: Vec<String>,
         cmd: &str,
     ) -> Result<()>;
+    /// Handle the command using the arguments parsed according to the
+    /// argument specification ([`Handler::args`]). Invoked by [`HandlerCli`]
+    /// instead of [`Handler::handle`] (the default implementation) for
+    /// handlers supplying the argument specification.
+    async fn handle_matches(
+        self: Arc<Self>,
+        ctx: &Arc<dyn Context>,
+        _matches: Matches,
+        argv: Vec<String>,
+        cmd: &str,
+    ) -> Result<()> {
+        self.handle(ctx, argv, cmd).await
+    }
 }
 
 downcast_sync!(dyn Handler);
 
 pub fn get_handler_help(handler: Arc<dyn Handler>, ctx: &Arc<dyn Context>) -> String {
     let s = handler.help(ctx);
-    if s.is_empty() {
-        handler.dyn_help(ctx)
-    } else {
+    if !s.is_empty() {
         s.to_string()
+    } else {
+        let s = handler.dyn_help(ctx);
+        match handler.args(ctx) {
+            Some(spec) if s.is_empty() => spec.about().to_string(),
+            _ => s,
+        }
+    }
+}
+
+/// Parse the command arguments according to the handler argument
+/// specification (see [`Handler::args`]) and invoke [`Handler::handle_matches`],
+/// displaying the usage if requested (`-h`/`--help`). Invokes [`Handler::handle`]
+/// if the handler does not supply the argument specification.
+pub async fn handle_args(
+    handler: Arc<dyn Handler>,
+    ctx: &Arc<dyn Context>,
+    argv: Vec<String>,
+    cmd: &str,
+) -> Result<()> {
+    let Some(spec) = handler.args(ctx) else {
+        return handler.handle(ctx, argv, cmd).await;
+    };
+
+    match spec.parse(&argv) {
+        Ok(matches) => handler.handle_matches(ctx, matches, argv, cmd).await,
+        Err(Error::Usage(help)) => {
+            let term = ctx.term();
+            help.lines().for_each(|line| term.writeln(line));
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Get the help text generated from the handler argument
+/// specification (see [`Handler::args`]).
+pub fn get_handler_usage(handler: Arc<dyn Handler>, ctx: &Arc<dyn Context>) -> Option<String> {
+    handler.args(ctx).map(|spec| spec.help())
+}
+
+/// `help` command listing the registered commands (`help`) or displaying
+/// the full usage of a command (`help <command>`), generated from its
+/// argument specification (see [`Handler::args`]).
+///
+/// ```ignore
+/// cli.handlers.register(&cli, HelpHandler::new(&cli.handlers));
+/// ```
+pub struct HelpHandler {
+    inner: Weak<Mutex<Inner>>,
+}
+
+impl HelpHandler {
+    pub fn new(handlers: &HandlerCli) -> Self {
+        HelpHandler {
+            inner: Arc::downgrade(&handlers.inner),
+        }
+    }
+
+    fn handlers(&self) -> Vec<(String, Arc<dyn Handler>)> {
+        let mut handlers = self
+            .inner
+            .upgrade()
+            .map(|inner| {
+                inner
+                    .lock()
+                    .unwrap()
+                    .handlers
+                    .iter()
+                    .map(|(verb, handler)| (verb.clone(), handler.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        handlers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        handlers
+    }
+}
+
+#[async_trait]
+impl Handler for HelpHandler {
+    fn verb(&self, _ctx: &Arc<dyn Context>) -> Option<&'static str> {
+        Some("help")
+    }
+    fn args(&self, _ctx: &Arc<dyn Context>) -> Option<Command> {
+        Some(
+            Command::new("help")
+                .with_about("Display the list of commands or the usage of a command")
+                .with_arg(
+                    Arg::positional("command")
+                        .with_required(false)
+                        .with_help("command to display the usage of"),
+                ),
+        )
+    }
+    async fn complete(&self, _ctx: &Arc<dyn Context>, cmd: &str) -> Result<Option<Vec<String>>> {
+        let argv = parse(cmd);
+        let partial = match argv.as_slice() {
+            [_] if cmd.ends_with(char::is_whitespace) => "",
+            [_, partial] if !cmd.ends_with(char::is_whitespace) => partial.as_str(),
+            _ => return Ok(None),
+        };
+        let verbs = self
+            .handlers()
+            .into_iter()
+            .map(|(verb, _)| verb)
+            .filter(|verb| verb.starts_with(partial))
+            .collect::<Vec<_>>();
+        Ok((!verbs.is_empty()).then_some(verbs))
+    }
+    async fn handle(
+        self: Arc<Self>,
+        ctx: &Arc<dyn Context>,
+        argv: Vec<String>,
+        cmd: &str,
+    ) -> Result<()> {
+        handle_args(self, ctx, argv, cmd).await
+    }
+    async fn handle_matches(
+        self: Arc<Self>,
+        ctx: &Arc<dyn Context>,
+        matches: Matches,
+        _argv: Vec<String>,
+        _cmd: &str,
+    ) -> Result<()> {
+        let term = ctx.term();
+        let handlers = self.handlers();
+        match matches.value("command") {
+            Some(verb) => {
+                let verb = verb.to_lowercase();
+                let (_, handler) = handlers
+                    .into_iter()
+                    .find(|(name, _)| *name == verb)
+                    .ok_or(Error::CommandNotFound(verb))?;
+                let help = get_handler_usage(handler.clone(), ctx)
+                    .unwrap_or_else(|| get_handler_help(handler, ctx));
+                help.lines().for_each(|line| term.writeln(line));
+            }
+            None => {
+                let mut table = Table::new().with_columns(&["Command", "Description"]);
+                for (verb, handler) in handlers {
+                    table.push_row([verb, get_handler_help(handler, ctx)]);
+                }
+                term.table(&table);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `history` command displaying (`history`) or
+/// clearing (`history clear`) the command history.
+pub struct HistoryHandler;
+
+#[async_trait]
+impl Handler for HistoryHandler {
+    fn verb(&self, _ctx: &Arc<dyn Context>) -> Option<&'static str> {
+        Some("history")
+    }
+    fn args(&self, _ctx: &Arc<dyn Context>) -> Option<Command> {
+        Some(
+            Command::new("history")
+                .with_about("Display or clear the command history")
+                .with_arg(
+                    Arg::positional("action")
+                        .with_kind(ValueKind::choice(&["clear"]))
+                        .with_required(false)
+                        .with_help("`clear` clears the command history"),
+                ),
+        )
+    }
+    async fn handle(
+        self: Arc<Self>,
+        ctx: &Arc<dyn Context>,
+        argv: Vec<String>,
+        cmd: &str,
+    ) -> Result<()> {
+        handle_args(self, ctx, argv, cmd).await
+    }
+    async fn handle_matches(
+        self: Arc<Self>,
+        ctx: &Arc<dyn Context>,
+        matches: Matches,
+        _argv: Vec<String>,
+        _cmd: &str,
+    ) -> Result<()> {
+        let term = ctx.term();
+        if matches.value("action") == Some("clear") {
+            term.clear_history().await?;
+        } else {
+            let history = term.history();
+            let width = history.len().to_string().len();
+            for (index, entry) in history.iter().enumerate() {
+                term.writeln(format!("{:>width$}  {entry}", index + 1));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `bindings` command listing the key bindings in effect.
+pub struct BindingsHandler;
+
+#[async_trait]
+impl Handler for BindingsHandler {
+    fn verb(&self, _ctx: &Arc<dyn Context>) -> Option<&'static str> {
+        Some("bindings")
+    }
+    fn help(&self, _ctx: &Arc<dyn Context>) -> &'static str {
+        "List key bindings"
+    }
+    async fn handle(
+        self: Arc<Self>,
+        ctx: &Arc<dyn Context>,
+        _argv: Vec<String>,
+        _cmd: &str,
+    ) -> Result<()> {
+        let term = ctx.term();
+        let mut table = Table::new().with_columns(&["Key", "Binding"]);
+        for (key, binding) in term.bindings() {
+            table.push_row([key.to_string(), binding.to_string()]);
+        }
+        term.table(&table);
+        Ok(())
     }
 }
 
@@ -87,6 +343,9 @@ struct Inner {
     handlers: HashMap<String, Arc<dyn Handler>>,
 }
 
+/// Command-line processor dispatching commands to registered [`Handler`]s.
+/// The [`HelpHandler`], [`HistoryHandler`] and [`BindingsHandler`] commands
+/// provided by this crate can be registered using [`HandlerCli::register`].
 #[derive(Default)]
 pub struct HandlerCli {
     inner: Arc<Mutex<Inner>>,
@@ -176,27 +435,86 @@ impl HandlerCli {
         Ok(())
     }
 
+    /// Execute a command or a pipeline of commands (see [`pipeline`](crate::pipeline)).
+    /// The command is executed as a pipeline only if it starts with the
+    /// verb of a registered handler.
     pub async fn execute<T>(&self, ctx: &Arc<T>, cmd: &str) -> Result<()>
     where
         T: Context + Sized,
     {
         let ctx: Arc<dyn Context> = ctx.clone();
 
+        let verb = cmd
+            .trim_start()
+            .split(|c: char| c.is_whitespace() || matches!(c, '|' | '>'))
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if self.get(verb.as_str()).is_none() {
+            return self.execute_command(&ctx, cmd).await;
+        }
+
+        let pipeline = Pipeline::parse(cmd)?;
+        if pipeline.is_simple() {
+            return self.execute_command(&ctx, cmd).await;
+        }
+
+        let term = ctx.term();
+        let mut lines = Vec::new();
+        for (index, stage) in pipeline.stages.iter().enumerate() {
+            // registered handlers take precedence over built-in filters
+            let argv = parse(stage);
+            let filter = (index > 0 && self.get(argv[0].to_lowercase().as_str()).is_none())
+                .then(|| Filter::try_from_argv(&argv))
+                .flatten();
+            match filter {
+                Some(filter) => lines = filter?.apply(lines),
+                None => {
+                    let (result, output) =
+                        term.capture_output(self.execute_command(&ctx, stage)).await;
+                    result?;
+                    lines = output;
+                }
+            }
+        }
+
+        match pipeline.redirect {
+            Some(redirect) => redirect.write(&lines).await?,
+            None => lines.iter().for_each(|line| term.writeln(line)),
+        }
+        Ok(())
+    }
+
+    async fn execute_command(&self, ctx: &Arc<dyn Context>, cmd: &str) -> Result<()> {
         let argv = parse(cmd);
         let action = argv[0].to_lowercase();
 
         let handler = self.get(action.as_str());
         if let Some(handler) = handler {
-            handler
-                .clone()
-                .handle(&ctx, argv[1..].to_vec(), cmd)
-                .await?;
-            Ok(())
+            handle_args(handler, ctx, argv[1..].to_vec(), cmd).await
         } else {
             Err(Error::CommandNotFound(action))
         }
     }
 
+    /// Returns true if the command must not be recorded in the command
+    /// history, as flagged by the [`Handler::secret`] of its handler.
+    /// Can be used to implement [`Cli::is_secret`].
+    pub fn is_secret<T>(&self, ctx: &Arc<T>, cmd: &str) -> bool
+    where
+        T: Context + Sized,
+    {
+        let ctx: Arc<dyn Context> = ctx.clone();
+        let argv = parse(cmd);
+        self.get(argv[0].to_lowercase().as_str())
+            .map(|handler| handler.secret(&ctx, &argv[1..]))
+            .unwrap_or(false)
+    }
+
+    /// Get completion candidates for the last (partial) word of the
+    /// supplied command line. Completes command names, then delegates
+    /// to [`Handler::complete`] and, if the handler does not provide
+    /// completions, to its argument specification ([`Handler::args`]).
     pub async fn complete<T>(&self, ctx: &Arc<T>, cmd: &str) -> Result<Option<Vec<String>>>
     where
         T: Context + Sized,
@@ -205,12 +523,116 @@ impl HandlerCli {
 
         let argv = parse(cmd);
         let action = argv[0].to_lowercase();
+        let trailing = cmd.is_empty() || cmd.ends_with(char::is_whitespace);
+
+        if argv.len() == 1 && (!trailing || action.is_empty()) {
+            let mut verbs = self
+                .inner()
+                .handlers
+                .keys()
+                .filter(|verb| verb.starts_with(action.as_str()))
+                .cloned()
+                .collect::<Vec<_>>();
+            verbs.sort();
+            return Ok((!verbs.is_empty()).then_some(verbs));
+        }
 
         let handler = self.get(action.as_str());
         if let Some(handler) = handler {
-            Ok(handler.clone().complete(&ctx, cmd).await?)
+            if let Some(list) = handler.clone().complete(&ctx, cmd).await? {
+                return Ok(Some(list));
+            }
+
+            if let Some(spec) = handler.args(&ctx) {
+                let (args, partial) = if trailing {
+                    (&argv[1..], "")
+                } else {
+                    (&argv[1..argv.len() - 1], argv[argv.len() - 1].as_str())
+                };
+                let list = spec.complete(args, partial);
+                return Ok((!list.is_empty()).then_some(list));
+            }
+
+            Ok(None)
         } else {
             Err(Error::CommandNotFound(action))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Options;
+
+    struct HelpCli {
+        term: Mutex<Option<Arc<Terminal>>>,
+        handlers: HandlerCli,
+    }
+
+    impl Context for HelpCli {
+        fn term(&self) -> Arc<Terminal> {
+            self.term.lock().unwrap().clone().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Cli for HelpCli {
+        fn init(self: Arc<Self>, term: &Arc<Terminal>) -> Result<()> {
+            *self.term.lock().unwrap() = Some(term.clone());
+            self.handlers
+                .register(&self, HelpHandler::new(&self.handlers));
+            self.handlers.register(&self, HistoryHandler);
+            Ok(())
+        }
+        async fn digest(self: Arc<Self>, _term: Arc<Terminal>, cmd: String) -> Result<()> {
+            self.handlers.execute(&self, &cmd).await
+        }
+        async fn complete(
+            self: Arc<Self>,
+            _term: Arc<Terminal>,
+            cmd: String,
+        ) -> Result<Option<Vec<String>>> {
+            self.handlers.complete(&self, &cmd).await
+        }
+        fn prompt(&self) -> Option<String> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_help() -> Result<()> {
+        let cli = Arc::new(HelpCli {
+            term: Mutex::new(None),
+            handlers: HandlerCli::new(),
+        });
+        let term = Arc::new(Terminal::try_new_headless(cli, Options::new())?);
+        term.init().await?;
+        let headless = term.headless().unwrap();
+
+        let script = crate::Script::parse("help history\nhistory clear\nhelp")?;
+        assert_eq!(
+            term.exec_script(&script).await?,
+            crate::script::EXIT_SUCCESS
+        );
+        let lines = headless.lines();
+        assert_eq!(
+            lines[..3],
+            [
+                "Display or clear the command history",
+                "",
+                "usage: history [<action>]"
+            ]
+        );
+        assert!(lines.contains(&"history  Display or clear the command history".to_string()));
+
+        assert_eq!(
+            term.handler
+                .clone()
+                .complete(term.clone(), "help hi".to_string())
+                .await?,
+            Some(vec!["history".to_string()])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+//!
+//! Tab completion helpers used by the [`Terminal`](crate::Terminal) line editor.
+//!
+//! Completion candidates are obtained from [`Cli::complete`](crate::Cli::complete)
+//! for the word under the cursor. A single match replaces the word, multiple
+//! matches are completed up to their common prefix and listed on a second
+//! consecutive Tab.
+//!
+
+/// Outcome of completing a word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Completion {
+    /// No candidates
+    None,
+    /// Replace the word with the supplied text. `complete` is
+    /// true if the text is a unique match (a separator
+    /// should be appended).
+    Replace { text: String, complete: bool },
+    /// Multiple candidates without a longer common prefix
+    Ambiguous(Vec<String>),
+}
+
+/// Returns the index of the first character of the word ending at the end of `line`.
+pub fn word_start(line: &[char]) -> usize {
+    line.iter()
+        .rposition(|c| c.is_whitespace())
+        .map(|index| index + 1)
+        .unwrap_or(0)
+}
+
+/// Complete `word` using the supplied candidates. Candidates not starting
+/// with the word are ignored.
+pub fn complete(word: &str, candidates: &[String]) -> Completion {
+    let mut matches = candidates
+        .iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .cloned()
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.dedup();
+
+    match matches.as_slice() {
+        [] => Completion::None,
+        [text] => Completion::Replace {
+            text: text.clone(),
+            complete: true,
+        },
+        [first, rest @ ..] => {
+            let prefix = rest.iter().fold(first.as_str(), |prefix, candidate| {
+                let len = prefix
+                    .char_indices()
+                    .zip(candidate.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map(|((index, _), _)| index)
+                    .unwrap_or(prefix.len().min(candidate.len()));
+                &prefix[..len]
+            });
+
+            if prefix.len() > word.len() {
+                Completion::Replace {
+                    text: prefix.to_string(),
+                    complete: false,
+                }
+            } else {
+                Completion::Ambiguous(matches)
+            }
+        }
+    }
+}
+
+/// Arrange candidates in columns fitting the supplied terminal width.
+pub fn columns(candidates: &[String], width: usize) -> Vec<String> {
+    let column_width = candidates
+        .iter()
+        .map(|candidate| candidate.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let per_row = (width / column_width).max(1);
+
+    candidates
+        .chunks(per_row)
+        .map(|row| {
+            row.iter()
+                .map(|candidate| format!("{candidate:column_width$}"))
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(word_start(&"peer ad".chars().collect::<Vec<_>>()), 5);
+        assert_eq!(word_start(&"peer ".chars().collect::<Vec<_>>()), 5);
+        assert_eq!(word_start(&"pe".chars().collect::<Vec<_>>()), 0);
+
+        assert_eq!(complete("x", &[]), Completion::None);
+        assert_eq!(complete("x", &list(&["add", "list"])), Completion::None);
+        assert_eq!(
+            complete("ad", &list(&["add", "list"])),
+            Completion::Replace {
+                text: "add".to_string(),
+                complete: true
+            }
+        );
+        assert_eq!(
+            complete("a", &list(&["alpha", "aloha", "albatross"])),
+            Completion::Replace {
+                text: "al".to_string(),
+                complete: false
+            }
+        );
+        assert_eq!(
+            complete("al", &list(&["alpha", "aloha", "albatross"])),
+            Completion::Ambiguous(list(&["albatross", "aloha", "alpha"]))
+        );
+
+        assert_eq!(
+            columns(&list(&["alpha", "aloha", "albatross"]), 24),
+            ["alpha      aloha", "albatross"]
+        );
+    }
+}
@@ -0,0 +1,606 @@
+//!
+//! Line editor core used by the [`Terminal`](crate::Terminal).
+//!
+//! [`LineEditor`] holds the line buffer, the cursor position and the
+//! command history and applies emacs-style editing [`Action`]s to them,
+//! independently of the output backend. Each action produces an [`Effect`]
+//! describing how the terminal should react (redraw the line, submit the
+//! command etc.), while [`LineEditor::render`] produces the text of the
+//! current line. This allows the editing behavior to be tested without
+//! a terminal. Submitted lines are not recorded in the history by the
+//! editor; the terminal records them via [`LineEditor::push_history`]
+//! unless the command is flagged as secret.
+//!
+//! Supported editing (default key bindings, see [`default_action`]):
+//! - `Home`/`Ctrl-A`, `End`/`Ctrl-E`, `Left`/`Ctrl-B`, `Right`/`Ctrl-F` - cursor movement
+//! - `Alt-B`, `Alt-F` - word movement
+//! - `Backspace`, `Delete`/`Ctrl-D` - delete a character
+//! - `Ctrl-K`, `Ctrl-U`, `Ctrl-W`, `Alt-D` - kill to the end/start of the line, previous/next word
+//! - `Ctrl-Y` - yank (paste) the last killed text
+//! - `Up`/`Ctrl-P`, `Down`/`Ctrl-N` - history navigation
+//! - `Ctrl-R` - incremental reverse history search
+//! - `Ctrl-L` - clear screen
+//! - `Enter` - accept the line; a line ending with `\` continues on the next line
+//!
+//! The terminal dispatches keys through a [`Keymap`](crate::keymap::Keymap)
+//! initialized with these bindings.
+//!
+
+use crate::history::History;
+use crate::keys::Key;
+use crate::UnicodeString;
+
+/// Default prompt displayed for continuation lines of a multi-line input.
+pub const DEFAULT_CONTINUATION_PROMPT: &str = "> ";
+
+/// Line editing action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Insert a character at the cursor position
+    Insert(char),
+    MoveLeft,
+    MoveRight,
+    MoveHome,
+    MoveEnd,
+    MoveWordLeft,
+    MoveWordRight,
+    DeleteBackward,
+    DeleteForward,
+    /// Kill (cut) text from the cursor to the end of the line
+    KillToEnd,
+    /// Kill (cut) text from the start of the line to the cursor
+    KillToStart,
+    /// Kill (cut) the whitespace-delimited word before the cursor
+    KillWordBackward,
+    /// Kill (cut) the word after the cursor
+    KillWordForward,
+    /// Insert the last killed text
+    Yank,
+    HistoryPrevious,
+    HistoryNext,
+    /// Start (or continue) incremental reverse history search
+    ReverseSearch,
+    ClearScreen,
+    /// Accept the line (submit the command)
+    Accept,
+    /// Complete the word under the cursor
+    Complete,
+    /// Cancel history search
+    Cancel,
+    /// Interrupt: discard the line (`Ctrl-C`)
+    Interrupt,
+}
+
+/// Result of applying an [`Action`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    /// Nothing has changed
+    None,
+    /// The line has changed and should be redrawn
+    Redraw,
+    /// The screen should be cleared and the line redrawn
+    ClearScreen,
+    /// The line has been continued; a new line should
+    /// be started and the (continuation) line rendered
+    Continue,
+    /// The command has been accepted
+    Submit(String),
+    /// The word under the cursor should be completed; `list`
+    /// is true if completion was requested twice in a row
+    Complete { list: bool },
+    /// The input has been interrupted
+    Interrupt,
+}
+
+/// Default key bindings.
+pub fn default_action(key: Key) -> Option<Action> {
+    let action = match key {
+        Key::Char(ch) => Action::Insert(ch),
+        Key::Enter => Action::Accept,
+        Key::Tab => Action::Complete,
+        Key::Backspace | Key::Ctrl('h') => Action::DeleteBackward,
+        Key::Delete | Key::Ctrl('d') => Action::DeleteForward,
+        Key::ArrowLeft | Key::Ctrl('b') => Action::MoveLeft,
+        Key::ArrowRight | Key::Ctrl('f') => Action::MoveRight,
+        Key::Home | Key::Ctrl('a') => Action::MoveHome,
+        Key::End | Key::Ctrl('e') => Action::MoveEnd,
+        Key::Alt('b') => Action::MoveWordLeft,
+        Key::Alt('f') => Action::MoveWordRight,
+        Key::Ctrl('k') => Action::KillToEnd,
+        Key::Ctrl('u') => Action::KillToStart,
+        Key::Ctrl('w') => Action::KillWordBackward,
+        Key::Alt('d') => Action::KillWordForward,
+        Key::Ctrl('y') => Action::Yank,
+        Key::ArrowUp | Key::Ctrl('p') => Action::HistoryPrevious,
+        Key::ArrowDown | Key::Ctrl('n') => Action::HistoryNext,
+        Key::Ctrl('r') => Action::ReverseSearch,
+        Key::Ctrl('l') => Action::ClearScreen,
+        Key::Esc | Key::Ctrl('g') => Action::Cancel,
+        Key::Ctrl('c') => Action::Interrupt,
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[derive(Debug, Clone)]
+struct Search {
+    query: UnicodeString,
+    /// history index of the current match
+    index: Option<usize>,
+    /// line buffer and cursor before the search has started
+    saved: (UnicodeString, usize),
+}
+
+fn is_word_char(c: &char) -> bool {
+    c.is_alphanumeric() || *c == '_'
+}
+
+/// Line editor state: line buffer, cursor position and command history.
+#[derive(Debug, Clone)]
+pub struct LineEditor {
+    pub buffer: UnicodeString,
+    pub cursor: usize,
+    history: History,
+    history_index: usize,
+    /// line being edited before navigating the history
+    draft: UnicodeString,
+    /// preceding lines of a multi-line input
+    lines: Vec<UnicodeString>,
+    kill_buffer: UnicodeString,
+    search: Option<Search>,
+    last_action: Option<Action>,
+    continuation_prompt: String,
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        LineEditor {
+            buffer: UnicodeString::default(),
+            cursor: 0,
+            history: History::default(),
+            history_index: 0,
+            draft: UnicodeString::default(),
+            lines: Vec::new(),
+            kill_buffer: UnicodeString::default(),
+            search: None,
+            last_action: None,
+            continuation_prompt: DEFAULT_CONTINUATION_PROMPT.to_string(),
+        }
+    }
+
+    pub fn reset_line_buffer(&mut self) {
+        self.buffer.clear();
+        self.cursor = 0;
+        self.lines.clear();
+        self.search = None;
+    }
+
+    /// Command history
+    pub fn history(&self) -> &[UnicodeString] {
+        self.history.entries()
+    }
+
+    pub fn history_list(&self) -> &History {
+        &self.history
+    }
+
+    /// Replace the command history (e.g. with a history loaded from storage)
+    pub fn set_history_list(&mut self, history: History) {
+        self.history = history;
+        self.history_index = self.history.len();
+    }
+
+    /// Append a command to the history. Returns `false` if the command
+    /// has not been recorded (see [`History::push`]).
+    pub fn push_history(&mut self, cmd: UnicodeString) -> bool {
+        let recorded = self.history.push(cmd);
+        self.history_index = self.history.len();
+        recorded
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_index = 0;
+        self.search = None;
+    }
+
+    pub fn set_history_size(&mut self, max_size: usize) {
+        self.history.set_max_size(max_size);
+        self.history_index = self.history.len();
+    }
+
+    pub fn set_continuation_prompt(&mut self, prompt: &str) {
+        self.continuation_prompt = prompt.to_string();
+    }
+
+    /// Returns true if a multi-line input is in progress
+    pub fn is_continuation(&self) -> bool {
+        !self.lines.is_empty()
+    }
+
+    /// Returns true if reverse history search is in progress
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Render the current line (including the prompt). Returns the
+    /// text and the number of characters the cursor should be moved
+    /// back from the end of the text.
+    pub fn render(&self, prompt: &str) -> (String, usize) {
+        let tail = self.buffer.len() - self.cursor;
+        let text = if let Some(search) = self.search.as_ref() {
+            format!("(reverse-i-search)`{}': {}", search.query, self.buffer)
+        } else if self.is_continuation() {
+            format!("{}{}", self.continuation_prompt, self.buffer)
+        } else {
+            format!("{}{}", prompt, self.buffer)
+        };
+        (text, tail)
+    }
+
+    /// Apply an editing action.
+    pub fn apply(&mut self, action: Action) -> Effect {
+        let last_action = self.last_action.replace(action);
+
+        if self.search.is_some() {
+            if let Some(effect) = self.apply_search(action) {
+                return effect;
+            }
+        }
+
+        match action {
+            Action::Insert(ch) => {
+                self.buffer.insert_char(self.cursor, ch);
+                self.cursor += 1;
+                Effect::Redraw
+            }
+            Action::MoveLeft => self.move_to(self.cursor.saturating_sub(1)),
+            Action::MoveRight => self.move_to((self.cursor + 1).min(self.buffer.len())),
+            Action::MoveHome => self.move_to(0),
+            Action::MoveEnd => self.move_to(self.buffer.len()),
+            Action::MoveWordLeft => self.move_to(self.word_left()),
+            Action::MoveWordRight => self.move_to(self.word_right()),
+            Action::DeleteBackward => {
+                if self.cursor == 0 {
+                    return Effect::None;
+                }
+                self.cursor -= 1;
+                self.buffer.remove(self.cursor);
+                Effect::Redraw
+            }
+            Action::DeleteForward => {
+                if self.cursor >= self.buffer.len() {
+                    return Effect::None;
+                }
+                self.buffer.remove(self.cursor);
+                Effect::Redraw
+            }
+            Action::KillToEnd => self.kill(self.cursor, self.buffer.len()),
+            Action::KillToStart => self.kill(0, self.cursor),
+            Action::KillWordBackward => {
+                let chars = &self.buffer.0[..self.cursor];
+                let end = chars
+                    .iter()
+                    .rposition(|c| !c.is_whitespace())
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+                let start = chars[..end]
+                    .iter()
+                    .rposition(|c| c.is_whitespace())
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+                self.kill(start, self.cursor)
+            }
+            Action::KillWordForward => self.kill(self.cursor, self.word_right()),
+            Action::Yank => {
+                if self.kill_buffer.is_empty() {
+                    return Effect::None;
+                }
+                let text = self.kill_buffer.clone();
+                let len = text.len();
+                self.buffer.insert(self.cursor, text);
+                self.cursor += len;
+                Effect::Redraw
+            }
+            Action::HistoryPrevious => {
+                if self.history_index == 0 {
+                    return Effect::None;
+                }
+                if self.history_index >= self.history.len() {
+                    self.draft = self.buffer.clone();
+                }
+                self.history_index -= 1;
+                self.set_buffer(self.history.entries()[self.history_index].clone());
+                Effect::Redraw
+            }
+            Action::HistoryNext => {
+                if self.history_index >= self.history.len() {
+                    return Effect::None;
+                }
+                self.history_index += 1;
+                let buffer = if self.history_index == self.history.len() {
+                    std::mem::take(&mut self.draft)
+                } else {
+                    self.history.entries()[self.history_index].clone()
+                };
+                self.set_buffer(buffer);
+                Effect::Redraw
+            }
+            Action::ReverseSearch => {
+                self.search = Some(Search {
+                    query: UnicodeString::default(),
+                    index: None,
+                    saved: (self.buffer.clone(), self.cursor),
+                });
+                Effect::Redraw
+            }
+            Action::ClearScreen => Effect::ClearScreen,
+            Action::Accept => self.accept(),
+            Action::Complete => Effect::Complete {
+                list: last_action == Some(Action::Complete),
+            },
+            Action::Cancel => Effect::None,
+            Action::Interrupt => {
+                self.reset_line_buffer();
+                self.history_index = self.history.len();
+                Effect::Interrupt
+            }
+        }
+    }
+
+    /// Handle an action during reverse history search. Returns `None`
+    /// if the search has ended and the action should be applied normally.
+    fn apply_search(&mut self, action: Action) -> Option<Effect> {
+        let search = self.search.as_mut()?;
+        match action {
+            Action::Insert(ch) => {
+                search.query.push(ch);
+                let from = search
+                    .index
+                    .map(|index| index + 1)
+                    .unwrap_or(self.history.len());
+                self.search_from(from);
+            }
+            Action::DeleteBackward => {
+                search.query.pop();
+                let len = self.history.len();
+                self.search_from(len);
+            }
+            Action::ReverseSearch => {
+                let from = search.index.unwrap_or(self.history.len());
+                self.search_from(from);
+            }
+            Action::Cancel | Action::Interrupt => {
+                let (buffer, cursor) = search.saved.clone();
+                self.search = None;
+                self.buffer = buffer;
+                self.cursor = cursor;
+            }
+            _ => {
+                if let Some(index) = search.index {
+                    self.history_index = index;
+                }
+                self.search = None;
+                return None;
+            }
+        }
+        Some(Effect::Redraw)
+    }
+
+    /// Search history entries preceding `from` for the current query.
+    fn search_from(&mut self, from: usize) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let query = search.query.to_string();
+        let found = self.history.entries()[..from.min(self.history.len())]
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, entry)| {
+                let entry_str = entry.to_string();
+                entry_str.find(&query).map(|position| {
+                    let cursor = entry_str[..position].chars().count();
+                    (index, entry.clone(), cursor)
+                })
+            });
+
+        if let Some((index, entry, cursor)) = found {
+            search.index = Some(index);
+            self.buffer = entry;
+            self.cursor = cursor;
+        }
+    }
+
+    fn accept(&mut self) -> Effect {
+        if self.buffer.0.last() == Some(&'\\') {
+            self.buffer.pop();
+            self.lines.push(std::mem::take(&mut self.buffer));
+            self.cursor = 0;
+            return Effect::Continue;
+        }
+
+        let mut line = std::mem::take(&mut self.lines).into_iter().fold(
+            UnicodeString::default(),
+            |mut line, part| {
+                line.extend(part);
+                line
+            },
+        );
+        line.extend(std::mem::take(&mut self.buffer));
+        self.cursor = 0;
+        self.draft.clear();
+
+        self.history_index = self.history.len();
+
+        Effect::Submit(line.to_string())
+    }
+
+    fn move_to(&mut self, cursor: usize) -> Effect {
+        if cursor == self.cursor {
+            Effect::None
+        } else {
+            self.cursor = cursor;
+            Effect::Redraw
+        }
+    }
+
+    fn set_buffer(&mut self, buffer: UnicodeString) {
+        self.cursor = buffer.len();
+        self.buffer = buffer;
+    }
+
+    fn kill(&mut self, start: usize, end: usize) -> Effect {
+        if start >= end {
+            return Effect::None;
+        }
+        self.kill_buffer = self.buffer.0.drain(start..end).collect::<Vec<_>>().into();
+        self.cursor = start;
+        Effect::Redraw
+    }
+
+    fn word_left(&self) -> usize {
+        let chars = &self.buffer.0[..self.cursor];
+        let end = chars
+            .iter()
+            .rposition(is_word_char)
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        chars[..end]
+            .iter()
+            .rposition(|c| !is_word_char(c))
+            .map(|index| index + 1)
+            .unwrap_or(0)
+    }
+
+    fn word_right(&self) -> usize {
+        let chars = &self.buffer.0;
+        let len = chars.len();
+        let start = chars[self.cursor..]
+            .iter()
+            .position(is_word_char)
+            .map(|index| self.cursor + index)
+            .unwrap_or(len);
+        chars[start..]
+            .iter()
+            .position(|c| !is_word_char(c))
+            .map(|index| start + index)
+            .unwrap_or(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(editor: &mut LineEditor, action: Action) -> Effect {
+        let effect = editor.apply(action);
+        if let Effect::Submit(line) = &effect {
+            editor.push_history(line.as_str().into());
+        }
+        effect
+    }
+
+    fn keys(editor: &mut LineEditor, keys: &[Key]) -> Vec<Effect> {
+        keys.iter()
+            .filter_map(|key| default_action(*key))
+            .map(|action| apply(editor, action))
+            .collect()
+    }
+
+    fn text(editor: &mut LineEditor, text: &str) {
+        text.chars().for_each(|ch| {
+            editor.apply(Action::Insert(ch));
+        });
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut editor = LineEditor::new();
+        text(&mut editor, "hello big world");
+        assert_eq!(editor.render("$ "), ("$ hello big world".to_string(), 0));
+
+        keys(&mut editor, &[Key::Alt('b'), Key::Alt('b')]);
+        assert_eq!(editor.cursor, 6);
+        keys(&mut editor, &[Key::Alt('d')]);
+        assert_eq!(editor.buffer.to_string(), "hello  world");
+        keys(&mut editor, &[Key::Ctrl('e'), Key::Ctrl('y')]);
+        assert_eq!(editor.buffer.to_string(), "hello  worldbig");
+
+        keys(&mut editor, &[Key::Ctrl('w')]);
+        assert_eq!(editor.buffer.to_string(), "hello  ");
+        keys(&mut editor, &[Key::Ctrl('a'), Key::Delete, Key::Ctrl('f')]);
+        assert_eq!(editor.buffer.to_string(), "ello  ");
+        assert_eq!(editor.render("$ "), ("$ ello  ".to_string(), 5));
+        keys(&mut editor, &[Key::Ctrl('k')]);
+        assert_eq!(editor.buffer.to_string(), "e");
+        keys(&mut editor, &[Key::Ctrl('u')]);
+        assert!(editor.buffer.is_empty());
+        assert_eq!(keys(&mut editor, &[Key::Backspace]), [Effect::None]);
+        assert_eq!(keys(&mut editor, &[Key::Ctrl('l')]), [Effect::ClearScreen]);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut editor = LineEditor::new();
+        for cmd in ["list peers", "connect 10.0.0.1", "list wallets"] {
+            text(&mut editor, cmd);
+            assert_eq!(
+                apply(&mut editor, Action::Accept),
+                Effect::Submit(cmd.to_string())
+            );
+        }
+        text(&mut editor, "list wallets");
+        apply(&mut editor, Action::Accept);
+        assert_eq!(editor.history().len(), 3);
+
+        text(&mut editor, "draft");
+        keys(&mut editor, &[Key::ArrowUp, Key::ArrowUp]);
+        assert_eq!(editor.buffer.to_string(), "connect 10.0.0.1");
+        keys(&mut editor, &[Key::ArrowDown, Key::ArrowDown]);
+        assert_eq!(editor.buffer.to_string(), "draft");
+        keys(&mut editor, &[Key::Ctrl('u')]);
+
+        keys(
+            &mut editor,
+            &[Key::Ctrl('r'), Key::Char('l'), Key::Char('i')],
+        );
+        assert_eq!(editor.buffer.to_string(), "list wallets");
+        assert_eq!(
+            editor.render("$ "),
+            ("(reverse-i-search)`li': list wallets".to_string(), 12)
+        );
+        keys(&mut editor, &[Key::Ctrl('r')]);
+        assert_eq!(editor.buffer.to_string(), "list peers");
+        keys(&mut editor, &[Key::Esc]);
+        assert!(!editor.is_searching());
+        assert!(editor.buffer.is_empty());
+
+        keys(&mut editor, &[Key::Ctrl('r'), Key::Char('1')]);
+        assert_eq!(
+            keys(&mut editor, &[Key::Enter]),
+            [Effect::Submit("connect 10.0.0.1".to_string())]
+        );
+        assert_eq!(editor.history().len(), 4);
+    }
+
+    #[test]
+    fn test_continuation() {
+        let mut editor = LineEditor::new();
+        text(&mut editor, "echo one \\");
+        assert_eq!(editor.apply(Action::Accept), Effect::Continue);
+        assert_eq!(editor.render("$ "), ("> ".to_string(), 0));
+        text(&mut editor, "two");
+        assert_eq!(
+            editor.apply(Action::Accept),
+            Effect::Submit("echo one two".to_string())
+        );
+        assert_eq!(editor.render("$ "), ("$ ".to_string(), 0));
+    }
+}
@@ -32,9 +32,37 @@ pub enum Error {
     DowncastError(String),
     #[error("command not found: {0}")]
     CommandNotFound(String),
+    #[error("{0}")]
+    InvalidArguments(String),
+    #[error("{0}")]
+    Usage(String),
+    #[error("script error at line {0}: {1}")]
+    Script(usize, String),
+    #[error("user input is not available when executing a script")]
+    NotInteractive,
+    #[error("undefined variable: {0}")]
+    UndefinedVariable(String),
+    #[error("unknown key: {0}")]
+    UnknownKey(String),
+    #[error("unknown key binding: {0}")]
+    UnknownBinding(String),
+    #[error("key binding error at line {0}: {1}")]
+    Keymap(usize, String),
+    #[error("recording error at line {0}: {1}")]
+    Recording(usize, String),
+    #[error("headless terminal did not settle: {0}")]
+    Settle(String),
+    #[error("remote session denied: {0}")]
+    RemoteDenied(String),
+    #[error("remote session error: {0}")]
+    Remote(String),
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
     #[error("aborting...")]
     UserAbort,
     #[error(transparent)]
+    Store(#[from] workflow_store::error::Error),
+    #[error(transparent)]
     CallbackError(#[from] workflow_wasm::callback::CallbackError),
 }
 
@@ -110,6 +138,13 @@ impl<T> From<ChannelError<T>> for Error {
     }
 }
 
+#[cfg(feature = "remote")]
+impl From<workflow_websocket::client::Error> for Error {
+    fn from(err: workflow_websocket::client::Error) -> Error {
+        Error::WebSocket(err.to_string())
+    }
+}
+
 impl<T> From<DowncastError<T>> for Error {
     fn from(err: DowncastError<T>) -> Error {
         Error::DowncastError(err.to_string())
@@ -0,0 +1,271 @@
+//!
+//! Command history of the [`Terminal`](crate::Terminal) line editor.
+//!
+//! The history is limited to a configurable number of entries, ignores
+//! blank lines and suppresses consecutive duplicates. It can be persisted
+//! via [`workflow_store::fs`] - to a file on native and Node.js platforms
+//! and to the browser local storage (keyed by the file name) in the browser.
+//! Entries are stored one per line (with newlines and backslashes escaped as
+//! `\n` and `\\`). Submitted entries are appended to the file, the file is
+//! compacted to the history size limit when the history is loaded.
+//!
+
+use crate::result::Result;
+use crate::unicode::UnicodeString;
+use std::path::Path;
+use workflow_store::fs;
+
+/// Default maximum number of history entries
+pub const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+/// Command history list.
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: Vec<UnicodeString>,
+    max_size: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_SIZE)
+    }
+}
+
+impl History {
+    pub fn new(max_size: usize) -> Self {
+        History {
+            entries: Vec::new(),
+            max_size,
+        }
+    }
+
+    pub fn entries(&self) -> &[UnicodeString] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Set the maximum number of entries, discarding
+    /// the oldest entries exceeding the limit.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.truncate();
+    }
+
+    /// Append an entry to the history. Returns `false` if the entry
+    /// is blank or is a duplicate of the last entry (in which case
+    /// it is not recorded).
+    pub fn push(&mut self, entry: UnicodeString) -> bool {
+        if entry.iter().all(|c| c.is_whitespace())
+            || self.entries.last().map(|last| last.0 == entry.0) == Some(true)
+        {
+            return false;
+        }
+
+        self.entries.push(entry);
+        self.truncate();
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn truncate(&mut self) {
+        if self.entries.len() > self.max_size {
+            let excess = self.entries.len() - self.max_size;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Replace history entries with the text content (one entry per line).
+    pub fn parse(&mut self, text: &str) {
+        self.entries.clear();
+        for line in text.lines() {
+            self.push(unescape(line).as_str().into());
+        }
+    }
+
+    /// Serialize history entries to text (one entry per line).
+    pub fn to_text(&self) -> String {
+        self.entries.iter().map(line).collect()
+    }
+
+    /// Load history entries from the supplied file. A missing
+    /// file results in an empty history. The file is rewritten
+    /// if it contains more entries than the history size limit.
+    pub async fn load(&mut self, filename: &Path) -> Result<()> {
+        if fs::exists(filename).await? {
+            let text = fs::read_to_string(filename).await?;
+            self.parse(&text);
+            if text.lines().count() > self.max_size {
+                self.save(filename).await?;
+            }
+        } else {
+            self.entries.clear();
+        }
+        Ok(())
+    }
+
+    /// Append a single entry to the supplied file.
+    pub async fn append(filename: &Path, entry: &UnicodeString) -> Result<()> {
+        fs::append_string(filename, &line(entry)).await?;
+        Ok(())
+    }
+
+    /// Store history entries to the supplied file.
+    pub async fn save(&self, filename: &Path) -> Result<()> {
+        fs::write_string(filename, &self.to_text()).await?;
+        Ok(())
+    }
+}
+
+/// Serialize an entry to a single line of text (escaping newlines).
+fn line(entry: &UnicodeString) -> String {
+    let mut line = String::new();
+    for c in entry.iter() {
+        match c {
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            c => line.push(*c),
+        }
+    }
+    line.push('\n');
+    line
+}
+
+/// Restore an entry serialized by [`line()`].
+fn unescape(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('r') => text.push('\r'),
+            Some(c) => text.push(c),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Context, HandlerCli, HistoryHandler};
+    use crate::terminal::{Options, Terminal};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+    use workflow_core::task::spawn;
+
+    struct HistoryCli {
+        term: Mutex<Option<Arc<Terminal>>>,
+        handlers: HandlerCli,
+    }
+
+    impl Context for HistoryCli {
+        fn term(&self) -> Arc<Terminal> {
+            self.term.lock().unwrap().clone().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Cli for HistoryCli {
+        fn init(self: Arc<Self>, term: &Arc<Terminal>) -> Result<()> {
+            *self.term.lock().unwrap() = Some(term.clone());
+            self.handlers.register(&self, HistoryHandler);
+            Ok(())
+        }
+        async fn digest(self: Arc<Self>, _term: Arc<Terminal>, cmd: String) -> Result<()> {
+            self.handlers.execute(&self, &cmd).await
+        }
+        async fn complete(
+            self: Arc<Self>,
+            _term: Arc<Terminal>,
+            _cmd: String,
+        ) -> Result<Option<Vec<String>>> {
+            Ok(None)
+        }
+        fn prompt(&self) -> Option<String> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = History::new(3);
+        assert!(history.push("list".into()));
+        assert!(!history.push("list".into()));
+        assert!(!history.push("  ".into()));
+        assert!(history.push("connect".into()));
+        assert!(history.push("list".into()));
+        assert!(history.push("exit".into()));
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.to_text(), "connect\nlist\nexit\n");
+
+        history.set_max_size(2);
+        assert_eq!(history.to_text(), "list\nexit\n");
+
+        history.parse("a\n\nb\nb\nc\n");
+        assert_eq!(history.to_text(), "b\nc\n");
+
+        history.push("echo \"a\nb\" \\n".into());
+        let text = history.to_text();
+        assert_eq!(text, "c\necho \"a\\nb\" \\\\n\n");
+        history.parse(&text);
+        assert_eq!(history.entries()[1].to_string(), "echo \"a\nb\" \\n");
+        history.clear();
+        assert!(history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_history_file() -> Result<()> {
+        let filename = std::env::temp_dir().join(format!(
+            "workflow-terminal-test-history-{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&filename, "a\nb\nc\n")?;
+
+        let cli = Arc::new(HistoryCli {
+            term: Mutex::new(None),
+            handlers: HandlerCli::new(),
+        });
+        let options = Options::new()
+            .with_history_file(filename.to_str().unwrap())
+            .with_history_size(2);
+        let term = Arc::new(Terminal::try_new_headless(cli.clone(), options)?);
+        term.init().await?;
+        assert_eq!(std::fs::read_to_string(&filename)?, "b\nc\n");
+
+        let headless = term.headless().unwrap();
+        let runner = term.clone();
+        spawn(async move { runner.run().await });
+        headless.line("history").await?;
+        assert_eq!(std::fs::read_to_string(&filename)?, "b\nc\nhistory\n");
+        term.exit().await;
+        headless.settle().await?;
+        std::fs::remove_file(&filename)?;
+
+        // an unreadable history file does not prevent initialization
+        let options = Options::new().with_history_file(std::env::temp_dir().to_str().unwrap());
+        let term = Arc::new(Terminal::try_new_headless(cli, options)?);
+        term.init().await?;
+        assert!(term.history().is_empty());
+        Ok(())
+    }
+}
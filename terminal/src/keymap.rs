@@ -0,0 +1,327 @@
+//!
+//! Key bindings of the [`Terminal`](crate::Terminal) line editor.
+//!
+//! A [`Keymap`] binds [`Key`]s (including `Alt` and `Ctrl` combinations)
+//! to line editing [`Action`]s, to application commands executed by the
+//! [`Cli`](crate::Cli) or to exiting the terminal. The default keymap
+//! contains the editor bindings (see [`default_action`]); `Ctrl-C` exits
+//! the terminal in the native environment. Printable characters that are
+//! not bound are inserted into the line.
+//!
+//! In the browser, unless clipboard handling is disabled
+//! ([`Options::disable_clipboard_handling`](crate::Options::disable_clipboard_handling)),
+//! `Ctrl-C`/`Ctrl-V` copy and paste instead of reaching the keymap. On macOS
+//! `Cmd-C`/`Cmd-V` copy and paste as well, while `Ctrl-C` also reaches the
+//! keymap (interrupting, i.e. discarding, the current line). The browser
+//! default action of bound `Ctrl` and `Alt` chords (such as `Ctrl-L` or
+//! `Ctrl-R`) is prevented, however chords reserved by the browser (such as
+//! `Ctrl-W` or `Ctrl-T`) are not delivered to the page and can not be
+//! bound. On macOS, `Alt` (Option) chords are mapped by the physical key
+//! (`Alt-B` rather than the composed `∫`).
+//!
+//! Bindings can be overridden using a configuration file (see
+//! [`Options::with_bindings_file`](crate::Options::with_bindings_file))
+//! containing one `<key> = <binding>` entry per line:
+//!
+//! ```text
+//! # discard the line instead of exiting
+//! ctrl-c = interrupt
+//! # run an application command
+//! alt-s = command status --verbose
+//! # remove the binding
+//! ctrl-l = none
+//! ```
+//!
+//! The bindings in effect are listed by the `bindings` command
+//! ([`BindingsHandler`](crate::cli::BindingsHandler)).
+//!
+
+use crate::editor::{default_action, Action};
+use crate::error::Error;
+use crate::keys::Key;
+use crate::result::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use workflow_store::fs;
+
+const ACTION_NAMES: [(Action, &str); 21] = [
+    (Action::MoveLeft, "move-left"),
+    (Action::MoveRight, "move-right"),
+    (Action::MoveHome, "move-home"),
+    (Action::MoveEnd, "move-end"),
+    (Action::MoveWordLeft, "move-word-left"),
+    (Action::MoveWordRight, "move-word-right"),
+    (Action::DeleteBackward, "delete-backward"),
+    (Action::DeleteForward, "delete-forward"),
+    (Action::KillToEnd, "kill-to-end"),
+    (Action::KillToStart, "kill-to-start"),
+    (Action::KillWordBackward, "kill-word-backward"),
+    (Action::KillWordForward, "kill-word-forward"),
+    (Action::Yank, "yank"),
+    (Action::HistoryPrevious, "history-previous"),
+    (Action::HistoryNext, "history-next"),
+    (Action::ReverseSearch, "reverse-search"),
+    (Action::ClearScreen, "clear-screen"),
+    (Action::Accept, "accept"),
+    (Action::Complete, "complete"),
+    (Action::Cancel, "cancel"),
+    (Action::Interrupt, "interrupt"),
+];
+
+/// Key binding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Binding {
+    /// Apply a line editing action
+    Action(Action),
+    /// Execute an application command (the line being edited is preserved)
+    Command(String),
+    /// Exit the terminal
+    Exit,
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Action(Action::Insert(c)) => write!(f, "insert {c}"),
+            Binding::Action(action) => {
+                let (_, name) = ACTION_NAMES.iter().find(|(a, _)| a == action).unwrap();
+                write!(f, "{name}")
+            }
+            Binding::Command(cmd) => write!(f, "command {cmd}"),
+            Binding::Exit => write!(f, "exit"),
+        }
+    }
+}
+
+impl FromStr for Binding {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Some(cmd) = text.strip_prefix("command ") {
+            return Ok(Binding::Command(cmd.trim().to_string()));
+        }
+        if let Some(c) = text.strip_prefix("insert ") {
+            let mut chars = c.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                return Ok(Binding::Action(Action::Insert(c)));
+            }
+        }
+        if text == "exit" {
+            return Ok(Binding::Exit);
+        }
+        ACTION_NAMES
+            .iter()
+            .find(|(_, name)| *name == text)
+            .map(|(action, _)| Binding::Action(*action))
+            .ok_or_else(|| Error::UnknownBinding(text.to_string()))
+    }
+}
+
+/// Keys with default bindings (in addition to printable characters)
+fn default_keys() -> impl Iterator<Item = Key> {
+    [
+        Key::Enter,
+        Key::Backspace,
+        Key::ArrowLeft,
+        Key::ArrowRight,
+        Key::ArrowUp,
+        Key::ArrowDown,
+        Key::Home,
+        Key::End,
+        Key::PageUp,
+        Key::PageDown,
+        Key::Tab,
+        Key::BackTab,
+        Key::Delete,
+        Key::Insert,
+        Key::Esc,
+    ]
+    .into_iter()
+    .chain(('a'..='z').flat_map(|c| [Key::Ctrl(c), Key::Alt(c)]))
+}
+
+/// Key bindings.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Key, Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = default_keys()
+            .filter_map(|key| default_action(key).map(|action| (key, Binding::Action(action))))
+            .collect::<HashMap<_, _>>();
+        if cfg!(not(target_arch = "wasm32")) {
+            bindings.insert(Key::Ctrl('c'), Binding::Exit);
+        }
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// Create a keymap containing the default bindings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a keymap without bindings (printable characters are still inserted)
+    pub fn empty() -> Self {
+        Keymap {
+            bindings: HashMap::new(),
+        }
+    }
+
+    pub fn with_binding(mut self, key: Key, binding: Binding) -> Self {
+        self.bind(key, binding);
+        self
+    }
+
+    pub fn bind(&mut self, key: Key, binding: Binding) {
+        self.bindings.insert(key, binding);
+    }
+
+    pub fn unbind(&mut self, key: Key) {
+        self.bindings.remove(&key);
+    }
+
+    /// Returns the binding of the key; unbound printable
+    /// characters are bound to [`Action::Insert`].
+    pub fn get(&self, key: Key) -> Option<Binding> {
+        match (self.bindings.get(&key), key) {
+            (Some(binding), _) => Some(binding.clone()),
+            (None, Key::Char(c)) => Some(Binding::Action(Action::Insert(c))),
+            _ => None,
+        }
+    }
+
+    /// Bindings sorted by the key name
+    pub fn bindings(&self) -> Vec<(Key, Binding)> {
+        let mut bindings = self
+            .bindings
+            .iter()
+            .map(|(key, binding)| (*key, binding.clone()))
+            .collect::<Vec<_>>();
+        bindings.sort_by_cached_key(|(key, _)| key.to_string());
+        bindings
+    }
+
+    /// Apply `<key> = <binding>` entries (`none` removes the binding);
+    /// empty lines and lines starting with `#` are ignored.
+    pub fn configure(&mut self, text: &str) -> Result<()> {
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |err: Error| Error::Keymap(index + 1, err.to_string());
+            let (key, binding) = line
+                .split_once('=')
+                .ok_or_else(|| Error::Keymap(index + 1, "expected `<key> = <binding>`".into()))?;
+            let key = key.trim().parse::<Key>().map_err(error)?;
+            match binding.trim() {
+                "none" => self.unbind(key),
+                binding => self.bind(key, binding.parse().map_err(error)?),
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply bindings from a configuration file (a local storage key
+    /// in the browser); the keymap is not changed if the file does not exist.
+    pub async fn load(&mut self, filename: &Path) -> Result<()> {
+        if fs::exists(filename).await? {
+            self.configure(&fs::read_to_string(filename).await?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::headless::tests::test_terminal;
+    use crate::terminal::Options;
+
+    #[test]
+    fn test_keymap() -> Result<()> {
+        for key in [Key::PageDown, Key::Char(' '), Key::Alt('B'), Key::Ctrl('c')] {
+            assert_eq!(key.to_string().parse::<Key>()?, key);
+        }
+        assert_eq!("Ctrl-R".parse::<Key>()?, Key::Ctrl('r'));
+
+        let mut keymap = Keymap::new();
+        assert_eq!(
+            keymap.get(Key::Ctrl('a')),
+            Some(Binding::Action(Action::MoveHome))
+        );
+        assert_eq!(
+            keymap.get(Key::Char('x')),
+            Some(Binding::Action(Action::Insert('x')))
+        );
+        assert_eq!(keymap.get(Key::Alt('s')), None);
+
+        keymap.configure(
+            r#"
+            # overrides
+            ctrl-c = interrupt
+            alt-s = command status --verbose
+            ctrl-l = none
+        "#,
+        )?;
+        assert_eq!(
+            keymap.get(Key::Ctrl('c')),
+            Some(Binding::Action(Action::Interrupt))
+        );
+        assert_eq!(
+            keymap.get(Key::Alt('s')),
+            Some(Binding::Command("status --verbose".into()))
+        );
+        assert_eq!(keymap.get(Key::Ctrl('l')), None);
+
+        let bindings = keymap.bindings();
+        assert_eq!(bindings.first().unwrap().0, Key::Alt('b'));
+        assert!(bindings.iter().all(|(_, binding)| binding
+            .to_string()
+            .parse::<Binding>()
+            .unwrap()
+            == *binding));
+
+        assert!(matches!(
+            keymap.configure("ctrl-x = yank\nctrl-xy = yank"),
+            Err(Error::Keymap(2, _))
+        ));
+        assert!(matches!(
+            keymap.configure("ctrl-x = jump"),
+            Err(Error::Keymap(1, msg)) if msg == "unknown key binding: jump"
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bindings() -> Result<()> {
+        let keymap = Keymap::new().with_binding(Key::Alt('h'), Binding::Command("history".into()));
+        let (term, headless) = test_terminal(Options::new().with_keymap(keymap), true).await?;
+
+        term.bind(Key::Ctrl('c'), Binding::Action(Action::Interrupt));
+        headless.line("bindings").await?;
+        let lines = headless.lines();
+        assert_eq!(lines[1], "Key        Binding");
+        assert!(lines.contains(&"alt-h      command history".to_string()));
+        assert!(lines.contains(&"ctrl-c     interrupt".to_string()));
+
+        headless.clear();
+        headless.text_input("abc").await?;
+        headless.key(Key::Ctrl('c')).await?;
+        assert!(headless.is_running());
+        headless.text_input("gr").await?;
+        headless.keys(&[Key::Alt('h'), Key::Char('x')]).await?;
+        assert_eq!(headless.lines(), ["$ abc", "$ gr", "1  bindings", "$ grx"]);
+
+        term.exit().await;
+        headless.settle().await?;
+        Ok(())
+    }
+}
@@ -1,6 +1,15 @@
 //!
 //! Terminal key definitions
 //!
+//! Keys can be converted to and from their names (as used in
+//! [`Keymap`](crate::keymap::Keymap) configuration files): `enter`, `tab`,
+//! `left`, `pageup`, `esc` etc., `ctrl-<char>`, `alt-<char>`, `space`
+//! or a single character.
+//!
+
+use crate::error::Error;
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Key {
@@ -14,6 +23,7 @@ pub enum Key {
     End,
     PageUp,
     PageDown,
+    Tab,
     BackTab,
     Delete,
     Insert,
@@ -22,3 +32,70 @@ pub enum Key {
     Ctrl(char),
     Esc,
 }
+
+const KEY_NAMES: [(Key, &str); 16] = [
+    (Key::Enter, "enter"),
+    (Key::Backspace, "backspace"),
+    (Key::ArrowLeft, "left"),
+    (Key::ArrowRight, "right"),
+    (Key::ArrowUp, "up"),
+    (Key::ArrowDown, "down"),
+    (Key::Home, "home"),
+    (Key::End, "end"),
+    (Key::PageUp, "pageup"),
+    (Key::PageDown, "pagedown"),
+    (Key::Tab, "tab"),
+    (Key::BackTab, "backtab"),
+    (Key::Delete, "delete"),
+    (Key::Insert, "insert"),
+    (Key::Esc, "esc"),
+    (Key::Char(' '), "space"),
+];
+
+fn char_name(c: char) -> String {
+    if c == ' ' {
+        "space".to_string()
+    } else {
+        c.to_string()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Char(c) => write!(f, "{}", char_name(*c)),
+            Key::Alt(c) => write!(f, "alt-{}", char_name(*c)),
+            Key::Ctrl(c) => write!(f, "ctrl-{}", char_name(*c)),
+            key => {
+                let (_, name) = KEY_NAMES.iter().find(|(k, _)| k == key).unwrap();
+                write!(f, "{name}")
+            }
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let parse_char = |text: &str| {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ if text.eq_ignore_ascii_case("space") => Ok(' '),
+                _ => Err(Error::UnknownKey(name.to_string())),
+            }
+        };
+
+        let lowercase = name.to_lowercase();
+        if let Some(text) = lowercase.strip_prefix("ctrl-") {
+            Ok(Key::Ctrl(parse_char(text)?))
+        } else if lowercase.starts_with("alt-") {
+            Ok(Key::Alt(parse_char(&name[4..])?))
+        } else if let Some((key, _)) = KEY_NAMES.iter().find(|(_, n)| *n == lowercase) {
+            Ok(*key)
+        } else {
+            Ok(Key::Char(parse_char(name)?))
+        }
+    }
+}
@@ -95,24 +95,42 @@
 
 extern crate self as workflow_terminal;
 
+pub mod args;
 pub mod clear;
 pub mod cli;
+pub mod completion;
 pub mod crlf;
 pub mod cursor;
+pub mod editor;
 pub mod error;
+pub mod history;
+pub mod keymap;
 pub mod keys;
 pub mod macros;
+pub mod pipeline;
 pub mod prelude;
+pub mod progress;
+pub mod recording;
+#[cfg(feature = "remote")]
+pub mod remote;
 pub mod result;
+pub mod script;
+pub mod table;
 pub mod terminal;
 pub mod unicode;
 
 pub use cli::{Cli, Context, Handler, HandlerCli};
 pub use crlf::CrLf;
+pub use keymap::{Binding, Keymap};
 pub use macros::*;
+pub use progress::{ProgressBar, Spinner};
+pub use recording::Recording;
 pub use result::Result;
+pub use script::Script;
+pub use table::{Align, Column, Table};
 pub use terminal::parse;
 pub use terminal::Event;
+pub use terminal::Headless;
 pub use terminal::Modifiers;
 pub use terminal::Options;
 pub use terminal::TargetElement;
@@ -0,0 +1,448 @@
+//!
+//! Command pipelines and output redirection.
+//!
+//! [`HandlerCli`](crate::HandlerCli) executes commands containing `|`
+//! and starting with the verb of a registered handler as pipelines
+//! (commands of unknown verbs are not parsed as pipelines): the output of each command (written using
+//! [`Terminal::write`](crate::Terminal::write) or [`Terminal::writeln`](crate::Terminal::writeln)
+//! by the command, see [`Terminal::capture_output`](crate::Terminal::capture_output))
+//! is captured and passed to the next command. The following built-in
+//! filters process the output of the preceding command (a registered
+//! handler with the same name takes precedence over the filter):
+//!
+//! - `grep [-v] [-i] <pattern>` - lines matching (or not matching, `-v`)
+//!   the regular expression, `-i` ignores case
+//! - `head [-n <count> | -<count>]` - first lines (10 by default)
+//! - `wc [-l]` - number of lines, words and characters (lines only with `-l`)
+//!
+//! The output of the last command can be redirected to a file using
+//! `> <file>` (or appended to a file using `>> <file>`). Files are written
+//! using [`workflow_store::fs`] (a local storage key in the browser).
+//!
+//! `|` and `>` within single or double quotes (or escaped with `\`)
+//! are passed to the command as-is.
+//!
+//! ```text
+//! list-peers | grep 10.0 > peers.txt
+//! note add "a | b" >> "my notes.txt"
+//! ```
+//!
+
+use crate::error::Error;
+use crate::result::Result;
+use regex::RegexBuilder;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use workflow_store::fs;
+
+/// Default number of lines output by the `head` filter
+pub const DEFAULT_HEAD_LINES: usize = 10;
+
+/// Built-in filter processing the output of the preceding command.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// `grep [-v] [-i] <pattern>`
+    Grep { regex: regex::Regex, invert: bool },
+    /// `head [-n <count> | -<count>]`
+    Head(usize),
+    /// `wc [-l]`
+    Wc { lines_only: bool },
+}
+
+impl Filter {
+    /// Create a filter from command arguments; returns `None`
+    /// if the command is not a built-in filter.
+    pub fn try_from_argv(argv: &[String]) -> Option<Result<Filter>> {
+        let (name, args) = argv.split_first()?;
+        let filter = match name.as_str() {
+            "grep" => Self::grep(args),
+            "head" => Self::head(args),
+            "wc" => match args {
+                [] => Ok(Filter::Wc { lines_only: false }),
+                [arg] if arg == "-l" => Ok(Filter::Wc { lines_only: true }),
+                _ => Err(Error::InvalidArguments("usage: wc [-l]".into())),
+            },
+            _ => return None,
+        };
+        Some(filter)
+    }
+
+    fn grep(args: &[String]) -> Result<Filter> {
+        let mut invert = false;
+        let mut ignore_case = false;
+        let mut patterns = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-v" => invert = true,
+                "-i" => ignore_case = true,
+                "-vi" | "-iv" => (invert, ignore_case) = (true, true),
+                _ => patterns.push(arg),
+            }
+        }
+        let [pattern] = patterns.as_slice() else {
+            return Err(Error::InvalidArguments(
+                "usage: grep [-v] [-i] <pattern>".into(),
+            ));
+        };
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|err| Error::InvalidArguments(format!("grep: {err}")))?;
+        Ok(Filter::Grep { regex, invert })
+    }
+
+    fn head(args: &[String]) -> Result<Filter> {
+        let count = match args {
+            [] => return Ok(Filter::Head(DEFAULT_HEAD_LINES)),
+            [flag, count] if flag == "-n" => count.as_str(),
+            [count] => count.strip_prefix('-').unwrap_or(""),
+            _ => "",
+        };
+        count
+            .parse()
+            .map(Filter::Head)
+            .map_err(|_| Error::InvalidArguments("usage: head [-n <count>]".into()))
+    }
+
+    /// Process lines output by the preceding command
+    pub fn apply(&self, lines: Vec<String>) -> Vec<String> {
+        match self {
+            Filter::Grep { regex, invert } => lines
+                .into_iter()
+                .filter(|line| regex.is_match(line) != *invert)
+                .collect(),
+            Filter::Head(count) => lines.into_iter().take(*count).collect(),
+            Filter::Wc { lines_only: true } => vec![lines.len().to_string()],
+            Filter::Wc { lines_only: false } => {
+                let words = lines
+                    .iter()
+                    .map(|line| line.split_whitespace().count())
+                    .sum::<usize>();
+                let chars = lines
+                    .iter()
+                    .map(|line| line.chars().count() + 1)
+                    .sum::<usize>();
+                vec![format!("{} {words} {chars}", lines.len())]
+            }
+        }
+    }
+}
+
+/// Output redirection of the last pipeline command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub filename: String,
+    /// append to the file (`>>`) instead of replacing it (`>`)
+    pub append: bool,
+}
+
+impl Redirect {
+    fn parse(target: &str, append: bool) -> Result<Redirect> {
+        let target = target.trim();
+        let filename = match target.chars().next() {
+            Some(quote @ ('"' | '\'')) => target
+                .strip_prefix(quote)
+                .and_then(|target| target.strip_suffix(quote))
+                .filter(|filename| !filename.contains(quote))
+                .unwrap_or_default(),
+            _ if target.contains(char::is_whitespace) => "",
+            _ => target,
+        };
+        if filename.is_empty() || split_unquoted(target, '|').len() > 1 {
+            return Err(Error::InvalidArguments(
+                "expected a single file name after `>`".into(),
+            ));
+        }
+        Ok(Redirect {
+            filename: filename.to_string(),
+            append,
+        })
+    }
+
+    /// Write lines to the redirection target file.
+    pub async fn write(&self, lines: &[String]) -> Result<()> {
+        let filename = fs::resolve_path(&self.filename)?;
+        let text = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        if self.append {
+            fs::append_string(&filename, &text).await?;
+        } else {
+            fs::write_string(&filename, &text).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Split the text at occurrences of the separator that are not
+/// within single or double quotes and not escaped with `\`.
+pub(crate) fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (c, quote) {
+            _ if escaped => escaped = false,
+            ('\\', _) => escaped = true,
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('"' | '\'', None) => quote = Some(c),
+            (c, None) if c == separator => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+thread_local! {
+    /// Output capture of the future being polled on this thread
+    static OUTPUT_CAPTURE: RefCell<Option<Arc<Mutex<String>>>> = const { RefCell::new(None) };
+}
+
+/// Append the text to the output capture of the future being
+/// polled (if any). Returns `false` if no capture is in progress.
+pub(crate) fn capture(text: &str) -> bool {
+    OUTPUT_CAPTURE.with(|capture| match capture.borrow().as_ref() {
+        Some(output) => {
+            output.lock().unwrap().push_str(text);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Future capturing the terminal output written while it is polled
+/// (i.e. by the wrapped future, but not by other tasks). Resolves to
+/// the output of the wrapped future and the captured lines.
+pub(crate) struct CaptureOutput<F> {
+    future: Pin<Box<F>>,
+    output: Arc<Mutex<String>>,
+}
+
+impl<F> CaptureOutput<F> {
+    pub fn new(future: F) -> Self {
+        CaptureOutput {
+            future: Box::pin(future),
+            output: Arc::new(Mutex::new(String::new())),
+        }
+    }
+
+    /// Captured output as lines (with ANSI escape sequences removed)
+    fn lines(&self) -> Vec<String> {
+        let text = strip_ansi(&std::mem::take(&mut *self.output.lock().unwrap()));
+        match text.strip_suffix('\n').unwrap_or(&text) {
+            "" => Vec::new(),
+            text => text
+                .split('\n')
+                .map(|line| line.replace('\r', ""))
+                .collect(),
+        }
+    }
+}
+
+impl<F: Future> Future for CaptureOutput<F> {
+    type Output = (F::Output, Vec<String>);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        struct Restore(Option<Arc<Mutex<String>>>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                OUTPUT_CAPTURE.with(|capture| *capture.borrow_mut() = self.0.take());
+            }
+        }
+
+        let output = self.output.clone();
+        let restore = Restore(OUTPUT_CAPTURE.with(|capture| capture.borrow_mut().replace(output)));
+        let poll = self.future.as_mut().poll(cx);
+        drop(restore);
+        poll.map(|result| (result, self.lines()))
+    }
+}
+
+/// Remove ANSI escape sequences (CSI and OSC) from the text.
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    plain
+}
+
+/// Command split into pipeline stages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    /// commands separated by `|`
+    pub stages: Vec<String>,
+    pub redirect: Option<Redirect>,
+}
+
+impl Pipeline {
+    pub fn parse(cmd: &str) -> Result<Pipeline> {
+        let (cmd, redirect) = match split_unquoted(cmd, '>').as_slice() {
+            [cmd] => (*cmd, None),
+            [cmd, target] => (*cmd, Some(Redirect::parse(target, false)?)),
+            [cmd, "", target] => (*cmd, Some(Redirect::parse(target, true)?)),
+            _ => {
+                return Err(Error::InvalidArguments(
+                    "expected a single file name after `>`".into(),
+                ))
+            }
+        };
+
+        let stages = split_unquoted(cmd, '|')
+            .into_iter()
+            .map(|stage| stage.trim().to_string())
+            .collect::<Vec<_>>();
+        if stages.iter().any(String::is_empty) {
+            return Err(Error::InvalidArguments("empty pipeline command".into()));
+        }
+        Ok(Pipeline { stages, redirect })
+    }
+
+    /// Returns true if the command is a single command without redirection
+    pub fn is_simple(&self) -> bool {
+        self.stages.len() == 1 && self.redirect.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::headless::tests::test_terminal;
+    use crate::terminal::parse;
+    use crate::terminal::Options;
+
+    fn filter(cmd: &str) -> Filter {
+        Filter::try_from_argv(&parse(cmd)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_pipeline() -> Result<()> {
+        let pipeline = Pipeline::parse("list-peers -a | grep 10.0 >> peers.txt")?;
+        assert_eq!(pipeline.stages, ["list-peers -a", "grep 10.0"]);
+        assert_eq!(
+            pipeline.redirect,
+            Some(Redirect {
+                filename: "peers.txt".into(),
+                append: true
+            })
+        );
+        assert!(Pipeline::parse("list")?.is_simple());
+        assert!(Pipeline::parse("list |").is_err());
+        assert!(Pipeline::parse("list > a b").is_err());
+        assert!(Pipeline::parse("list > a|b").is_err());
+        assert!(Pipeline::parse("list > a > b").is_err());
+
+        let pipeline = Pipeline::parse(r#"note "a | b" 'x > y' c\|d | wc > "my notes.txt""#)?;
+        assert_eq!(pipeline.stages, [r#"note "a | b" 'x > y' c\|d"#, "wc"]);
+        assert_eq!(
+            pipeline.redirect,
+            Some(Redirect {
+                filename: "my notes.txt".into(),
+                append: false
+            })
+        );
+        assert!(Pipeline::parse(r#"echo "a > b""#)?.is_simple());
+
+        let lines = ["10.0.0.1 up", "10.0.0.2 down", "192.168.0.1 up"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(filter("grep 10\\.0").apply(lines.clone()).len(), 2);
+        assert_eq!(
+            filter("grep -v -i UP").apply(lines.clone()),
+            ["10.0.0.2 down"]
+        );
+        assert_eq!(filter("head -1").apply(lines.clone()), ["10.0.0.1 up"]);
+        assert_eq!(filter("head -n 2").apply(lines.clone()).len(), 2);
+        assert_eq!(filter("wc").apply(lines.clone()), ["3 6 41"]);
+        assert_eq!(filter("wc -l").apply(lines), ["3"]);
+        assert!(Filter::try_from_argv(&parse("list")).is_none());
+        assert!(Filter::try_from_argv(&parse("head -n x")).unwrap().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exec_pipeline() -> Result<()> {
+        let (term, headless) = test_terminal(Options::new(), false).await?;
+
+        let filename = std::env::temp_dir().join(format!(
+            "workflow-terminal-test-pipeline-{}.txt",
+            std::process::id()
+        ));
+        let script = crate::script::Script::parse(
+            "bindings | grep -i CTRL-Y; bindings | grep alt | wc -l\n\
+             echo one \"two | three\" | grep two; echo one | head -n 1\n\
+             bindings | grep Binding > $FILE; history | wc -l >> $FILE\n\
+             bindings | grep",
+        )?
+        .with_var("FILE", filename.to_str().unwrap());
+        assert_eq!(
+            term.exec_script(&script).await?,
+            crate::script::EXIT_FAILURE
+        );
+        assert_eq!(
+            headless.lines(),
+            [
+                "ctrl-y     yank",
+                "3",
+                r#"one "two | three""#,
+                "-n 1",
+                "line 4: usage: grep [-v] [-i] <pattern>"
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&filename)?,
+            "Key        Binding\n0\n"
+        );
+        std::fs::remove_file(&filename)?;
+
+        // status lines are not captured
+        headless.clear();
+        let script = crate::script::Script::parse("sync | grep block")?;
+        assert_eq!(
+            term.exec_script(&script).await?,
+            crate::script::EXIT_SUCCESS
+        );
+        assert_eq!(headless.lines(), ["block 1", "block 2"]);
+
+        // commands of unknown verbs are not parsed as pipelines
+        headless.clear();
+        let script = crate::script::Script::parse("unknown > a > b")?;
+        assert_eq!(
+            term.exec_script(&script).await?,
+            crate::script::EXIT_FAILURE
+        );
+        assert_eq!(headless.lines(), ["line 1: command not found: unknown"]);
+        Ok(())
+    }
+}
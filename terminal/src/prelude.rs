@@ -1,6 +1,9 @@
 pub use crate::{
     cli,
-    cli::{declare_handler, get_handler_help, register_handlers},
+    cli::{
+        declare_handler, get_handler_help, get_handler_usage, register_handlers, BindingsHandler,
+        HelpHandler, HistoryHandler,
+    },
     parse,
     terminal::{Terminal, Theme},
     terrorln, tpara, tprint, tprintln, twarnln, Cli, Context, CrLf, Handler,
@@ -0,0 +1,431 @@
+//!
+//! Progress bar and spinner widgets.
+//!
+//! Widgets are displayed as terminal status lines (see
+//! [`Terminal::add_status_line`](crate::Terminal::add_status_line)) at the
+//! bottom of the terminal output. They are redrawn below any output written
+//! using [`Terminal::writeln`](crate::Terminal::writeln) (including output
+//! from concurrent tasks) and above the prompt or the user input line when
+//! the terminal is idle or waiting for [`Terminal::ask`](crate::Terminal::ask).
+//! Widgets use only ANSI sequences supported by xterm.js and native terminals.
+//!
+//! ```ignore
+//! let progress = ProgressBar::new(&term, files.len() as u64).with_message("downloading");
+//! for file in files {
+//!     download(file).await?;
+//!     progress.inc(1);
+//! }
+//! progress.finish_with_message("download complete");
+//! ```
+//!
+
+use crate::terminal::Terminal;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use textwrap::core::display_width;
+use workflow_core::task::{sleep, spawn};
+
+/// Maximum width of the progress bar (excluding the message and the counters)
+pub const DEFAULT_BAR_WIDTH: usize = 40;
+/// Spinner animation frames
+pub const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+/// Spinner animation interval
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Progress {
+    message: String,
+    position: u64,
+    total: u64,
+}
+
+/// Render a progress bar line fitting the supplied width.
+fn render_bar(message: &str, position: u64, total: u64, width: Option<usize>) -> String {
+    let position = position.min(total);
+    let percent = (position as u128 * 100)
+        .checked_div(total as u128)
+        .unwrap_or(100);
+    let counters = format!("{percent:>3}% {position}/{total}");
+    let prefix = if message.is_empty() {
+        String::new()
+    } else {
+        format!("{message} ")
+    };
+
+    let available = width
+        .map(|width| {
+            width
+                .saturating_sub(1)
+                .saturating_sub(display_width(&prefix) + display_width(&counters) + 3)
+        })
+        .unwrap_or(DEFAULT_BAR_WIDTH);
+    let bar_width = available.min(DEFAULT_BAR_WIDTH);
+    if bar_width < 5 {
+        return format!("{prefix}{counters}");
+    }
+
+    let filled = if total == 0 {
+        bar_width
+    } else {
+        // `position <= total`, as such the result does not exceed `bar_width`
+        (position as u128 * bar_width as u128 / total as u128) as usize
+    };
+    let bar = if filled >= bar_width {
+        "=".repeat(bar_width)
+    } else {
+        format!(
+            "{}>{}",
+            "=".repeat(filled),
+            " ".repeat(bar_width - filled - 1)
+        )
+    };
+    format!("{prefix}[{bar}] {counters}")
+}
+
+/// Progress bar widget. The progress bar is removed
+/// when finished or dropped.
+pub struct ProgressBar {
+    term: Arc<Terminal>,
+    id: u64,
+    state: Mutex<Progress>,
+    finished: AtomicBool,
+}
+
+impl ProgressBar {
+    /// Create and display a progress bar for the supplied total count
+    pub fn new(term: &Arc<Terminal>, total: u64) -> Self {
+        let id = term.add_status_line(render_bar("", 0, total, term.cols()));
+        ProgressBar {
+            term: term.clone(),
+            id,
+            state: Mutex::new(Progress {
+                message: String::new(),
+                position: 0,
+                total,
+            }),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_message(self, message: &str) -> Self {
+        self.set_message(message);
+        self
+    }
+
+    pub fn set_message(&self, message: &str) {
+        self.update(|state| state.message = message.to_string());
+    }
+
+    pub fn set_position(&self, position: u64) {
+        self.update(|state| state.position = position);
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.update(|state| state.total = total);
+    }
+
+    /// Advance the progress by `delta`
+    pub fn inc(&self, delta: u64) {
+        self.update(|state| state.position = state.position.saturating_add(delta));
+    }
+
+    pub fn position(&self) -> u64 {
+        self.state.lock().unwrap().position
+    }
+
+    pub fn total(&self) -> u64 {
+        self.state.lock().unwrap().total
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Progress)) {
+        let line = {
+            let mut state = self.state.lock().unwrap();
+            f(&mut state);
+            render_bar(
+                &state.message,
+                state.position,
+                state.total,
+                self.term.cols(),
+            )
+        };
+        if !self.finished.load(Ordering::SeqCst) {
+            self.term.set_status_line(self.id, line);
+        }
+    }
+
+    /// Remove the progress bar
+    pub fn finish(&self) {
+        if !self.finished.swap(true, Ordering::SeqCst) {
+            self.term.remove_status_line(self.id);
+        }
+    }
+
+    /// Remove the progress bar and output the supplied message
+    pub fn finish_with_message(&self, message: &str) {
+        self.finish();
+        self.term.writeln(message);
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Spinner widget animated while a task of unknown duration
+/// is in progress. The spinner is removed when finished or dropped.
+pub struct Spinner {
+    term: Arc<Terminal>,
+    id: u64,
+    message: Arc<Mutex<String>>,
+    frame: Arc<AtomicUsize>,
+    active: Arc<AtomicBool>,
+}
+
+impl Spinner {
+    /// Create and display an animated spinner
+    pub fn new(term: &Arc<Terminal>, message: &str) -> Self {
+        let spinner = Self::new_impl(term, message);
+
+        let term = term.clone();
+        let id = spinner.id;
+        let message = spinner.message.clone();
+        let frame = spinner.frame.clone();
+        let active = spinner.active.clone();
+        spawn(async move {
+            loop {
+                sleep(SPINNER_INTERVAL).await;
+                if !active.load(Ordering::SeqCst) {
+                    break;
+                }
+                let frame = frame.fetch_add(1, Ordering::SeqCst) + 1;
+                let line = Self::render(&message.lock().unwrap(), frame);
+                term.set_status_line(id, line);
+            }
+        });
+
+        spinner
+    }
+
+    /// Create and display a spinner that is advanced
+    /// manually using [`Spinner::tick`]
+    pub fn new_manual(term: &Arc<Terminal>, message: &str) -> Self {
+        Self::new_impl(term, message)
+    }
+
+    fn new_impl(term: &Arc<Terminal>, message: &str) -> Self {
+        let id = term.add_status_line(Self::render(message, 0));
+        Spinner {
+            term: term.clone(),
+            id,
+            message: Arc::new(Mutex::new(message.to_string())),
+            frame: Arc::new(AtomicUsize::new(0)),
+            active: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    fn render(message: &str, frame: usize) -> String {
+        format!("{} {message}", SPINNER_FRAMES[frame % SPINNER_FRAMES.len()])
+    }
+
+    pub fn set_message(&self, message: &str) {
+        *self.message.lock().unwrap() = message.to_string();
+        self.refresh();
+    }
+
+    /// Advance the spinner animation
+    pub fn tick(&self) {
+        self.frame.fetch_add(1, Ordering::SeqCst);
+        self.refresh();
+    }
+
+    fn refresh(&self) {
+        if self.active.load(Ordering::SeqCst) {
+            let line = Self::render(
+                &self.message.lock().unwrap(),
+                self.frame.load(Ordering::SeqCst),
+            );
+            self.term.set_status_line(self.id, line);
+        }
+    }
+
+    /// Remove the spinner
+    pub fn finish(&self) {
+        if self.active.swap(false, Ordering::SeqCst) {
+            self.term.remove_status_line(self.id);
+        }
+    }
+
+    /// Remove the spinner and output the supplied message
+    pub fn finish_with_message(&self, message: &str) {
+        self.finish();
+        self.term.writeln(message);
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Context, Handler, HandlerCli};
+    use crate::result::Result;
+    use crate::terminal::Options;
+    use async_trait::async_trait;
+
+    struct ProgressCli {
+        term: Mutex<Option<Arc<Terminal>>>,
+        handlers: HandlerCli,
+    }
+
+    impl Context for ProgressCli {
+        fn term(&self) -> Arc<Terminal> {
+            self.term.lock().unwrap().clone().unwrap()
+        }
+    }
+
+    struct SyncBlocks;
+
+    #[async_trait]
+    impl Handler for SyncBlocks {
+        fn verb(&self, _ctx: &Arc<dyn Context>) -> Option<&'static str> {
+            Some("sync")
+        }
+        async fn handle(
+            self: Arc<Self>,
+            ctx: &Arc<dyn Context>,
+            _argv: Vec<String>,
+            _cmd: &str,
+        ) -> Result<()> {
+            let term = ctx.term();
+            let headless = term.headless().unwrap();
+            let progress = ProgressBar::new(&term, 2);
+            for block in 1..=2 {
+                term.writeln(format!("block {block}"));
+                progress.inc(1);
+            }
+            assert_eq!(
+                headless.lines(),
+                [
+                    "block 1",
+                    "block 2",
+                    "[============================] 100% 2/2"
+                ]
+            );
+            progress.finish_with_message("synced");
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Cli for ProgressCli {
+        fn init(self: Arc<Self>, term: &Arc<Terminal>) -> Result<()> {
+            *self.term.lock().unwrap() = Some(term.clone());
+            self.handlers.register(&self, SyncBlocks);
+            Ok(())
+        }
+        async fn digest(self: Arc<Self>, _term: Arc<Terminal>, cmd: String) -> Result<()> {
+            self.handlers.execute(&self, &cmd).await
+        }
+        async fn complete(
+            self: Arc<Self>,
+            _term: Arc<Terminal>,
+            _cmd: String,
+        ) -> Result<Option<Vec<String>>> {
+            Ok(None)
+        }
+        fn prompt(&self) -> Option<String> {
+            None
+        }
+    }
+
+    async fn progress_terminal() -> Result<Arc<Terminal>> {
+        let cli = Arc::new(ProgressCli {
+            term: Mutex::new(None),
+            handlers: HandlerCli::new(),
+        });
+        let term = Arc::new(Terminal::try_new_headless(cli, Options::new())?);
+        term.init().await?;
+        term.headless().unwrap().set_cols(40);
+        Ok(term)
+    }
+
+    #[test]
+    fn test_render_bar() {
+        assert_eq!(
+            render_bar("sync", 25, 100, Some(40)),
+            "sync [=====>              ]  25% 25/100"
+        );
+        assert_eq!(
+            render_bar("", 3, 3, None),
+            format!("[{}] 100% 3/3", "=".repeat(DEFAULT_BAR_WIDTH))
+        );
+        assert_eq!(render_bar("sync", 1, 2, Some(16)), "sync  50% 1/2");
+        let max = u64::MAX;
+        assert_eq!(
+            render_bar("", max / 2, max, None),
+            format!(
+                "[{}>{}]  49% {}/{max}",
+                "=".repeat(19),
+                " ".repeat(20),
+                max / 2
+            )
+        );
+        assert_eq!(
+            render_bar("", max, 1, None),
+            format!("[{}] 100% 1/1", "=".repeat(DEFAULT_BAR_WIDTH))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_lines() -> Result<()> {
+        let term = progress_terminal().await?;
+        let headless = term.headless().unwrap();
+        let runner = term.clone();
+        spawn(async move { runner.run().await });
+        headless.text_input("li").await?;
+
+        let progress = ProgressBar::new(&term, 4).with_message("sync");
+        let spinner = Spinner::new_manual(&term, "waiting");
+        progress.inc(1);
+        term.writeln("block 1");
+        assert_eq!(
+            headless.lines(),
+            [
+                "block 1",
+                "sync [=====>                 ]  25% 1/4",
+                "| waiting",
+                "$ li"
+            ]
+        );
+
+        spinner.tick();
+        progress.finish_with_message("synced");
+        headless.text_input("st").await?;
+        assert_eq!(
+            headless.lines(),
+            ["block 1", "synced", "/ waiting", "$ list"]
+        );
+
+        drop(spinner);
+        assert_eq!(headless.lines(), ["block 1", "synced", "$ list"]);
+        term.exit().await;
+        headless.settle().await?;
+
+        // widgets updated while a command is executing
+        let term = progress_terminal().await?;
+        let headless = term.headless().unwrap();
+        let script = crate::Script::parse("sync")?;
+        assert_eq!(
+            term.exec_script(&script).await?,
+            crate::script::EXIT_SUCCESS
+        );
+        assert_eq!(headless.lines(), ["block 1", "block 2", "synced"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,464 @@
+//!
+//! Session recording and replay in the [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/)
+//! format (compatible with `asciinema play` and the asciinema web player).
+//!
+//! Recording takes place in the [`Terminal`](crate::Terminal) (independently of
+//! the backend) and captures all output written using [`Terminal::write`](crate::Terminal::write)
+//! (including output of [`Terminal::writeln`](crate::Terminal::writeln), the prompt
+//! and line editing) as well as the input keys and terminal size changes,
+//! along with their timing. Keys entered while the terminal prompts for user
+//! input (see [`Terminal::ask`](crate::Terminal::ask)) are not recorded as
+//! they may be secret.
+//!
+//! ```ignore
+//! term.start_recording(Some("sync issue"));
+//! // ... user session ...
+//! if let Some(recording) = term.stop_recording() {
+//!     recording.save("~/session.cast").await?;
+//! }
+//!
+//! let recording = Recording::load("~/session.cast").await?;
+//! term.replay(&recording, 2.0).await?;
+//! ```
+//!
+
+use crate::error::Error;
+use crate::keys::Key;
+use crate::result::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use workflow_core::time::{unixtime_as_millis_u64, Instant};
+use workflow_store::fs;
+
+/// asciicast format version
+pub const ASCIICAST_VERSION: u32 = 2;
+/// Terminal width recorded when the backend does not report it
+pub const DEFAULT_WIDTH: usize = 80;
+/// Terminal height recorded when the backend does not report it
+pub const DEFAULT_HEIGHT: usize = 24;
+
+/// asciicast header (the first line of a recording)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    /// Unix timestamp (seconds) of the beginning of the recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// Maximum delay (seconds) between events during replay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_time_limit: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+}
+
+/// Recorded event type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// Data written to the terminal (`o`)
+    Output,
+    /// Input key sequence (`i`)
+    Input,
+    /// Terminal resize to `<cols>x<rows>` (`r`)
+    Resize,
+    /// Marker (`m`)
+    Marker,
+}
+
+impl EventKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EventKind::Output => "o",
+            EventKind::Input => "i",
+            EventKind::Resize => "r",
+            EventKind::Marker => "m",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "o" => Some(EventKind::Output),
+            "i" => Some(EventKind::Input),
+            "r" => Some(EventKind::Resize),
+            "m" => Some(EventKind::Marker),
+            _ => None,
+        }
+    }
+}
+
+/// Recorded event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Time (seconds) since the beginning of the recording
+    pub time: f64,
+    pub kind: EventKind,
+    pub data: String,
+}
+
+/// Session recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub header: Header,
+    pub events: Vec<Event>,
+}
+
+impl Recording {
+    /// Create an empty recording of a terminal of the supplied size
+    pub fn new(width: usize, height: usize) -> Self {
+        Recording {
+            header: Header {
+                version: ASCIICAST_VERSION,
+                width,
+                height,
+                timestamp: None,
+                idle_time_limit: None,
+                title: None,
+                env: None,
+            },
+            events: Vec::new(),
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.header.title = Some(title.to_string());
+        self
+    }
+
+    /// Limit delays between events during replay (seconds)
+    pub fn with_idle_time_limit(mut self, idle_time_limit: f64) -> Self {
+        self.header.idle_time_limit = Some(idle_time_limit);
+        self
+    }
+
+    pub fn push(&mut self, time: f64, kind: EventKind, data: &str) {
+        self.events.push(Event {
+            time,
+            kind,
+            data: data.to_string(),
+        });
+    }
+
+    /// Recording duration in seconds
+    pub fn duration(&self) -> f64 {
+        self.events.last().map(|event| event.time).unwrap_or(0.0)
+    }
+
+    /// Output events (the data written to the terminal)
+    pub fn output(&self) -> impl Iterator<Item = &Event> {
+        self.events
+            .iter()
+            .filter(|event| event.kind == EventKind::Output)
+    }
+
+    /// Parse a recording in the asciicast v2 format.
+    pub fn parse(text: &str) -> Result<Recording> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let (_, header) = lines
+            .next()
+            .ok_or_else(|| Error::Recording(1, "missing header".into()))?;
+        let header: Header =
+            serde_json::from_str(header).map_err(|err| Error::Recording(1, err.to_string()))?;
+        if header.version != ASCIICAST_VERSION {
+            return Err(Error::Recording(
+                1,
+                format!("unsupported asciicast version {}", header.version),
+            ));
+        }
+        if let Some(limit) = header
+            .idle_time_limit
+            .filter(|limit| limit.is_nan() || *limit < 0.0)
+        {
+            return Err(Error::Recording(
+                1,
+                format!("invalid idle time limit {limit}"),
+            ));
+        }
+
+        let mut events = Vec::new();
+        for (index, line) in lines {
+            let line_number = index + 1;
+            let (time, code, data): (f64, String, String) = serde_json::from_str(line)
+                .map_err(|err| Error::Recording(line_number, err.to_string()))?;
+            let kind = EventKind::from_code(&code).ok_or_else(|| {
+                Error::Recording(line_number, format!("unknown event type `{code}`"))
+            })?;
+            events.push(Event { time, kind, data });
+        }
+
+        Ok(Recording { header, events })
+    }
+
+    /// Load a recording from a file (a local storage key in the browser).
+    pub async fn load(filename: &str) -> Result<Recording> {
+        let filename = fs::resolve_path(filename)?;
+        Self::parse(&fs::read_to_string(&filename).await?)
+    }
+
+    /// Save the recording to a file (a local storage key in the browser).
+    pub async fn save(&self, filename: &str) -> Result<()> {
+        let filename = fs::resolve_path(filename)?;
+        fs::write_string(&filename, &self.to_string()).await?;
+        Ok(())
+    }
+
+    /// Delays before each event during replay at the supplied speed,
+    /// limited by the idle time limit of the recording. Returns
+    /// [`Error::Recording`] (with the line of the event in the asciicast
+    /// file) if a delay is not representable as a [`Duration`].
+    pub(crate) fn delays(&self, speed: f64) -> Result<Vec<Duration>> {
+        let mut previous = 0.0;
+        self.events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let mut delay = (event.time - previous).max(0.0);
+                previous = event.time;
+                if let Some(limit) = self.header.idle_time_limit {
+                    delay = delay.min(limit);
+                }
+                Duration::try_from_secs_f64(delay / speed).map_err(|err| {
+                    Error::Recording(index + 2, format!("invalid delay {delay}: {err}"))
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = serde_json::to_string(&self.header).map_err(|_| fmt::Error)?;
+        writeln!(f, "{header}")?;
+        for event in self.events.iter() {
+            let event = serde_json::to_string(&(
+                (event.time * 1e6).round() / 1e6,
+                event.kind.code(),
+                &event.data,
+            ))
+            .map_err(|_| fmt::Error)?;
+            writeln!(f, "{event}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Recording in progress.
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    start: Instant,
+    size: (usize, usize),
+    recording: Recording,
+}
+
+impl Recorder {
+    pub fn new(width: usize, height: usize, title: Option<&str>) -> Self {
+        let mut recording = Recording::new(width, height);
+        recording.header.timestamp = Some(unixtime_as_millis_u64() / 1000);
+        recording.header.title = title.map(String::from);
+        Recorder {
+            start: Instant::now(),
+            size: (width, height),
+            recording,
+        }
+    }
+
+    fn push(&mut self, kind: EventKind, data: &str) {
+        let time = self.start.elapsed().as_secs_f64();
+        self.recording.push(time, kind, data);
+    }
+
+    pub fn output(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.push(EventKind::Output, text);
+        }
+    }
+
+    /// Record a resize event if the terminal size has changed.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if self.size != (width, height) {
+            self.size = (width, height);
+            self.push(EventKind::Resize, &format!("{width}x{height}"));
+        }
+    }
+
+    pub fn input(&mut self, key: &Key) {
+        if let Some(sequence) = input_sequence(key) {
+            self.push(EventKind::Input, &sequence);
+        }
+    }
+
+    pub fn finish(self) -> Recording {
+        self.recording
+    }
+}
+
+/// Terminal input sequence (as produced by xterm) of a key.
+fn input_sequence(key: &Key) -> Option<String> {
+    let sequence = match key {
+        Key::Enter => "\r",
+        Key::Backspace => "\x7f",
+        Key::ArrowLeft => "\x1b[D",
+        Key::ArrowRight => "\x1b[C",
+        Key::ArrowUp => "\x1b[A",
+        Key::ArrowDown => "\x1b[B",
+        Key::Home => "\x1b[H",
+        Key::End => "\x1b[F",
+        Key::PageUp => "\x1b[5~",
+        Key::PageDown => "\x1b[6~",
+        Key::Tab => "\t",
+        Key::BackTab => "\x1b[Z",
+        Key::Delete => "\x1b[3~",
+        Key::Insert => "\x1b[2~",
+        Key::Esc => "\x1b",
+        Key::Char(c) => return Some(c.to_string()),
+        Key::Alt(c) => return Some(format!("\x1b{c}")),
+        Key::Ctrl(c) => {
+            let c = c.to_ascii_uppercase();
+            return ('@'..='_')
+                .contains(&c)
+                .then_some(((c as u8) & 0x1f) as char)
+                .map(String::from);
+        }
+    };
+    Some(sequence.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::headless::tests::test_terminal;
+    use crate::terminal::Options;
+
+    fn input(recording: &Recording) -> String {
+        recording
+            .events
+            .iter()
+            .filter(|event| event.kind == EventKind::Input)
+            .map(|event| event.data.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_recording() -> Result<()> {
+        let mut recording = Recording::new(100, 30).with_title("demo");
+        recording.push(0.25, EventKind::Output, "$ ");
+        recording.push(1.0, EventKind::Input, "l");
+        recording.push(1.0000004, EventKind::Output, "l");
+        recording.push(9.5, EventKind::Output, "\u{1b}[2K\"done\"\n\r");
+
+        let text = recording.to_string();
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                r#"{"version":2,"width":100,"height":30,"title":"demo"}"#,
+                r#"[0.25,"o","$ "]"#,
+                r#"[1.0,"i","l"]"#,
+                r#"[1.0,"o","l"]"#,
+                r#"[9.5,"o","\u001b[2K\"done\"\n\r"]"#,
+            ]
+        );
+
+        let parsed = Recording::parse(&text)?;
+        assert_eq!(parsed.header, recording.header);
+        assert_eq!(
+            parsed.output().map(|e| e.data.as_str()).collect::<String>(),
+            "$ l\u{1b}[2K\"done\"\n\r"
+        );
+        assert_eq!(parsed.duration(), 9.5);
+
+        let recording = parsed.with_idle_time_limit(2.0);
+        assert_eq!(
+            recording.delays(2.0)?,
+            [0.125, 0.375, 0.0, 1.0].map(Duration::from_secs_f64)
+        );
+        let mut recording = recording.with_idle_time_limit(-1.0);
+        assert!(matches!(recording.delays(1.0), Err(Error::Recording(2, _))));
+        recording.header.idle_time_limit = None;
+        recording.push(f64::MAX, EventKind::Output, "");
+        assert!(matches!(recording.delays(1.0), Err(Error::Recording(6, _))));
+
+        assert!(matches!(
+            Recording::parse("{\"version\":1,\"width\":80,\"height\":24}"),
+            Err(Error::Recording(1, _))
+        ));
+        assert!(matches!(
+            Recording::parse("{\"version\":2,\"width\":80,\"height\":24,\"idle_time_limit\":-1}"),
+            Err(Error::Recording(1, _))
+        ));
+        assert!(matches!(
+            Recording::parse("{\"version\":2,\"width\":80,\"height\":24}\n[1.0,\"x\",\"\"]"),
+            Err(Error::Recording(2, _))
+        ));
+
+        assert_eq!(input_sequence(&Key::Ctrl('c')).as_deref(), Some("\x03"));
+        assert_eq!(input_sequence(&Key::ArrowUp).as_deref(), Some("\x1b[A"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_session() -> Result<()> {
+        let (term, headless) = test_terminal(Options::new(), true).await?;
+
+        headless.settle().await?;
+        headless.clear();
+        term.start_recording(Some("test"));
+        headless.line("bindings | grep Binding").await?;
+        headless.set_cols(100);
+        headless.set_rows(30);
+        term.writeln("resized");
+        let recording = term.stop_recording().unwrap();
+        assert!(!term.is_recording());
+        assert_eq!(recording.header.title.as_deref(), Some("test"));
+        assert_eq!(input(&recording), "bindings | grep Binding\r");
+        assert_eq!(
+            recording
+                .events
+                .iter()
+                .filter(|event| event.kind == EventKind::Resize)
+                .map(|event| event.data.as_str())
+                .collect::<Vec<_>>(),
+            ["100x30"]
+        );
+        let recording = Recording::parse(&recording.to_string())?;
+
+        let (replay, replay_headless) = test_terminal(Options::new(), false).await?;
+        assert!(replay.replay(&recording, 0.0).await.is_err());
+        replay.replay(&recording, 100.0).await?;
+        let lines = headless.lines();
+        assert_eq!(lines[1], "Key        Binding");
+        assert_eq!(replay_headless.lines(), lines);
+
+        term.exit().await;
+        headless.settle().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_user_input() -> Result<()> {
+        let (term, headless) = test_terminal(Options::new(), true).await?;
+
+        headless.settle().await?;
+        term.start_recording(None);
+        headless.line("greet").await?;
+        headless.line("alice").await?;
+        headless.line("1234").await?;
+        headless.line("1").await?;
+        headless.key(Key::Char('x')).await?;
+        let recording = term.stop_recording().unwrap();
+        assert_eq!(input(&recording), "greet\r");
+        assert!(!recording.to_string().contains("1234"));
+        assert!(recording.to_string().contains("hello alice (4) green"));
+
+        term.exit().await;
+        headless.settle().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//!
+//! Client attaching a local [`Terminal`] to a remote session.
+//!
+
+use super::{Frame, DEFAULT_HANDSHAKE_TIMEOUT};
+use crate::error::Error;
+use crate::keys::Key;
+use crate::result::Result;
+use crate::terminal::Terminal;
+use futures::{select, FutureExt, StreamExt};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use workflow_core::channel::Channel;
+use workflow_core::task::{interval, sleep, spawn};
+use workflow_websocket::client::{ConnectOptions, Message, WebSocket};
+
+/// Key detaching the local terminal from the remote session
+pub const DETACH_KEY: Key = Key::Ctrl(']');
+/// Interval of checking the local terminal size for changes
+pub const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Local terminal attached to a remote session served by
+/// [`TerminalServer`](super::TerminalServer). While attached, keys
+/// received by the local terminal are forwarded to the remote session
+/// (except [`DETACH_KEY`]) and the session output is written to the
+/// local terminal.
+pub struct RemoteClient {
+    term: Arc<Terminal>,
+    ws: WebSocket,
+    keys: Channel<Key>,
+    attached: AtomicBool,
+    size: Mutex<(usize, usize)>,
+}
+
+impl RemoteClient {
+    /// Connect to the server at `url` (`wss://`, or `ws://` for local
+    /// servers only, as the token is sent in plain text), authenticate
+    /// using `token` and attach the local terminal to a new remote session.
+    /// Fails if the server does not respond to the handshake within
+    /// [`DEFAULT_HANDSHAKE_TIMEOUT`].
+    pub async fn attach(term: &Arc<Terminal>, url: &str, token: &str) -> Result<Arc<Self>> {
+        let ws = WebSocket::new(Some(url), None)?;
+        ws.connect(ConnectOptions::blocking_fallback()).await?;
+
+        let size = Self::terminal_size(term);
+        let hello = Frame::Hello {
+            token: token.to_string(),
+            cols: size.0,
+            rows: size.1,
+        };
+        ws.post(hello.encode().into()).await?;
+        let welcome = select! {
+            result = Self::handshake(&ws).fuse() => result,
+            _ = sleep(DEFAULT_HANDSHAKE_TIMEOUT).fuse() => {
+                Err(Error::Remote("handshake timeout".into()))
+            }
+        };
+        if let Err(err) = welcome {
+            ws.disconnect().await.ok();
+            return Err(err);
+        }
+
+        let client = Arc::new(RemoteClient {
+            term: term.clone(),
+            ws,
+            keys: Channel::unbounded(),
+            attached: AtomicBool::new(true),
+            size: Mutex::new(size),
+        });
+        term.crlf();
+        term.set_remote(Some(client.keys.sender.clone()));
+        client.start();
+        Ok(client)
+    }
+
+    /// Wait for the server response to the handshake message.
+    async fn handshake(ws: &WebSocket) -> Result<()> {
+        loop {
+            match ws.recv().await? {
+                Message::Text(text) => {
+                    return match Frame::decode(&text)? {
+                        Frame::Welcome => Ok(()),
+                        Frame::Denied(reason) => Err(Error::RemoteDenied(reason)),
+                        frame => Err(Error::Remote(format!(
+                            "unexpected handshake response {frame:?}"
+                        ))),
+                    }
+                }
+                Message::Close => return Err(Error::Remote("connection closed".into())),
+                _ => {}
+            }
+        }
+    }
+
+    fn terminal_size(term: &Terminal) -> (usize, usize) {
+        (term.cols().unwrap_or(0), term.rows().unwrap_or(0))
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.attached.load(Ordering::SeqCst)
+    }
+
+    fn start(self: &Arc<Self>) {
+        let this = self.clone();
+        spawn(async move {
+            let mut resize = interval(RESIZE_POLL_INTERVAL);
+            while this.is_attached() {
+                select! {
+                    key = this.keys.receiver.recv().fuse() => match key {
+                        Ok(DETACH_KEY) | Err(_) => this.detach().await,
+                        Ok(key) => this.post(Frame::Key(key)).await,
+                    },
+                    _ = resize.next().fuse() => this.check_size().await,
+                }
+            }
+        });
+
+        let this = self.clone();
+        spawn(async move {
+            while this.is_attached() {
+                match this.ws.recv().await {
+                    Ok(Message::Text(text)) => match Frame::decode(&text) {
+                        Ok(Frame::Output(text)) => this.term.write(text),
+                        _ => this.detach().await,
+                    },
+                    Ok(Message::Open) | Ok(Message::Binary(_)) => {}
+                    Ok(Message::Close) | Err(_) => this.detach().await,
+                }
+            }
+        });
+    }
+
+    async fn post(&self, frame: Frame) {
+        if self.ws.post(frame.encode().into()).await.is_err() {
+            self.detach().await;
+        }
+    }
+
+    async fn check_size(&self) {
+        let size = Self::terminal_size(&self.term);
+        let changed = {
+            let mut current = self.size.lock().unwrap();
+            std::mem::replace(&mut *current, size) != size
+        };
+        if changed {
+            let (cols, rows) = size;
+            self.post(Frame::Resize { cols, rows }).await;
+        }
+    }
+
+    /// Detach the local terminal from the remote session,
+    /// closing the connection and restoring the local prompt.
+    pub async fn detach(&self) {
+        if self.attached.swap(false, Ordering::SeqCst) {
+            self.term.set_remote(None);
+            self.ws.disconnect().await.ok();
+            self.term.crlf();
+            self.term.prompt();
+        }
+    }
+}
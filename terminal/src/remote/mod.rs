@@ -0,0 +1,274 @@
+//!
+//! Remote terminal sessions over WebSocket (available
+//! with the `remote` feature).
+//!
+//! [`TerminalServer`] (native only) is a [`workflow_websocket`] server
+//! handler that serves a [`Cli`](crate::Cli) to remote clients: each
+//! connection receives its own [`Terminal`](crate::Terminal) session
+//! (using the [`Headless`](crate::Headless) backend) whose input is
+//! streamed from the client and whose output is streamed back to it.
+//! [`RemoteClient`] attaches a local terminal (xterm.js in the browser
+//! or the native console) to a remote session: keys are forwarded to
+//! the session, the session output is displayed and terminal size
+//! changes are propagated.
+//!
+//! Clients authenticate using a token supplied in the handshake, which
+//! is verified by the authenticator of the [`TerminalServer`] (tokens
+//! should be compared using [`token_eq`]). The token and the session are
+//! sent in plain text over `ws://`, as such servers reachable from other
+//! hosts must be accessed using `wss://` (e.g. behind a TLS proxy).
+//!
+//! ```ignore
+//! // daemon
+//! let server = WebSocketServer::new(
+//!     Arc::new(TerminalServer::new(
+//!         Arc::new(|| DaemonCli::new() as Arc<dyn Cli>),
+//!         Arc::new(|_peer, token| token_eq(token, "secret")),
+//!     )),
+//!     None,
+//! );
+//! let listener = server.bind("127.0.0.1:7878").await?;
+//! server.listen(listener, None).await?;
+//!
+//! // client (typically executed by a command handler)
+//! RemoteClient::attach(&term, "wss://daemon.example.com", "secret").await?;
+//! ```
+//!
+//! Messages are exchanged as WebSocket text messages encoding a [`Frame`].
+//!
+
+use crate::error::Error;
+use crate::keys::Key;
+use crate::result::Result;
+use crate::table::truncate;
+use cfg_if::cfg_if;
+use std::time::Duration;
+
+mod client;
+pub use client::{RemoteClient, DETACH_KEY, RESIZE_POLL_INTERVAL};
+
+cfg_if! {
+    if #[cfg(not(target_arch = "wasm32"))] {
+        mod server;
+        pub use server::{token_eq, AuthenticatorFn, CliFactoryFn, Session, TerminalServer};
+    }
+}
+
+/// Time allowed for the handshake (the client handshake message
+/// on the server and the server response on the client)
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Remote session protocol message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// Client handshake: authentication token and terminal size
+    Hello {
+        token: String,
+        cols: usize,
+        rows: usize,
+    },
+    /// Handshake accepted
+    Welcome,
+    /// Handshake rejected
+    Denied(String),
+    /// Key pressed on the client
+    Key(Key),
+    /// Client terminal size change
+    Resize { cols: usize, rows: usize },
+    /// Session output (including ANSI sequences)
+    Output(String),
+    /// Session has exited
+    Exit,
+}
+
+impl Frame {
+    pub fn encode(&self) -> String {
+        match self {
+            Frame::Hello { token, cols, rows } => format!("hello {cols} {rows} {token}"),
+            Frame::Welcome => "welcome".to_string(),
+            Frame::Denied(reason) => format!("denied {reason}"),
+            Frame::Key(key) => format!("key {key}"),
+            Frame::Resize { cols, rows } => format!("resize {cols} {rows}"),
+            Frame::Output(text) => format!("out {text}"),
+            Frame::Exit => "exit".to_string(),
+        }
+    }
+
+    pub fn decode(text: &str) -> Result<Frame> {
+        let (kind, args) = text.split_once(' ').unwrap_or((text, ""));
+        // the message arguments are not reported as they may contain the token
+        let malformed = || Error::Remote(format!("malformed `{}` message", truncate(kind, 16)));
+        let size = |args: &str| -> Result<(usize, usize, String)> {
+            let mut parts = args.splitn(3, ' ');
+            let cols = parts.next().and_then(|cols| cols.parse().ok());
+            let rows = parts.next().and_then(|rows| rows.parse().ok());
+            match (cols, rows) {
+                (Some(cols), Some(rows)) => Ok((cols, rows, parts.next().unwrap_or("").into())),
+                _ => Err(malformed()),
+            }
+        };
+
+        let frame = match kind {
+            "hello" => {
+                let (cols, rows, token) = size(args)?;
+                Frame::Hello { token, cols, rows }
+            }
+            "welcome" => Frame::Welcome,
+            "denied" => Frame::Denied(args.to_string()),
+            "key" => Frame::Key(args.parse()?),
+            "resize" => {
+                let (cols, rows, _) = size(args)?;
+                Frame::Resize { cols, rows }
+            }
+            "out" => Frame::Output(args.to_string()),
+            "exit" => Frame::Exit,
+            _ => return Err(malformed()),
+        };
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{BindingsHandler, Cli, Context, HandlerCli};
+    use crate::terminal::{Options, Terminal};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use workflow_core::task::sleep;
+    use workflow_websocket::server::WebSocketServer;
+
+    struct DaemonCli {
+        term: Mutex<Option<Arc<Terminal>>>,
+        handlers: HandlerCli,
+    }
+
+    impl Context for DaemonCli {
+        fn term(&self) -> Arc<Terminal> {
+            self.term.lock().unwrap().clone().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Cli for DaemonCli {
+        fn init(self: Arc<Self>, term: &Arc<Terminal>) -> Result<()> {
+            *self.term.lock().unwrap() = Some(term.clone());
+            self.handlers.register(&self, BindingsHandler);
+            Ok(())
+        }
+        async fn digest(self: Arc<Self>, _term: Arc<Terminal>, cmd: String) -> Result<()> {
+            self.handlers.execute(&self, &cmd).await
+        }
+        async fn complete(
+            self: Arc<Self>,
+            _term: Arc<Terminal>,
+            _cmd: String,
+        ) -> Result<Option<Vec<String>>> {
+            Ok(None)
+        }
+        fn prompt(&self) -> Option<String> {
+            None
+        }
+    }
+
+    fn daemon_cli() -> Arc<dyn Cli> {
+        Arc::new(DaemonCli {
+            term: Mutex::new(None),
+            handlers: HandlerCli::new(),
+        })
+    }
+
+    async fn wait_for(f: impl Fn() -> bool) {
+        for _ in 0..2000 {
+            if f() {
+                return;
+            }
+            sleep(Duration::from_millis(1)).await;
+        }
+        panic!("timeout");
+    }
+
+    #[test]
+    fn test_frames() -> Result<()> {
+        for frame in [
+            Frame::Hello {
+                token: "secret token".into(),
+                cols: 80,
+                rows: 24,
+            },
+            Frame::Key(Key::Char(' ')),
+            Frame::Key(Key::Ctrl(']')),
+            Frame::Resize { cols: 120, rows: 0 },
+            Frame::Output("\x1b[2K\r$ ls\n\r".into()),
+            Frame::Exit,
+        ] {
+            assert_eq!(Frame::decode(&frame.encode())?, frame);
+        }
+        assert!(Frame::decode("resize 80").is_err());
+        assert!(Frame::decode("hello").is_err());
+        assert_eq!(
+            Frame::decode("hello x 24 secret").unwrap_err().to_string(),
+            "remote session error: malformed `hello` message"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remote_session() -> Result<()> {
+        assert!(token_eq("secret", "secret"));
+        assert!(!token_eq("secreT", "secret"));
+        assert!(!token_eq("secret!", "secret"));
+
+        let server = WebSocketServer::new(
+            Arc::new(
+                TerminalServer::new(
+                    Arc::new(daemon_cli),
+                    Arc::new(|_peer, token| token_eq(token, "secret")),
+                )
+                .with_prompt("remote$ "),
+            ),
+            None,
+        );
+        let listener = server.bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr()?);
+        let listening = server.clone();
+        tokio::spawn(async move { listening.listen(listener, None).await });
+
+        let term = Arc::new(Terminal::try_new_headless(
+            daemon_cli(),
+            Options::new().with_prompt("local$ "),
+        )?);
+        term.init().await?;
+        let headless = term.headless().unwrap();
+        let runner = term.clone();
+        tokio::spawn(async move { runner.run().await });
+
+        assert!(matches!(
+            RemoteClient::attach(&term, &url, "guess").await,
+            Err(Error::RemoteDenied(_))
+        ));
+
+        let client = RemoteClient::attach(&term, &url, "secret").await?;
+        assert!(term.is_remote());
+        wait_for(|| {
+            headless
+                .lines()
+                .last()
+                .is_some_and(|line| line == "remote$")
+        })
+        .await;
+
+        headless.line("bindings | grep ctrl-y").await?;
+        wait_for(|| headless.text().contains("ctrl-y     yank")).await;
+
+        headless.key(DETACH_KEY).await?;
+        wait_for(|| !client.is_attached()).await;
+        assert!(!term.is_remote());
+        wait_for(|| headless.lines().last().is_some_and(|line| line == "local$")).await;
+
+        term.exit().await;
+        server.stop_and_join().await.unwrap();
+        Ok(())
+    }
+}
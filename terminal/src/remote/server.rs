@@ -0,0 +1,177 @@
+//!
+//! WebSocket server handler serving [`Terminal`] sessions (native only).
+//!
+
+use super::{Frame, DEFAULT_HANDSHAKE_TIMEOUT};
+use crate::cli::Cli;
+use crate::terminal::{Headless, Options, Terminal};
+use async_trait::async_trait;
+use futures::{select, FutureExt, SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use workflow_core::task::{sleep, spawn};
+use workflow_log::log_error;
+use workflow_websocket::server::{
+    Error, Message, Result, WebSocketHandler, WebSocketReceiver, WebSocketSender, WebSocketSink,
+};
+
+/// Creates a [`Cli`] instance for each remote session
+pub type CliFactoryFn = Arc<dyn Fn() -> Arc<dyn Cli> + Send + Sync>;
+/// Verifies the authentication token supplied by a client
+pub type AuthenticatorFn = Arc<dyn Fn(&SocketAddr, &str) -> bool + Send + Sync>;
+
+/// Compare the supplied token with the expected token in constant time
+/// (with respect to the token contents), for use by authenticators.
+pub fn token_eq(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Remote terminal session (WebSocket connection context)
+pub struct Session {
+    pub peer: SocketAddr,
+    pub term: Arc<Terminal>,
+    headless: Arc<Headless>,
+}
+
+/// [`WebSocketHandler`] serving a [`Cli`] to remote clients
+/// (see [`RemoteClient`](super::RemoteClient)).
+pub struct TerminalServer {
+    cli: CliFactoryFn,
+    authenticator: AuthenticatorFn,
+    prompt: Option<String>,
+    handshake_timeout: Duration,
+}
+
+impl TerminalServer {
+    pub fn new(cli: CliFactoryFn, authenticator: AuthenticatorFn) -> Self {
+        TerminalServer {
+            cli,
+            authenticator,
+            prompt: None,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+        }
+    }
+
+    /// Set the prompt of remote sessions
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = Some(prompt.to_string());
+        self
+    }
+
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    fn options(&self) -> Options {
+        match &self.prompt {
+            Some(prompt) => Options::new().with_prompt(prompt),
+            None => Options::new(),
+        }
+    }
+}
+
+fn send(sink: &WebSocketSink, frame: Frame) {
+    sink.send(Message::Text(frame.encode())).ok();
+}
+
+#[async_trait]
+impl WebSocketHandler for TerminalServer {
+    type Context = Arc<Session>;
+
+    async fn handshake(
+        self: &Arc<Self>,
+        peer: &SocketAddr,
+        sender: &mut WebSocketSender,
+        receiver: &mut WebSocketReceiver,
+        sink: &WebSocketSink,
+    ) -> Result<Self::Context> {
+        let msg = select! {
+            msg = receiver.next().fuse() => msg,
+            _ = sleep(self.handshake_timeout).fuse() => return Err(Error::ConnectionTimeout),
+        };
+        let (token, cols, rows) = match msg {
+            Some(Ok(Message::Text(text))) => match Frame::decode(&text) {
+                Ok(Frame::Hello { token, cols, rows }) => (token, cols, rows),
+                _ => return Err(Error::MalformedHandshake),
+            },
+            _ => return Err(Error::MalformedHandshake),
+        };
+
+        if !(self.authenticator)(peer, &token) {
+            let reason = "authentication failed".to_string();
+            sender
+                .send(Message::Text(Frame::Denied(reason.clone()).encode()))
+                .await?;
+            return Err(Error::NegotiationFailureWithReason(reason));
+        }
+
+        let term = Terminal::try_new_headless((self.cli)(), self.options())
+            .map(Arc::new)
+            .map_err(|err| Error::Other(err.to_string()))?;
+        let headless = term.headless().unwrap();
+        headless.set_cols(cols);
+        headless.set_rows(rows);
+        let output = sink.clone();
+        headless.set_output_handler(Some(Arc::new(move |text: &str| {
+            send(&output, Frame::Output(text.to_string()));
+        })));
+
+        sender.send(Message::Text(Frame::Welcome.encode())).await?;
+        term.init()
+            .await
+            .map_err(|err| Error::Other(err.to_string()))?;
+        term.refresh_prompt();
+
+        let runner = term.clone();
+        let sink = sink.clone();
+        spawn(async move {
+            if let Err(err) = runner.run().await {
+                log_error!("Remote terminal session error: {err}");
+            }
+            send(&sink, Frame::Exit);
+            sink.send(Message::Close(None)).ok();
+        });
+
+        Ok(Arc::new(Session {
+            peer: *peer,
+            term,
+            headless,
+        }))
+    }
+
+    async fn message(
+        self: &Arc<Self>,
+        session: &Self::Context,
+        msg: Message,
+        _sink: &WebSocketSink,
+    ) -> Result<()> {
+        match msg {
+            Message::Text(text) => match Frame::decode(&text) {
+                Ok(Frame::Key(key)) => session.headless.send_key(key),
+                Ok(Frame::Resize { cols, rows }) => {
+                    session.headless.set_cols(cols);
+                    session.headless.set_rows(rows);
+                    session.term.refresh_prompt();
+                }
+                _ => return Err(Error::MalformedMessage),
+            },
+            Message::Close(_) => {}
+            _ => return Err(Error::MalformedMessage),
+        }
+        Ok(())
+    }
+
+    async fn disconnect(self: &Arc<Self>, session: Self::Context, _result: Result<()>) {
+        if !session.term.terminate.load(Ordering::SeqCst) {
+            session.term.exit().await;
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//!
+//! Non-interactive execution of command sequences (scripts, batch mode).
+//!
+//! A script contains one command per line; multiple commands can be
+//! separated by `;` (outside of quotes). Lines starting with `#` are comments. Scripts
+//! support the following statements in addition to commands:
+//!
+//! - `NAME=value` - variable assignment; variables are substituted
+//!   in commands and values using `$NAME` or `${NAME}` (`\$` produces
+//!   a literal `$`)
+//! - `set -e` / `set +e` - stop (or continue) execution on the first
+//!   failed command
+//! - `set -x` / `set +x` - echo (or not) commands before execution
+//!   (options can be combined, e.g. `set -ex`)
+//!
+//! Scripts are executed using [`Terminal::exec_script`](crate::Terminal::exec_script),
+//! which returns a process exit code and does not enter the terminal raw mode:
+//!
+//! ```ignore
+//! let args = std::env::args().collect::<Vec<_>>();
+//! if let Some(script) = Script::from_args(&args).await? {
+//!     let code = term.exec_script(&script).await?;
+//!     std::process::exit(code);
+//! }
+//! term.run().await?;
+//! ```
+//!
+
+use crate::error::Error;
+use crate::pipeline::split_unquoted;
+use crate::result::Result;
+use std::collections::HashMap;
+use workflow_store::fs;
+
+/// Exit code of a successfully executed script
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit code of a script containing a failed command
+pub const EXIT_FAILURE: i32 = 1;
+
+/// Script statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    /// Command passed to the [`Cli`](crate::Cli)
+    Command(String),
+    /// `NAME=value` variable assignment
+    Assign { name: String, value: String },
+    /// `set -<option>` (enabled) or `set +<option>` (disabled)
+    Set { option: char, enabled: bool },
+}
+
+/// Parsed script along with its variables.
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    statements: Vec<(usize, Statement)>,
+    variables: HashMap<String, String>,
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Script {
+    /// Parse script text.
+    pub fn parse(text: &str) -> Result<Script> {
+        let mut statements = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+
+            for text in split_unquoted(line, ';')
+                .into_iter()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+            {
+                if let Some(options) = text
+                    .strip_prefix("set ")
+                    .map(str::trim)
+                    .filter(|options| options.starts_with(['-', '+']))
+                {
+                    let enabled = options.starts_with('-');
+                    for option in options[1..].chars() {
+                        if !matches!(option, 'e' | 'x') {
+                            return Err(Error::Script(
+                                line_number,
+                                format!("unsupported option `{options}`"),
+                            ));
+                        }
+                        statements.push((line_number, Statement::Set { option, enabled }));
+                    }
+                    continue;
+                }
+
+                let statement = match text.split_once('=') {
+                    Some((name, value)) if is_identifier(name) => Statement::Assign {
+                        name: name.to_string(),
+                        value: value.trim().to_string(),
+                    },
+                    _ => Statement::Command(text.to_string()),
+                };
+                statements.push((line_number, statement));
+            }
+        }
+
+        Ok(Script {
+            statements,
+            variables: HashMap::new(),
+        })
+    }
+
+    /// Load a script from a file (a local storage key in the browser).
+    pub async fn load(filename: &str) -> Result<Script> {
+        let filename = fs::resolve_path(filename)?;
+        Self::parse(&fs::read_to_string(&filename).await?)
+    }
+
+    /// Read a script from the standard input.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_stdin() -> Result<Script> {
+        let text = std::io::read_to_string(std::io::stdin())?;
+        Self::parse(&text)
+    }
+
+    /// Create a script from process arguments:
+    /// - `--exec "cmd1; cmd2"` executes the supplied commands
+    /// - `--script <file>` executes the script file (`-` reads the standard
+    ///   input in the native environment)
+    ///
+    /// Returns `None` if the arguments do not request non-interactive execution.
+    pub async fn from_args(args: &[String]) -> Result<Option<Script>> {
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--exec" => {
+                    let commands = args.next().ok_or_else(|| {
+                        Error::InvalidArguments("--exec requires commands".into())
+                    })?;
+                    return Ok(Some(Self::parse(commands)?));
+                }
+                "--script" => {
+                    let filename = args.next().ok_or_else(|| {
+                        Error::InvalidArguments("--script requires a file".into())
+                    })?;
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if filename == "-" {
+                        return Ok(Some(Self::from_stdin()?));
+                    }
+                    return Ok(Some(Self::load(filename).await?));
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Define a variable (such as a script argument).
+    pub fn with_var(mut self, name: &str, value: &str) -> Self {
+        self.variables.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn statements(&self) -> impl Iterator<Item = &Statement> {
+        self.statements.iter().map(|(_, statement)| statement)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Create an executor tracking variables and options of a script run.
+    pub(crate) fn run(&self) -> Run<'_> {
+        Run {
+            script: self,
+            index: 0,
+            variables: self.variables.clone(),
+            errexit: false,
+            xtrace: false,
+        }
+    }
+}
+
+/// Substitute `$NAME` and `${NAME}` references using the supplied variables.
+pub fn substitute(text: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'$') => {
+                result.push('$');
+                chars.next();
+            }
+            '$' => {
+                let name = if chars.next_if_eq(&'{').is_some() {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break name,
+                            Some(c) => name.push(c),
+                            None => return Err(Error::Custom(format!("unterminated `${{{name}`"))),
+                        }
+                    }
+                } else {
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                        name.push(c);
+                    }
+                    name
+                };
+
+                if name.is_empty() {
+                    result.push('$');
+                } else {
+                    let value = variables.get(&name).ok_or(Error::UndefinedVariable(name))?;
+                    result.push_str(value);
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    Ok(result)
+}
+
+/// State of a script run: applies assignments and options and
+/// yields commands (with variables substituted) to execute.
+pub(crate) struct Run<'script> {
+    script: &'script Script,
+    index: usize,
+    variables: HashMap<String, String>,
+    /// stop on the first failed command (`set -e`)
+    pub errexit: bool,
+    /// echo commands (`set -x`)
+    pub xtrace: bool,
+}
+
+impl Run<'_> {
+    /// Returns the next command to execute along with its
+    /// line number, or an error if the substitution fails.
+    pub fn next_command(&mut self) -> Option<(usize, Result<String>)> {
+        while let Some((line, statement)) = self.script.statements.get(self.index) {
+            self.index += 1;
+            match statement {
+                Statement::Command(command) => {
+                    return Some((*line, substitute(command, &self.variables)));
+                }
+                Statement::Assign { name, value } => match substitute(value, &self.variables) {
+                    Ok(value) => {
+                        self.variables.insert(name.clone(), value);
+                    }
+                    Err(err) => return Some((*line, Err(err))),
+                },
+                Statement::Set { option, enabled } => match option {
+                    'e' => self.errexit = *enabled,
+                    'x' => self.xtrace = *enabled,
+                    _ => {}
+                },
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::headless::tests::test_terminal;
+    use crate::terminal::Options;
+
+    #[test]
+    fn test_script() -> Result<()> {
+        let script = Script::parse(
+            r#"
+            # provisioning
+            set -e
+            NET=testnet; PEER=${NET}-1
+            connect $PEER ; list \$HOME
+            echo $UNDEFINED; echo "a;b" 'c;d'
+        "#,
+        )?
+        .with_var("USER", "alice");
+
+        assert_eq!(
+            script.statements().take(2).collect::<Vec<_>>(),
+            [
+                &Statement::Set {
+                    option: 'e',
+                    enabled: true
+                },
+                &Statement::Assign {
+                    name: "NET".into(),
+                    value: "testnet".into()
+                }
+            ]
+        );
+
+        let mut run = script.run();
+        let (line, command) = run.next_command().unwrap();
+        assert_eq!((line, command?), (5, "connect testnet-1".to_string()));
+        assert!(run.errexit);
+        assert_eq!(run.next_command().unwrap().1?, "list $HOME");
+        assert!(matches!(
+            run.next_command(),
+            Some((6, Err(Error::UndefinedVariable(name)))) if name == "UNDEFINED"
+        ));
+        assert_eq!(run.next_command().unwrap().1?, r#"echo "a;b" 'c;d'"#);
+        assert!(run.next_command().is_none());
+
+        assert!(matches!(
+            Script::parse("ok\nset -q"),
+            Err(Error::Script(2, _))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exec_script() -> Result<()> {
+        let (term, headless) = test_terminal(Options::new(), false).await?;
+
+        let script = Script::parse("CMD=unknown\n$CMD; set -ex\nhistory\n$CMD\nhistory")?;
+        assert_eq!(term.exec_script(&script).await?, EXIT_FAILURE);
+        assert_eq!(
+            headless.lines(),
+            [
+                "line 2: command not found: unknown",
+                "+ history",
+                "+ unknown",
+                "line 4: command not found: unknown"
+            ]
+        );
+
+        // user input is not available
+        headless.clear();
+        let script = Script::parse("greet")?;
+        assert_eq!(term.exec_script(&script).await?, EXIT_FAILURE);
+        assert_eq!(
+            headless.lines(),
+            ["line 1: user input is not available when executing a script"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,306 @@
+//!
+//! Table renderer with column alignment and width fitting.
+//!
+//! Column widths are derived from the cell contents. If the table does not
+//! fit the available width (typically [`Terminal::cols`](crate::Terminal::cols)),
+//! the widest columns are narrowed and the overflowing cells are truncated
+//! with an ellipsis.
+//!
+//! ```ignore
+//! let table = Table::new()
+//!     .with_column(Column::new("Peer"))
+//!     .with_column(Column::new("Latency").with_align(Align::Right))
+//!     .with_row(["10.0.0.1:16110", "12 ms"])
+//!     .with_row(["10.0.0.2:16110", "140 ms"]);
+//! term.table(&table);
+//! ```
+//!
+
+use pad::{Alignment, PadStr};
+use textwrap::core::display_width;
+
+/// Minimum width a column is narrowed to when fitting the table
+const MIN_COLUMN_WIDTH: usize = 3;
+const ELLIPSIS: char = '…';
+
+/// Cell alignment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+impl From<Align> for Alignment {
+    fn from(align: Align) -> Self {
+        match align {
+            Align::Left => Alignment::Left,
+            Align::Right => Alignment::Right,
+            Align::Center => Alignment::Middle,
+        }
+    }
+}
+
+/// Table column definition
+#[derive(Debug, Clone)]
+pub struct Column {
+    title: String,
+    align: Align,
+    max_width: Option<usize>,
+}
+
+impl Column {
+    pub fn new(title: &str) -> Self {
+        Column {
+            title: title.to_string(),
+            align: Align::Left,
+            max_width: None,
+        }
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Limit the column width (cells are truncated)
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+/// Table renderer
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    separator: String,
+    header: bool,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Table {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            separator: "  ".to_string(),
+            header: true,
+        }
+    }
+
+    pub fn with_column(mut self, column: Column) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Add left-aligned columns with the supplied titles
+    pub fn with_columns(mut self, titles: &[&str]) -> Self {
+        self.columns
+            .extend(titles.iter().map(|title| Column::new(title)));
+        self
+    }
+
+    pub fn with_row<I, S>(mut self, row: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.push_row(row);
+        self
+    }
+
+    /// Column separator (two spaces by default)
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Render the header (column titles and an underline); enabled by default
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn push_row<I, S>(&mut self, row: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.rows
+            .push(row.into_iter().map(|cell| cell.to_string()).collect());
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Compute column widths fitting the supplied total width.
+    fn widths(&self, width: Option<usize>) -> Vec<usize> {
+        let columns = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(self.columns.len());
+
+        let mut widths = (0..columns)
+            .map(|index| {
+                let column = self.columns.get(index);
+                let title = match column {
+                    Some(column) if self.header => display_width(&column.title),
+                    _ => 0,
+                };
+                let width = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .map(|cell| display_width(cell))
+                    .max()
+                    .unwrap_or(0)
+                    .max(title);
+                column
+                    .and_then(|column| column.max_width)
+                    .map(|max_width| width.min(max_width))
+                    .unwrap_or(width)
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(width) = width {
+            let separators = display_width(&self.separator) * columns.saturating_sub(1);
+            let available = width.saturating_sub(separators);
+            while widths.iter().sum::<usize>() > available {
+                match widths
+                    .iter_mut()
+                    .filter(|width| **width > MIN_COLUMN_WIDTH)
+                    .max_by_key(|width| **width)
+                {
+                    Some(widest) => *widest -= 1,
+                    None => break,
+                }
+            }
+        }
+
+        widths
+    }
+
+    fn format_row(&self, cells: &[String], widths: &[usize], header: bool) -> String {
+        widths
+            .iter()
+            .enumerate()
+            .map(|(index, width)| {
+                let cell = cells.get(index).map(String::as_str).unwrap_or("");
+                let align = match self.columns.get(index) {
+                    Some(column) if !header => column.align,
+                    _ => Align::Left,
+                };
+                truncate(cell, *width).pad_to_width_with_alignment(*width, align.into())
+            })
+            .collect::<Vec<_>>()
+            .join(&self.separator)
+            .trim_end()
+            .to_string()
+    }
+
+    /// Render the table into lines fitting the supplied width
+    /// (`None` renders the table without width limits).
+    pub fn render(&self, width: Option<usize>) -> Vec<String> {
+        let widths = self.widths(width);
+        let mut lines = Vec::with_capacity(self.rows.len() + 2);
+        if self.header && !self.columns.is_empty() {
+            let titles = self
+                .columns
+                .iter()
+                .map(|column| column.title.clone())
+                .collect::<Vec<_>>();
+            lines.push(self.format_row(&titles, &widths, true));
+            lines.push(
+                widths
+                    .iter()
+                    .map(|width| "-".repeat(*width))
+                    .collect::<Vec<_>>()
+                    .join(&self.separator),
+            );
+        }
+        lines.extend(
+            self.rows
+                .iter()
+                .map(|row| self.format_row(row, &widths, false)),
+        );
+        lines
+    }
+}
+
+/// Truncate text to the supplied display width, terminating
+/// truncated text with an ellipsis.
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+
+    let mut result = String::new();
+    for c in text.chars() {
+        if display_width(&result) + display_width(c.encode_utf8(&mut [0; 4])) + 1 > width {
+            break;
+        }
+        result.push(c);
+    }
+    if width > 0 {
+        result.push(ELLIPSIS);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let table = Table::new()
+            .with_column(Column::new("Peer"))
+            .with_column(Column::new("Latency").with_align(Align::Right))
+            .with_column(Column::new("Agent"))
+            .with_row(["10.0.0.1", "12 ms", "node/0.1"])
+            .with_row(["10.0.0.22", "140 ms", "node/0.2 (linux)"]);
+
+        assert_eq!(
+            table.render(None),
+            [
+                "Peer       Latency  Agent",
+                "---------  -------  ----------------",
+                "10.0.0.1     12 ms  node/0.1",
+                "10.0.0.22   140 ms  node/0.2 (linux)",
+            ]
+        );
+
+        assert_eq!(
+            table.render(Some(30)),
+            [
+                "Peer       Latency  Agent",
+                "---------  -------  ----------",
+                "10.0.0.1     12 ms  node/0.1",
+                "10.0.0.22   140 ms  node/0.2 …",
+            ]
+        );
+
+        let table = Table::new()
+            .with_header(false)
+            .with_separator(" | ")
+            .with_row(["a", "b"])
+            .with_row(["ccc"]);
+        assert_eq!(table.render(None), ["a   | b", "ccc |"]);
+    }
+}
@@ -85,6 +85,14 @@ impl Crossterm {
                         KeyCode::Up => Key::ArrowUp,
                         KeyCode::Down => Key::ArrowDown,
                         KeyCode::Backspace => Key::Backspace,
+                        KeyCode::Home => Key::Home,
+                        KeyCode::End => Key::End,
+                        KeyCode::Delete => Key::Delete,
+                        KeyCode::Insert => Key::Insert,
+                        KeyCode::PageUp => Key::PageUp,
+                        KeyCode::PageDown => Key::PageDown,
+                        KeyCode::Tab => Key::Tab,
+                        KeyCode::BackTab => Key::BackTab,
                         _ => {
                             continue;
                         }
@@ -149,11 +157,11 @@ impl Crossterm {
     }
 
     pub fn cols(&self) -> Option<usize> {
-        None
+        terminal::size().ok().map(|(cols, _)| cols as usize)
     }
 
     pub fn rows(&self) -> Option<usize> {
-        None
+        terminal::size().ok().map(|(_, rows)| rows as usize)
     }
 
     pub fn increase_font_size(&self) -> Result<Option<f64>> {
@@ -0,0 +1,602 @@
+//!
+//! In-memory terminal backend for testing [`Cli`](crate::Cli) and
+//! [`Handler`](crate::Handler) implementations without a TTY or a browser.
+//!
+//! Input is fed as [`Key`] sequences or text lines and the output is
+//! captured either as raw text (including ANSI sequences) or as plain
+//! text rendered by a minimal screen emulation (ANSI sequences, cursor
+//! movement and line clearing applied). The output can alternatively be
+//! forwarded to an output handler (see [`Headless::set_output_handler`]),
+//! which is used to serve terminal sessions remotely (see the
+//! `remote` module, available with the `remote` feature).
+//!
+//! ```ignore
+//! let term = Arc::new(Terminal::try_new_headless(cli, Options::new())?);
+//! term.init().await?;
+//! let headless = term.headless().unwrap();
+//! let runner = term.clone();
+//! workflow_core::task::spawn(async move { runner.run().await });
+//!
+//! headless.line("hello").await?;
+//! assert!(headless.text().contains("hello back to you!"));
+//! ```
+//!
+
+use crate::error::Error;
+use crate::keys::Key;
+use crate::terminal::Options;
+use crate::terminal::Terminal;
+use crate::Result;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use workflow_core::channel::Channel;
+use workflow_core::task::sleep;
+use workflow_core::time::Instant;
+
+/// Default number of columns reported by the headless backend
+pub const DEFAULT_HEADLESS_COLS: usize = 80;
+
+/// Default time [`Headless::settle`] waits for the terminal to become idle
+pub const DEFAULT_SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Output handler receiving the raw terminal output
+pub type OutputHandlerFn = Arc<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Default)]
+struct Input {
+    keys: VecDeque<Key>,
+    /// number of intake loops waiting for input
+    waiting: usize,
+}
+
+///
+/// # Headless
+///
+/// In-memory terminal backend driven by scripted input.
+///
+pub struct Headless {
+    terminal: Arc<Mutex<Option<Arc<Terminal>>>>,
+    terminate: Arc<AtomicBool>,
+    running: Arc<AtomicBool>,
+    input: Arc<Mutex<Input>>,
+    signal: Channel<()>,
+    output: Arc<Mutex<String>>,
+    output_handler: Arc<Mutex<Option<OutputHandlerFn>>>,
+    cols: Arc<AtomicUsize>,
+    /// number of rows (0 if unknown)
+    rows: Arc<AtomicUsize>,
+    settle_timeout: Arc<Mutex<Duration>>,
+}
+
+impl Headless {
+    pub fn try_new() -> Result<Self> {
+        Self::try_new_with_options(&Options::default())
+    }
+
+    pub fn try_new_with_options(_options: &Options) -> Result<Self> {
+        let headless = Headless {
+            terminal: Arc::new(Mutex::new(None)),
+            terminate: Arc::new(AtomicBool::new(false)),
+            running: Arc::new(AtomicBool::new(false)),
+            input: Arc::new(Mutex::new(Input::default())),
+            signal: Channel::unbounded(),
+            output: Arc::new(Mutex::new(String::new())),
+            output_handler: Arc::new(Mutex::new(None)),
+            cols: Arc::new(AtomicUsize::new(DEFAULT_HEADLESS_COLS)),
+            rows: Arc::new(AtomicUsize::new(0)),
+            settle_timeout: Arc::new(Mutex::new(DEFAULT_SETTLE_TIMEOUT)),
+        };
+        Ok(headless)
+    }
+
+    pub async fn init(self: &Arc<Self>, terminal: &Arc<Terminal>) -> Result<()> {
+        *self.terminal.lock().unwrap() = Some(terminal.clone());
+        Ok(())
+    }
+
+    pub fn exit(&self) {
+        self.terminate.store(true, Ordering::SeqCst);
+        self.signal.try_send(()).ok();
+    }
+
+    pub fn terminal(&self) -> Arc<Terminal> {
+        self.terminal.lock().unwrap().as_ref().unwrap().clone()
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        self.running.store(true, Ordering::SeqCst);
+        let result = self.intake(&self.terminate).await;
+        self.running.store(false, Ordering::SeqCst);
+        result
+    }
+
+    pub async fn intake(&self, terminate: &Arc<AtomicBool>) -> Result<()> {
+        while !terminate.load(Ordering::SeqCst) {
+            let key = {
+                let mut input = self.input.lock().unwrap();
+                let key = input.keys.pop_front();
+                if key.is_none() {
+                    input.waiting += 1;
+                }
+                key
+            };
+
+            match key {
+                Some(key) => {
+                    self.terminal().ingest(key, "".to_string()).await?;
+                }
+                None => {
+                    self.signal.recv().await?;
+                    self.input.lock().unwrap().waiting -= 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn write<S>(&self, s: S)
+    where
+        S: ToString,
+    {
+        let handler = self.output_handler.lock().unwrap().clone();
+        match handler {
+            Some(handler) => handler(&s.to_string()),
+            None => self.output.lock().unwrap().push_str(&s.to_string()),
+        }
+    }
+
+    /// Forward the output to the supplied handler instead of capturing it
+    /// (`None` restores the capture).
+    pub fn set_output_handler(&self, handler: Option<OutputHandlerFn>) {
+        *self.output_handler.lock().unwrap() = handler;
+    }
+
+    /// Queue a key without waiting for it to be processed.
+    pub fn send_key(&self, key: Key) {
+        self.input.lock().unwrap().keys.push_back(key);
+        self.signal.try_send(()).ok();
+    }
+
+    /// Feed a key and wait until the terminal is idle (see [`Headless::settle`]).
+    pub async fn key(&self, key: Key) -> Result<()> {
+        self.send_key(key);
+        self.settle().await
+    }
+
+    /// Feed a key sequence and wait until the terminal is idle.
+    pub async fn keys(&self, keys: &[Key]) -> Result<()> {
+        keys.iter().for_each(|key| self.send_key(*key));
+        self.settle().await
+    }
+
+    /// Type the supplied text (without pressing Enter)
+    /// and wait until the terminal is idle.
+    pub async fn text_input(&self, text: &str) -> Result<()> {
+        text.chars().for_each(|ch| self.send_key(Key::Char(ch)));
+        self.settle().await
+    }
+
+    /// Type the supplied text followed by Enter and
+    /// wait until the terminal is idle.
+    pub async fn line(&self, text: &str) -> Result<()> {
+        text.chars().for_each(|ch| self.send_key(Key::Char(ch)));
+        self.send_key(Key::Enter);
+        self.settle().await
+    }
+
+    /// Set the time [`Headless::settle`] waits for the terminal
+    /// to become idle (see [`DEFAULT_SETTLE_TIMEOUT`]).
+    pub fn set_settle_timeout(&self, timeout: Duration) {
+        *self.settle_timeout.lock().unwrap() = timeout;
+    }
+
+    /// Wait until all queued keys have been processed and the terminal
+    /// is waiting for further input (either at the prompt or inside
+    /// [`Terminal::ask`], [`Terminal::kbhit`] or [`Terminal::select`]),
+    /// or until the terminal processing loop has exited.
+    ///
+    /// Returns [`Error::Settle`] if the terminal does not become idle
+    /// within the settle timeout (see [`Headless::set_settle_timeout`]),
+    /// e.g. if the terminal is not running or a command does not complete.
+    pub async fn settle(&self) -> Result<()> {
+        let timeout = *self.settle_timeout.lock().unwrap();
+        let start = Instant::now();
+        loop {
+            {
+                let input = self.input.lock().unwrap();
+                let exited = self.terminate.load(Ordering::SeqCst);
+                if input.keys.is_empty() && (input.waiting > 0 || exited) {
+                    return Ok(());
+                }
+                if start.elapsed() > timeout {
+                    return Err(Error::Settle(format!(
+                        "{timeout:?} elapsed ({} queued keys, running: {}); output:\n{}",
+                        input.keys.len(),
+                        self.is_running(),
+                        self.text()
+                    )));
+                }
+            }
+            sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    /// Returns true while the terminal processing loop is active.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Raw output including ANSI sequences.
+    pub fn output(&self) -> String {
+        self.output.lock().unwrap().clone()
+    }
+
+    /// Output rendered as plain text lines (ANSI sequences applied and stripped).
+    pub fn lines(&self) -> Vec<String> {
+        render(&self.output.lock().unwrap())
+    }
+
+    /// Output rendered as plain text (ANSI sequences applied and stripped).
+    pub fn text(&self) -> String {
+        self.lines().join("\n")
+    }
+
+    /// Discard the captured output.
+    pub fn clear(&self) {
+        self.output.lock().unwrap().clear();
+    }
+
+    /// Set the number of columns reported to the terminal
+    /// (`0` if the number of columns is unknown).
+    pub fn set_cols(&self, cols: usize) {
+        self.cols.store(cols, Ordering::SeqCst);
+    }
+
+    /// Set the number of rows reported to the terminal
+    /// (`0` if the number of rows is unknown).
+    pub fn set_rows(&self, rows: usize) {
+        self.rows.store(rows, Ordering::SeqCst);
+    }
+}
+
+// compatibility functions
+impl Headless {
+    pub fn get_font_size(&self) -> Result<Option<f64>> {
+        Ok(None)
+    }
+
+    pub fn set_font_size(&self, _font_size: f64) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn cols(&self) -> Option<usize> {
+        Some(self.cols.load(Ordering::SeqCst)).filter(|cols| *cols > 0)
+    }
+
+    pub fn rows(&self) -> Option<usize> {
+        Some(self.rows.load(Ordering::SeqCst)).filter(|rows| *rows > 0)
+    }
+
+    pub fn increase_font_size(&self) -> Result<Option<f64>> {
+        Ok(None)
+    }
+
+    pub fn decrease_font_size(&self) -> Result<Option<f64>> {
+        Ok(None)
+    }
+}
+
+/// Render terminal output into plain text lines applying carriage
+/// returns, backspaces, cursor movement and line/screen clearing.
+/// Trailing whitespace is trimmed and trailing empty lines are removed.
+pub fn render(output: &str) -> Vec<String> {
+    let mut lines: Vec<Vec<char>> = vec![Vec::new()];
+    let (mut row, mut col) = (0usize, 0usize);
+    let mut chars = output.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut command = None;
+                    for c in chars.by_ref() {
+                        if c.is_ascii_digit() || c == ';' || c == '?' {
+                            params.push(c);
+                        } else {
+                            command = Some(c);
+                            break;
+                        }
+                    }
+                    let args = params
+                        .trim_start_matches('?')
+                        .split(';')
+                        .map(|arg| arg.parse::<usize>().ok())
+                        .collect::<Vec<_>>();
+                    let arg = |index: usize, default: usize| {
+                        args.get(index).copied().flatten().unwrap_or(default)
+                    };
+                    let line = &mut lines[row];
+                    match command {
+                        Some('K') => match arg(0, 0) {
+                            0 => line.truncate(col),
+                            1 => line.iter_mut().take(col + 1).for_each(|c| *c = ' '),
+                            _ => line.clear(),
+                        },
+                        Some('J') => match arg(0, 0) {
+                            0 => {
+                                line.truncate(col);
+                                lines.truncate(row + 1);
+                            }
+                            2 => {
+                                lines = vec![Vec::new()];
+                                row = 0;
+                            }
+                            _ => {}
+                        },
+                        Some('H') | Some('f') => {
+                            row = arg(0, 1).saturating_sub(1);
+                            col = arg(1, 1).saturating_sub(1);
+                        }
+                        Some('A') => row = row.saturating_sub(arg(0, 1)),
+                        Some('B') => row += arg(0, 1),
+                        Some('C') => col += arg(0, 1),
+                        Some('D') => col = col.saturating_sub(arg(0, 1)),
+                        _ => {}
+                    }
+                }
+                Some(']') => {
+                    // operating system command, terminated by BEL or ST
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' => col = 0,
+            '\n' => {
+                row += 1;
+                col = 0;
+            }
+            '\x08' => col = col.saturating_sub(1),
+            '\t' => col = (col / 8 + 1) * 8,
+            c if c.is_control() => {}
+            c => {
+                let line = &mut lines[row];
+                if line.len() <= col {
+                    line.resize(col + 1, ' ');
+                }
+                line[col] = c;
+                col += 1;
+            }
+        }
+
+        if lines.len() <= row {
+            lines.resize(row + 1, Vec::new());
+        }
+    }
+
+    let mut lines = lines
+        .into_iter()
+        .map(|line| line.into_iter().collect::<String>().trim_end().to_string())
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cli::{Cli, Context, Handler, HandlerCli};
+    use crate::progress::ProgressBar;
+    use async_trait::async_trait;
+    use workflow_core::task::spawn;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(render("$ abc\x08\x08X\n\r"), ["$ aXc"]);
+        assert_eq!(render("one\n\r\x1b[2K\r$ two\x1b[1DX"), ["one", "$ twX"]);
+        assert_eq!(
+            render("\x1b[31mred\x1b[0m\x1b]0;title\x07\n\r\tx"),
+            ["red", "        x"]
+        );
+        assert_eq!(render("junk\x1b[2J\x1b[1;1Hclean"), ["clean"]);
+    }
+
+    struct TestCli {
+        term: Mutex<Option<Arc<Terminal>>>,
+        handlers: HandlerCli,
+    }
+
+    impl Context for TestCli {
+        fn term(&self) -> Arc<Terminal> {
+            self.term.lock().unwrap().clone().unwrap()
+        }
+    }
+
+    struct Greet;
+
+    #[async_trait]
+    impl Handler for Greet {
+        fn verb(&self, _ctx: &Arc<dyn Context>) -> Option<&'static str> {
+            Some("greet")
+        }
+        async fn handle(
+            self: Arc<Self>,
+            ctx: &Arc<dyn Context>,
+            _argv: Vec<String>,
+            _cmd: &str,
+        ) -> Result<()> {
+            let term = ctx.term();
+            let name = term.ask(false, "name: ").await?;
+            let pin = term.ask(true, "pin: ").await?;
+            let color = term.select("color?", &["red", "green"]).await?;
+            term.kbhit(Some("press any key")).await?;
+            term.writeln(format!(
+                "hello {name} ({}) {}",
+                pin.len(),
+                color.unwrap_or_default()
+            ));
+            Ok(())
+        }
+    }
+
+    /// Writes its arguments (registered as `echo` and as `head`,
+    /// shadowing the built-in pipeline filter)
+    struct Echo(&'static str);
+
+    #[async_trait]
+    impl Handler for Echo {
+        fn verb(&self, _ctx: &Arc<dyn Context>) -> Option<&'static str> {
+            Some(self.0)
+        }
+        async fn handle(
+            self: Arc<Self>,
+            ctx: &Arc<dyn Context>,
+            argv: Vec<String>,
+            _cmd: &str,
+        ) -> Result<()> {
+            let term = ctx.term();
+            term.write(argv.join(" "));
+            term.writeln("");
+            Ok(())
+        }
+    }
+
+    /// Writes blocks while displaying a progress bar
+    struct Sync;
+
+    #[async_trait]
+    impl Handler for Sync {
+        fn verb(&self, _ctx: &Arc<dyn Context>) -> Option<&'static str> {
+            Some("sync")
+        }
+        async fn handle(
+            self: Arc<Self>,
+            ctx: &Arc<dyn Context>,
+            _argv: Vec<String>,
+            _cmd: &str,
+        ) -> Result<()> {
+            let term = ctx.term();
+            let progress = ProgressBar::new(&term, 2);
+            for block in 1..=2 {
+                term.writeln(format!("block {block}"));
+                progress.inc(1);
+            }
+            progress.finish_with_message("synced");
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Cli for TestCli {
+        fn init(self: Arc<Self>, term: &Arc<Terminal>) -> Result<()> {
+            *self.term.lock().unwrap() = Some(term.clone());
+            self.handlers.register(&self, Greet);
+            self.handlers.register(&self, Sync);
+            self.handlers.register(&self, Echo("echo"));
+            self.handlers.register(&self, Echo("head"));
+            self.handlers.register(&self, crate::cli::HistoryHandler);
+            self.handlers.register(&self, crate::cli::BindingsHandler);
+            Ok(())
+        }
+        async fn digest(self: Arc<Self>, _term: Arc<Terminal>, cmd: String) -> Result<()> {
+            self.handlers.execute(&self, &cmd).await
+        }
+        async fn complete(
+            self: Arc<Self>,
+            _term: Arc<Terminal>,
+            cmd: String,
+        ) -> Result<Option<Vec<String>>> {
+            self.handlers.complete(&self, &cmd).await
+        }
+        fn prompt(&self) -> Option<String> {
+            None
+        }
+    }
+
+    /// Create and initialize a headless terminal running a test [`Cli`]
+    /// (with the `greet`, `sync`, `echo`, `head`, `history` and `bindings` commands).
+    /// The terminal processing loop is started if `run` is true.
+    pub(crate) async fn test_terminal(
+        options: Options,
+        run: bool,
+    ) -> Result<(Arc<Terminal>, Arc<Headless>)> {
+        let cli = Arc::new(TestCli {
+            term: Mutex::new(None),
+            handlers: HandlerCli::new(),
+        });
+        let term = Arc::new(Terminal::try_new_headless(cli, options)?);
+        term.init().await?;
+        let headless = term.headless().unwrap();
+        if run {
+            let runner = term.clone();
+            spawn(async move { runner.run().await });
+        }
+        Ok((term, headless))
+    }
+
+    #[tokio::test]
+    async fn test_headless() -> Result<()> {
+        let (term, headless) = test_terminal(Options::new(), true).await?;
+
+        headless
+            .keys(&[Key::Char('g'), Key::Char('r'), Key::Tab])
+            .await?;
+        assert_eq!(headless.lines(), ["$ greet"]);
+
+        headless.key(Key::Enter).await?;
+        headless.line("alice").await?;
+        headless.line("1234").await?;
+        headless.line("1").await?;
+        assert_eq!(headless.lines().last().unwrap(), "press any key");
+        headless.key(Key::Char('x')).await?;
+        assert_eq!(
+            headless.lines(),
+            [
+                "$ greet",
+                "name: alice",
+                "pin:",
+                "0: red",
+                "1: green",
+                "color? [0..1] or <enter> to abort: 1",
+                "press any key",
+                "hello alice (4) green",
+                "$"
+            ]
+        );
+
+        headless.set_cols(0);
+        assert_eq!(headless.cols(), None);
+        headless.set_cols(DEFAULT_HEADLESS_COLS);
+
+        headless.clear();
+        *term.prompt.lock().unwrap() = "# ".to_string();
+        term.refresh_prompt();
+        assert_eq!(headless.text(), "#");
+        assert_eq!(headless.output(), "\x1b[2K\r# ");
+
+        term.exit().await;
+        headless.settle().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_settle_timeout() -> Result<()> {
+        let (_term, headless) = test_terminal(Options::new(), false).await?;
+        headless.set_settle_timeout(Duration::from_millis(50));
+        // the terminal is not running
+        assert!(matches!(
+            headless.key(Key::Enter).await,
+            Err(Error::Settle(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//!
+//! Terminal backend dispatch: the platform backend (xterm.js in the browser,
+//! termion or crossterm in the native environment) or the in-memory
+//! [`Headless`] backend.
+//!
+
+use super::headless::Headless;
+use super::{Options, Terminal};
+use crate::Result;
+use cfg_if::cfg_if;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+        use super::xterm::Xterm as Native;
+        use super::{EventHandlerFn, LinkMatcherHandlerFn, Theme};
+    } else if #[cfg(feature = "termion")] {
+        use super::termion::Termion as Native;
+    } else {
+        use super::crossterm::Crossterm as Native;
+    }
+}
+
+/// Terminal backend
+pub enum Interface {
+    /// Platform backend
+    Native(Arc<Native>),
+    /// In-memory backend (see [`Headless`])
+    Headless(Arc<Headless>),
+}
+
+impl Interface {
+    pub fn try_new() -> Result<Self> {
+        Ok(Interface::Native(Arc::new(Native::try_new()?)))
+    }
+
+    pub fn try_new_with_options(options: &Options) -> Result<Self> {
+        Ok(Interface::Native(Arc::new(Native::try_new_with_options(
+            options,
+        )?)))
+    }
+
+    pub fn try_new_headless(options: &Options) -> Result<Self> {
+        Ok(Interface::Headless(Arc::new(
+            Headless::try_new_with_options(options)?,
+        )))
+    }
+
+    /// Returns the in-memory backend if the terminal is headless
+    pub fn headless(&self) -> Option<Arc<Headless>> {
+        match self {
+            Interface::Headless(headless) => Some(headless.clone()),
+            _ => None,
+        }
+    }
+
+    pub async fn init(&self, terminal: &Arc<Terminal>) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.init(terminal).await,
+            Interface::Headless(headless) => headless.init(terminal).await,
+        }
+    }
+
+    pub fn exit(&self) {
+        match self {
+            Interface::Native(native) => native.exit(),
+            Interface::Headless(headless) => headless.exit(),
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.run().await,
+            Interface::Headless(headless) => headless.run().await,
+        }
+    }
+
+    pub async fn intake(&self, terminate: &Arc<AtomicBool>) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.intake(terminate).await,
+            Interface::Headless(headless) => headless.intake(terminate).await,
+        }
+    }
+
+    pub fn write<S>(&self, s: S)
+    where
+        S: ToString,
+    {
+        match self {
+            Interface::Native(native) => native.write(s),
+            Interface::Headless(headless) => headless.write(s),
+        }
+    }
+
+    pub fn get_font_size(&self) -> Result<Option<f64>> {
+        match self {
+            Interface::Native(native) => native.get_font_size(),
+            Interface::Headless(headless) => headless.get_font_size(),
+        }
+    }
+
+    pub fn set_font_size(&self, font_size: f64) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.set_font_size(font_size),
+            Interface::Headless(headless) => headless.set_font_size(font_size),
+        }
+    }
+
+    pub fn cols(&self) -> Option<usize> {
+        match self {
+            Interface::Native(native) => native.cols(),
+            Interface::Headless(headless) => headless.cols(),
+        }
+    }
+
+    pub fn rows(&self) -> Option<usize> {
+        match self {
+            Interface::Native(native) => native.rows(),
+            Interface::Headless(headless) => headless.rows(),
+        }
+    }
+
+    pub fn increase_font_size(&self) -> Result<Option<f64>> {
+        match self {
+            Interface::Native(native) => native.increase_font_size(),
+            Interface::Headless(headless) => headless.increase_font_size(),
+        }
+    }
+
+    pub fn decrease_font_size(&self) -> Result<Option<f64>> {
+        match self {
+            Interface::Native(native) => native.decrease_font_size(),
+            Interface::Headless(headless) => headless.decrease_font_size(),
+        }
+    }
+}
+
+// browser-specific functions (no-op for the headless backend)
+#[cfg(target_arch = "wasm32")]
+impl Interface {
+    pub fn set_theme(&self, theme: Theme) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.set_theme(theme),
+            Interface::Headless(_) => Ok(()),
+        }
+    }
+
+    pub fn update_theme(&self) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.update_theme(),
+            Interface::Headless(_) => Ok(()),
+        }
+    }
+
+    pub fn clipboard_copy(&self) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.clipboard_copy(),
+            Interface::Headless(_) => Ok(()),
+        }
+    }
+
+    pub fn clipboard_paste(&self) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.clipboard_paste(),
+            Interface::Headless(_) => Ok(()),
+        }
+    }
+
+    pub fn register_event_handler(&self, handler: EventHandlerFn) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.register_event_handler(handler),
+            Interface::Headless(_) => Ok(()),
+        }
+    }
+
+    pub fn register_link_matcher(
+        &self,
+        regexp: &js_sys::RegExp,
+        handler: LinkMatcherHandlerFn,
+    ) -> Result<()> {
+        match self {
+            Interface::Native(native) => native.register_link_matcher(regexp, handler),
+            Interface::Headless(_) => Ok(()),
+        }
+    }
+}
@@ -4,21 +4,31 @@
 
 use crate::clear::*;
 use crate::cli::Cli;
+use crate::completion::{self, Completion};
 use crate::cursor::*;
+use crate::editor::{Action, Effect, LineEditor};
 use crate::error::Error;
+use crate::history::History;
+use crate::keymap::{Binding, Keymap};
 use crate::keys::Key;
+use crate::pipeline::{self, CaptureOutput};
+use crate::recording::{EventKind, Recorder, Recording, DEFAULT_HEIGHT, DEFAULT_WIDTH};
 use crate::result::Result;
+use crate::script::{Script, EXIT_FAILURE, EXIT_SUCCESS};
+use crate::table::{truncate, Table};
 use crate::CrLf;
 use crate::UnicodeString;
 use cfg_if::cfg_if;
 use futures::*;
 pub use pad::PadStr;
 use regex::Regex;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, LockResult, Mutex, MutexGuard};
 use workflow_core::channel::{unbounded, Channel, DuplexChannel, Receiver, Sender};
-use workflow_core::task::spawn;
-use workflow_log::log_error;
+use workflow_core::task::{sleep, spawn};
+use workflow_log::{log_error, log_warn};
+use workflow_store::fs;
 
 const DEFAULT_PARA_WIDTH: usize = 80;
 
@@ -49,48 +59,60 @@ cfg_if! {
     if #[cfg(target_arch = "wasm32")] {
         // pub mod xterm;
         // pub mod bindings;
-        use crate::terminal::xterm::Xterm as Interface;
-
     } else if #[cfg(feature = "termion")] {
         pub mod termion;
-        use crate::terminal::termion::Termion as Interface;
     } else {
         pub mod crossterm;
-        use crate::terminal::crossterm::Crossterm as Interface;
         pub use crate::terminal::crossterm::{disable_raw_mode,init_panic_hook};
     }
 }
 
-#[derive(Debug)]
-pub struct Inner {
-    pub buffer: UnicodeString,
-    history: Vec<UnicodeString>,
-    pub cursor: usize,
-    history_index: usize,
-}
-
-impl Default for Inner {
-    fn default() -> Self {
-        Self::new()
-    }
+pub mod headless;
+mod interface;
+pub use headless::Headless;
+pub use interface::Interface;
+
+/// Status lines (such as progress bars) displayed at the bottom
+/// of the terminal output, above the prompt or the user input line.
+#[derive(Default)]
+struct StatusLines {
+    lines: Vec<(u64, String)>,
+    next_id: u64,
+    /// number of rows above the cursor row occupied by the
+    /// displayed footer (`None` if the footer is not displayed)
+    footer: Option<usize>,
 }
 
-impl Inner {
-    pub fn new() -> Self {
-        Inner {
-            buffer: UnicodeString::default(),
-            history: vec![],
-            cursor: 0,
-            history_index: 0,
+impl StatusLines {
+    /// Render status lines, followed by CRLF if a line (prompt) follows
+    fn render(&mut self, line_follows: bool, cols: Option<usize>) -> String {
+        if self.lines.is_empty() {
+            self.footer = None;
+            return String::new();
         }
-    }
 
-    pub fn reset_line_buffer(&mut self) {
-        self.buffer.clear();
-        self.cursor = 0;
+        let text = self
+            .lines
+            .iter()
+            .map(|(_, line)| match cols {
+                Some(cols) => truncate(line, cols.saturating_sub(1)),
+                None => line.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\r");
+        if line_follows {
+            self.footer = Some(self.lines.len());
+            text + "\n\r"
+        } else {
+            self.footer = Some(self.lines.len() - 1);
+            text
+        }
     }
 }
 
+/// Line editor state of the terminal (see [`LineEditor`](crate::editor::LineEditor))
+pub type Inner = LineEditor;
+
 #[derive(Clone)]
 struct UserInput {
     prompt: Arc<Mutex<Option<String>>>,
@@ -195,11 +217,22 @@ impl UserInput {
     }
 
     fn ingest(&self, key: Key, term: &Arc<Terminal>) -> Result<()> {
-        match key {
-            Key::Ctrl('c') => {
+        let binding = term.keymap.lock()?.get(key);
+        match binding {
+            Some(Binding::Exit) => {
                 self.close()?;
                 term.abort();
+                return Ok(());
+            }
+            Some(Binding::Action(Action::Interrupt)) => {
+                term.crlf();
+                self.close()?;
+                return Ok(());
             }
+            _ => {}
+        }
+
+        match key {
             Key::Char(ch) => {
                 self.buffer.lock().unwrap().push(ch);
                 if !self.is_secret() {
@@ -258,6 +291,14 @@ pub struct Terminal {
     pub pipe_crlf: Channel<String>,
     pub pipe_ctl: DuplexChannel<()>,
     pub para_width: Arc<AtomicUsize>,
+    history_file: Arc<Mutex<Option<PathBuf>>>,
+    keymap: Arc<Mutex<Keymap>>,
+    bindings_file: Option<String>,
+    remote: Arc<Mutex<Option<Sender<Key>>>>,
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    status: Arc<Mutex<StatusLines>>,
+    // set while executing a script, user input is not available
+    scripted: Arc<AtomicBool>,
 }
 
 impl Terminal {
@@ -268,6 +309,7 @@ impl Terminal {
         let terminal = Self {
             inner: Arc::new(Mutex::new(Inner::new())),
             running: Arc::new(AtomicBool::new(false)),
+            scripted: Arc::new(AtomicBool::new(false)),
             prompt: Arc::new(Mutex::new(prompt.to_string())),
             term,
             handler,
@@ -277,6 +319,12 @@ impl Terminal {
             pipe_crlf: Channel::unbounded(),
             pipe_ctl: DuplexChannel::oneshot(),
             para_width: Arc::new(AtomicUsize::new(DEFAULT_PARA_WIDTH)),
+            history_file: Arc::new(Mutex::new(None)),
+            keymap: Arc::new(Mutex::new(Keymap::default())),
+            bindings_file: None,
+            remote: Arc::new(Mutex::new(None)),
+            recorder: Arc::new(Mutex::new(None)),
+            status: Arc::new(Mutex::new(StatusLines::default())),
         };
 
         Ok(terminal)
@@ -290,10 +338,36 @@ impl Terminal {
         options: Options,
     ) -> Result<Self> {
         let term = Arc::new(Interface::try_new_with_options(&options)?);
+        Self::try_new_with_interface(handler, term, options)
+    }
+
+    /// Create a new terminal instance bound to the supplied command-line
+    /// processor [`Cli`] using the in-memory [`Headless`] backend
+    /// (see [`Terminal::headless`]). Useful for testing [`Cli`] implementations.
+    pub fn try_new_headless(handler: Arc<dyn Cli>, options: Options) -> Result<Self> {
+        let term = Arc::new(Interface::try_new_headless(&options)?);
+        Self::try_new_with_interface(handler, term, options)
+    }
+
+    fn try_new_with_interface(
+        handler: Arc<dyn Cli>,
+        term: Arc<Interface>,
+        options: Options,
+    ) -> Result<Self> {
+        let history_file = options
+            .history_file
+            .as_deref()
+            .map(fs::resolve_path)
+            .transpose()?;
+        let mut inner = Inner::new();
+        if let Some(history_size) = options.history_size {
+            inner.set_history_size(history_size);
+        }
 
         let terminal = Self {
-            inner: Arc::new(Mutex::new(Inner::new())),
+            inner: Arc::new(Mutex::new(inner)),
             running: Arc::new(AtomicBool::new(false)),
+            scripted: Arc::new(AtomicBool::new(false)),
             prompt: Arc::new(Mutex::new(options.prompt())),
             term,
             handler,
@@ -303,6 +377,12 @@ impl Terminal {
             pipe_crlf: Channel::unbounded(),
             pipe_ctl: DuplexChannel::oneshot(),
             para_width: Arc::new(AtomicUsize::new(DEFAULT_PARA_WIDTH)),
+            history_file: Arc::new(Mutex::new(history_file)),
+            keymap: Arc::new(Mutex::new(options.keymap.unwrap_or_default())),
+            bindings_file: options.bindings_file,
+            remote: Arc::new(Mutex::new(None)),
+            recorder: Arc::new(Mutex::new(None)),
+            status: Arc::new(Mutex::new(StatusLines::default())),
         };
 
         Ok(terminal)
@@ -312,6 +392,13 @@ impl Terminal {
     pub async fn init(self: &Arc<Self>) -> Result<()> {
         self.term.init(self).await?;
 
+        if let Err(err) = self.load_history().await {
+            log_warn!("Unable to load the command history: {err}");
+        }
+        if let Some(filename) = &self.bindings_file {
+            self.load_bindings(filename).await?;
+        }
+
         self.handler.clone().init(self)?;
 
         Ok(())
@@ -322,16 +409,121 @@ impl Terminal {
         self.inner.lock()
     }
 
+    /// Bind a key (see [`Keymap`])
+    pub fn bind(&self, key: Key, binding: Binding) {
+        self.keymap.lock().unwrap().bind(key, binding);
+    }
+
+    /// Remove a key binding
+    pub fn unbind(&self, key: Key) {
+        self.keymap.lock().unwrap().unbind(key);
+    }
+
+    /// Binding of the supplied key (see [`Keymap::get`])
+    pub fn binding(&self, key: Key) -> Option<Binding> {
+        self.keymap.lock().unwrap().get(key)
+    }
+
+    /// Key bindings in effect, sorted by the key name
+    pub fn bindings(&self) -> Vec<(Key, Binding)> {
+        self.keymap.lock().unwrap().bindings()
+    }
+
+    /// Replace the key bindings
+    pub fn set_keymap(&self, keymap: Keymap) {
+        *self.keymap.lock().unwrap() = keymap;
+    }
+
+    /// Apply key bindings from a configuration file
+    /// (see [`Keymap::configure`]); supports `~/` home folder prefix.
+    pub async fn load_bindings(&self, filename: &str) -> Result<()> {
+        let filename = fs::resolve_path(filename)?;
+        let mut keymap = self.keymap.lock()?.clone();
+        keymap.load(&filename).await?;
+        self.set_keymap(keymap);
+        Ok(())
+    }
+
+    /// Forward keys to a remote session (see [`RemoteClient`](crate::remote::RemoteClient))
+    #[cfg(feature = "remote")]
+    pub(crate) fn set_remote(&self, remote: Option<Sender<Key>>) {
+        *self.remote.lock().unwrap() = remote;
+    }
+
+    /// Returns true if the terminal is attached to a remote session
+    pub fn is_remote(&self) -> bool {
+        self.remote.lock().unwrap().is_some()
+    }
+
     /// Get terminal command line history list as `Vec<String>`
     pub fn history(&self) -> Vec<UnicodeString> {
         let data = self.inner().unwrap();
-        data.history.clone()
+        data.history().to_vec()
+    }
+
+    /// Set the command history file (supports `~/` home folder prefix)
+    /// and load the history from it. In the browser, the file name is
+    /// used as the local storage key.
+    pub async fn set_history_file(&self, filename: &str) -> Result<()> {
+        *self.history_file.lock()? = Some(fs::resolve_path(filename)?);
+        self.load_history().await
+    }
+
+    /// Set the maximum number of command history entries
+    pub fn set_history_size(&self, history_size: usize) -> Result<()> {
+        self.inner()?.set_history_size(history_size);
+        Ok(())
+    }
+
+    async fn load_history(&self) -> Result<()> {
+        let filename = self.history_file.lock()?.clone();
+        if let Some(filename) = filename {
+            let mut history = self.inner()?.history_list().clone();
+            history.load(&filename).await?;
+            self.inner()?.set_history_list(history);
+        }
+        Ok(())
+    }
+
+    /// Store the command history to the history file (if configured)
+    pub async fn save_history(&self) -> Result<()> {
+        let filename = self.history_file.lock()?.clone();
+        if let Some(filename) = filename {
+            let history = self.inner()?.history_list().clone();
+            history.save(&filename).await?;
+        }
+        Ok(())
+    }
+
+    /// Clear the command history (including the history file)
+    pub async fn clear_history(&self) -> Result<()> {
+        self.inner()?.clear_history();
+        self.save_history().await
+    }
+
+    /// Record a submitted command in the history, unless the command
+    /// is flagged as secret by the [`Cli`] (see [`Cli::is_secret`]).
+    async fn record_history(&self, cmd: &str) -> Result<()> {
+        let entry = UnicodeString::from(cmd);
+        if !self.handler.is_secret(cmd) && self.inner()?.push_history(entry.clone()) {
+            let filename = self.history_file.lock()?.clone();
+            if let Some(filename) = filename {
+                History::append(&filename, &entry).await?;
+            }
+        }
+        Ok(())
     }
 
     pub fn reset_line_buffer(&self) {
         self.inner().unwrap().reset_line_buffer();
     }
 
+    /// Set the prompt displayed for continuation lines of a multi-line
+    /// input (lines ending with `\`)
+    pub fn set_continuation_prompt(&self, prompt: &str) {
+        self.inner().unwrap().set_continuation_prompt(prompt);
+    }
+
     /// Get the current terminal prompt string
     pub fn get_prompt(&self) -> String {
         if let Some(prompt) = self.handler.prompt() {
@@ -346,48 +538,222 @@ impl Terminal {
         let mut data = self.inner().unwrap();
         data.cursor = 0;
         data.buffer.clear();
-        self.term().write(self.get_prompt());
+        self.write_with_footer("", "", Some(&self.get_prompt()));
     }
 
     /// Output CRLF sequence
     pub fn crlf(&self) {
-        self.term().write("\n\r".to_string());
+        if self.is_running() && !self.user_input.is_enabled() {
+            self.write_with_footer("", "\n\r", None);
+        } else {
+            // the line is complete, status lines are displayed
+            // again below it on the next refresh
+            self.status.lock().unwrap().footer = None;
+            self.write("\n\r");
+        }
     }
 
-    /// Write a string
+    /// Write a string (captured if an output capture is in progress,
+    /// otherwise recorded if a session recording is in progress)
     pub fn write<S>(&self, s: S)
     where
         S: ToString,
     {
-        self.term().write(s);
+        let text = s.to_string();
+        if pipeline::capture(&text) {
+            return;
+        }
+        self.write_output(text);
+    }
+
+    /// Write to the terminal (and the active recording) bypassing
+    /// the output capture
+    fn write_output(&self, text: String) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            let (width, height) = self.recording_size();
+            recorder.resize(width, height);
+            recorder.output(&text);
+        }
+        self.term().write(text);
     }
 
     /// Write a string ending with CRLF sequence
+    /// (captured if an output capture is in progress)
     pub fn writeln<S>(&self, s: S)
     where
         S: ToString,
     {
+        let text = s.to_string();
+        if pipeline::capture(&format!("{text}\n")) {
+            return;
+        }
+
+        let text = format!("{text}\n\r");
         if self.is_running() {
             if self.user_input.is_enabled() {
-                if let Some(prompt) = self.user_input.get_prompt() {
-                    self.write(format!("{}{}\n\r", ClearLine, s.to_string()));
-                    self.write(prompt);
-                    if !self.user_input.secret.load(Ordering::SeqCst) {
-                        self.write(self.user_input.get_buffer());
-                    }
+                if let Some(line) = self.user_input_line() {
+                    self.write_with_footer(ClearLine.as_ref(), &text, Some(&line));
                 }
             } else {
-                self.write(format!("{}\n\r", s.to_string()));
+                self.write_with_footer("", &text, None);
             }
         } else {
-            self.write(format!("{}{}\n\r", ClearLine, s.to_string()));
             let data = self.inner().unwrap();
-            let p = format!("{}{}", self.get_prompt(), data.buffer);
-            self.write(p);
-            let l = data.buffer.len() - data.cursor;
-            for _ in 0..l {
-                self.write("\x08".to_string());
+            let (line, tail) = data.render(&self.get_prompt());
+            self.write_with_footer(
+                ClearLine.as_ref(),
+                &text,
+                Some(&format!("{}{}", ClearLine, line)),
+            );
+            if tail > 0 {
+                self.write_output(Left(tail as u16).to_string());
+            }
+        }
+    }
+
+    /// Start recording the session output and input keys (see
+    /// [`recording`](crate::recording)), discarding any recording
+    /// in progress.
+    pub fn start_recording(&self, title: Option<&str>) {
+        let (width, height) = self.recording_size();
+        *self.recorder.lock().unwrap() = Some(Recorder::new(width, height, title));
+    }
+
+    /// Terminal size recorded in the session recording
+    fn recording_size(&self) -> (usize, usize) {
+        (
+            self.cols().unwrap_or(DEFAULT_WIDTH),
+            self.rows().unwrap_or(DEFAULT_HEIGHT),
+        )
+    }
+
+    /// Stop recording, returning the recording (if one is in progress)
+    pub fn stop_recording(&self) -> Option<Recording> {
+        self.recorder.lock().unwrap().take().map(Recorder::finish)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.lock().unwrap().is_some()
+    }
+
+    /// Replay the output of a recording at the supplied speed
+    /// (`1.0` replays the session in real time). Replayed
+    /// output is not recorded.
+    pub async fn replay(&self, recording: &Recording, speed: f64) -> Result<()> {
+        if !(speed > 0.0 && speed.is_finite()) {
+            return Err(Error::InvalidArguments(format!(
+                "invalid replay speed: {speed}"
+            )));
+        }
+
+        for (event, delay) in recording.events.iter().zip(recording.delays(speed)?) {
+            if !delay.is_zero() {
+                sleep(delay).await;
+            }
+            if event.kind == EventKind::Output {
+                self.term().write(&event.data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute the future capturing the output it writes using
+    /// [`Terminal::write`] or [`Terminal::writeln`] instead of displaying
+    /// it (used to pass the output of a pipeline command to the next
+    /// command). Output written by other tasks is not captured.
+    /// Returns the result of the future and the captured lines.
+    pub async fn capture_output<F, T>(&self, future: F) -> (T, Vec<String>)
+    where
+        F: Future<Output = T>,
+    {
+        CaptureOutput::new(future).await
+    }
+
+    /// User input line (prompt followed by the input buffer)
+    /// displayed by [`Terminal::ask`] or [`Terminal::kbhit`].
+    fn user_input_line(&self) -> Option<String> {
+        self.user_input.get_prompt().map(|prompt| {
+            if self.user_input.is_secret() {
+                prompt
+            } else {
+                prompt + &self.user_input.get_buffer()
+            }
+        })
+    }
+
+    /// Write complete lines (`text`) above the footer and redraw the
+    /// footer: status lines followed by the prompt or the user input
+    /// `line` (if any). `clear` is written instead of clearing the
+    /// footer when the footer is not displayed. Output is written
+    /// using a single write while holding the status lock to prevent
+    /// concurrent output from interleaving with the footer.
+    fn write_with_footer(&self, clear: &str, text: &str, line: Option<&str>) {
+        let mut status = self.status.lock().unwrap();
+        let mut output = match status.footer.take() {
+            Some(0) => ClearLine.to_string(),
+            Some(rows) => format!("\r{}{}", Up(rows as u16), ClearBelow),
+            None => clear.to_string(),
+        };
+        output.push_str(text);
+        output.push_str(&status.render(line.is_some(), self.cols()));
+        if let Some(line) = line {
+            output.push_str(line);
+        }
+        // the footer is not a part of the command output
+        self.write_output(output);
+    }
+
+    /// Add a status line displayed at the bottom of the terminal output
+    /// (above the prompt) until removed. Returns the status line id.
+    /// Used by [`ProgressBar`](crate::progress::ProgressBar) and
+    /// [`Spinner`](crate::progress::Spinner).
+    pub fn add_status_line(&self, text: String) -> u64 {
+        let id = {
+            let mut status = self.status.lock().unwrap();
+            let id = status.next_id;
+            status.next_id += 1;
+            status.lines.push((id, text));
+            id
+        };
+        self.refresh_status();
+        id
+    }
+
+    /// Update the text of a status line
+    pub fn set_status_line(&self, id: u64, text: String) {
+        {
+            let mut status = self.status.lock().unwrap();
+            match status.lines.iter_mut().find(|(line_id, _)| *line_id == id) {
+                Some((_, line)) if *line != text => *line = text,
+                _ => return,
+            }
+        }
+        self.refresh_status();
+    }
+
+    /// Remove a status line
+    pub fn remove_status_line(&self, id: u64) {
+        self.status
+            .lock()
+            .unwrap()
+            .lines
+            .retain(|(line_id, _)| *line_id != id);
+        self.refresh_status();
+    }
+
+    /// Redraw status lines (along with the prompt or the user input line)
+    pub fn refresh_status(&self) {
+        if self.is_running() {
+            if self.user_input.is_enabled() {
+                if let Some(line) = self.user_input_line() {
+                    self.write_with_footer(ClearLine.as_ref(), "", Some(&line));
+                }
+            } else {
+                self.write_with_footer("", "", None);
             }
+        } else {
+            let data = self.inner().unwrap();
+            self.redraw_line(&data);
         }
     }
 
@@ -396,14 +762,15 @@ impl Terminal {
     /// data that should be updated.
     pub fn refresh_prompt(&self) {
         if !self.is_running() {
-            self.write(format!("{}", ClearLine));
             let data = self.inner().unwrap();
-            let p = format!("{}{}", self.get_prompt(), data.buffer);
-            self.write(p);
-            let l = data.buffer.len() - data.cursor;
-            for _ in 0..l {
-                self.write("\x08".to_string());
-            }
+            self.redraw_line(&data);
+        }
+    }
+
+    /// Write a [`Table`] fitting the terminal width
+    pub fn table(&self, table: &Table) {
+        for line in table.render(self.cols()) {
+            self.writeln(line);
         }
     }
 
@@ -481,6 +848,12 @@ impl Terminal {
         Arc::clone(&self.term)
     }
 
+    /// Get the in-memory backend if the terminal has been
+    /// created using [`Terminal::try_new_headless`]
+    pub fn headless(&self) -> Option<Arc<Headless>> {
+        self.term.headless()
+    }
+
     async fn pipe_start(self: &Arc<Self>) -> Result<()> {
         let self_ = self.clone();
         spawn(async move {
@@ -545,19 +918,26 @@ impl Terminal {
 
     /// Ask a question (input a string until CRLF).
     /// `secret` argument suppresses echoing of the
-    /// user input (useful for password entry)
+    /// user input (useful for password entry).
+    /// Answers are never recorded in the command history.
+    /// A key bound to [`Action::Interrupt`] cancels the input (an empty
+    /// string is returned) and a key bound to [`Binding::Exit`] exits
+    /// the terminal. Returns [`Error::NotInteractive`] while executing
+    /// a script (see [`Terminal::exec_script`]).
     pub async fn ask(self: &Arc<Terminal>, secret: bool, prompt: &str) -> Result<String> {
+        self.interactive()?;
         self.reset_line_buffer();
-        self.term().write(prompt.to_string());
+        self.write_with_footer("", "", Some(prompt));
         self.user_input
             .capture(secret, false, Some(prompt.to_string()), self)
             .await
     }
 
     pub async fn kbhit(self: &Arc<Terminal>, prompt: Option<&str>) -> Result<String> {
+        self.interactive()?;
         self.reset_line_buffer();
         if let Some(prompt) = prompt {
-            self.term().write(prompt.to_string());
+            self.write_with_footer("", "", Some(prompt));
         }
         self.user_input
             .capture(true, true, prompt.map(String::from), self)
@@ -602,141 +982,169 @@ impl Terminal {
     }
 
     async fn ingest(self: &Arc<Terminal>, key: Key, _term_key: String) -> Result<()> {
+        // keys entered at a user input prompt are not recorded as they may be secret
+        if !self.user_input.is_enabled() {
+            if let Some(recorder) = self.recorder.lock()?.as_mut() {
+                let (width, height) = self.recording_size();
+                recorder.resize(width, height);
+                recorder.input(&key);
+            }
+        }
+
+        let remote = self.remote.lock()?.clone();
+        if let Some(remote) = remote {
+            remote.try_send(key)?;
+            return Ok(());
+        }
+
         if self.user_input.is_enabled() {
             self.user_input.ingest(key, self)?;
             return Ok(());
         }
 
-        match key {
-            Key::Backspace => {
-                let mut data = self.inner()?;
-                if data.cursor == 0 {
-                    return Ok(());
-                }
-                self.write("\x08".to_string());
-                data.cursor -= 1;
-                let idx = data.cursor;
-                data.buffer.remove(idx);
-                self.trail(data.cursor, &data.buffer, true, true, 0);
+        let binding = self.keymap.lock()?.get(key);
+        match binding {
+            Some(Binding::Action(action)) => self.apply(action).await,
+            Some(Binding::Command(cmd)) => self.exec_binding(cmd).await,
+            Some(Binding::Exit) => {
+                self.exit().await;
+                Ok(())
             }
-            Key::ArrowUp => {
-                let mut data = self.inner()?;
-                if data.history_index == 0 {
-                    return Ok(());
-                }
-                let current_buffer = data.buffer.clone();
-                let index = data.history_index;
-                //log_trace!("ArrowUp: index {}, data.history.len(): {}", index, data.history.len());
-                if data.history.len() <= index {
-                    data.history.push(current_buffer);
-                } else {
-                    data.history[index] = current_buffer;
-                }
-                data.history_index -= 1;
+            None => Ok(()),
+        }
+    }
 
-                data.buffer = data.history[data.history_index].clone();
-                self.write(format!("{}{}{}", ClearLine, self.get_prompt(), data.buffer));
-                data.cursor = data.buffer.len();
-            }
-            Key::ArrowDown => {
-                let mut data = self.inner()?;
-                let len = data.history.len();
-                if data.history_index >= len {
-                    return Ok(());
-                }
-                let index = data.history_index;
-                data.history[index] = data.buffer.clone();
-                data.history_index += 1;
-                if data.history_index == len {
-                    data.buffer.clear();
-                } else {
-                    data.buffer = data.history[data.history_index].clone();
-                }
+    /// Execute a command bound to a key, restoring the
+    /// line being edited once the command completes.
+    async fn exec_binding(self: &Arc<Terminal>, cmd: String) -> Result<()> {
+        let (buffer, cursor) = {
+            let data = self.inner()?;
+            (data.buffer.clone(), data.cursor)
+        };
+        self.crlf();
+        self.running.store(true, Ordering::SeqCst);
+        self.exec(cmd).await.ok();
+        self.running.store(false, Ordering::SeqCst);
+        if !self.terminate.load(Ordering::SeqCst) {
+            let mut data = self.inner()?;
+            data.buffer = buffer;
+            data.cursor = cursor;
+            self.redraw_line(&data);
+        }
+        Ok(())
+    }
 
-                self.write(format!("{}{}{}", ClearLine, self.get_prompt(), data.buffer));
-                data.cursor = data.buffer.len();
+    /// Apply a line editing action and render its effect.
+    async fn apply(self: &Arc<Terminal>, action: Action) -> Result<()> {
+        let effect = self.inner()?.apply(action);
+        match effect {
+            Effect::None => {}
+            Effect::Redraw => {
+                let data = self.inner()?;
+                self.redraw_line(&data);
             }
-            Key::ArrowLeft => {
-                let mut data = self.inner()?;
-                if data.cursor == 0 {
-                    return Ok(());
-                }
-                data.cursor -= 1;
-                self.write(Left(1));
+            Effect::ClearScreen => {
+                self.write(format!("{}{}", ClearScreen, Goto(1, 1)));
+                let data = self.inner()?;
+                self.redraw_line(&data);
             }
-            Key::ArrowRight => {
-                let mut data = self.inner()?;
-                if data.cursor < data.buffer.len() {
-                    data.cursor += 1;
-                    self.write(Right(1));
-                }
+            Effect::Continue => {
+                self.crlf();
+                let data = self.inner()?;
+                self.redraw_line(&data);
             }
-            Key::Enter => {
-                let cmd = {
-                    let mut data = self.inner()?;
-                    let buffer = data.buffer.clone();
-                    let length = data.history.len();
-
-                    data.buffer.clear();
-                    data.cursor = 0;
-
-                    if !buffer.is_empty() {
-                        let cmd = buffer.clone();
-
-                        if length == 0 || !data.history[length - 1].is_empty() {
-                            data.history_index = length;
-                        } else {
-                            data.history_index = length - 1;
-                        }
-                        let index = data.history_index;
-                        if length <= index {
-                            data.history.push(buffer);
-                        } else {
-                            data.history[index] = buffer;
-                        }
-                        data.history_index += 1;
-
-                        Some(cmd)
-                    } else {
-                        None
-                    }
-                };
-
+            Effect::Complete { list } => {
+                self.complete(list).await?;
+            }
+            Effect::Interrupt => {
                 self.crlf();
-
-                if let Some(cmd) = cmd {
+                self.prompt();
+            }
+            Effect::Submit(cmd) => {
+                self.crlf();
+                if cmd.is_empty() {
+                    self.prompt();
+                } else {
+                    self.record_history(&cmd)
+                        .await
+                        .unwrap_or_else(|err| log_error!("Error saving history: {err}"));
                     self.running.store(true, Ordering::SeqCst);
                     self.exec(cmd).await.ok();
                     self.running.store(false, Ordering::SeqCst);
-                } else {
-                    self.prompt();
                 }
             }
-            Key::Alt(_c) => {
-                return Ok(());
+        }
+
+        Ok(())
+    }
+
+    /// Complete the word under the cursor using [`Cli::complete`].
+    /// Candidates are listed if the completion is ambiguous and
+    /// `list` is true (Tab pressed twice).
+    async fn complete(self: &Arc<Terminal>, list: bool) -> Result<()> {
+        let (line, start, word) = {
+            let data = self.inner()?;
+            let line = &data.buffer.0[..data.cursor];
+            let start = completion::word_start(line);
+            (
+                line.iter().collect::<String>(),
+                start,
+                line[start..].iter().collect::<String>(),
+            )
+        };
+
+        // completion errors (such as an unknown command) are not reported
+        let candidates = match self.handler.clone().complete(self.clone(), line).await {
+            Ok(Some(candidates)) => candidates,
+            _ => return Ok(()),
+        };
+
+        match completion::complete(&word, &candidates) {
+            Completion::None => {}
+            Completion::Replace { text, complete } => {
+                let mut data = self.inner()?;
+                let mut text = UnicodeString::from(text);
+                if complete
+                    && !data
+                        .buffer
+                        .0
+                        .get(data.cursor)
+                        .is_some_and(|c| c.is_whitespace())
+                {
+                    text.push(' ');
+                }
+                let cursor = data.cursor;
+                let len = text.len();
+                data.buffer.0.splice(start..cursor, text.0);
+                data.cursor = start + len;
+                self.redraw_line(&data);
             }
-            Key::Ctrl('c') => {
-                cfg_if! {
-                    if #[cfg(not(target_arch = "wasm32"))] {
-                        self.exit().await;
+            Completion::Ambiguous(candidates) => {
+                if list {
+                    let data = self.inner()?;
+                    let width = self.cols().unwrap_or(DEFAULT_PARA_WIDTH);
+                    self.crlf();
+                    for row in completion::columns(&candidates, width) {
+                        self.write(row);
+                        self.crlf();
                     }
+                    self.redraw_line(&data);
                 }
-                return Ok(());
-            }
-            Key::Ctrl(_c) => {
-                return Ok(());
-            }
-            Key::Char(ch) => {
-                self.inject_char(ch)?;
-            }
-            _ => {
-                return Ok(());
             }
         }
 
         Ok(())
     }
 
+    /// Redraw the prompt and the line buffer, positioning the cursor.
+    fn redraw_line(&self, data: &Inner) {
+        let (line, tail) = data.render(&self.get_prompt());
+        self.write_with_footer(ClearLine.as_ref(), "", Some(&line));
+        if tail > 0 {
+            self.write(Left(tail as u16));
+        }
+    }
+
     fn trail(
         &self,
         cursor: usize,
@@ -771,6 +1179,14 @@ impl Terminal {
         self.running.load(Ordering::SeqCst)
     }
 
+    fn interactive(&self) -> Result<()> {
+        if self.scripted.load(Ordering::SeqCst) {
+            Err(Error::NotInteractive)
+        } else {
+            Ok(())
+        }
+    }
+
     pub async fn exec<S: ToString>(self: &Arc<Terminal>, cmd: S) -> Result<()> {
         if let Err(err) = self
             .handler
@@ -782,12 +1198,59 @@ impl Terminal {
         }
         if self.terminate.load(Ordering::SeqCst) {
             self.term().exit();
-        } else {
+        } else if !self.is_remote() {
             self.prompt();
         }
         Ok(())
     }
 
+    /// Execute a [`Script`] non-interactively (without entering the raw
+    /// mode), passing its commands to the [`Cli`]. Command errors are
+    /// written to the terminal; execution stops on the first error if
+    /// `set -e` is in effect or if the terminal is exited by a command.
+    /// Commands requesting user input (e.g. using [`Terminal::ask`]) fail.
+    /// Returns [`EXIT_SUCCESS`](crate::script::EXIT_SUCCESS) if all
+    /// commands have succeeded and [`EXIT_FAILURE`](crate::script::EXIT_FAILURE)
+    /// otherwise.
+    pub async fn exec_script(self: &Arc<Terminal>, script: &Script) -> Result<i32> {
+        self.pipe_start().await?;
+        self.running.store(true, Ordering::SeqCst);
+        self.scripted.store(true, Ordering::SeqCst);
+
+        let mut code = EXIT_SUCCESS;
+        let mut run = script.run();
+        while let Some((line, cmd)) = run.next_command() {
+            let result = match cmd {
+                Ok(cmd) => {
+                    if run.xtrace {
+                        self.writeln(format!("+ {cmd}"));
+                    }
+                    self.handler.clone().digest(self.clone(), cmd).await
+                }
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = result {
+                self.writeln(format!("line {line}: {err}"));
+                code = EXIT_FAILURE;
+                if run.errexit {
+                    break;
+                }
+            }
+
+            if self.terminate.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+
+        self.scripted.store(false, Ordering::SeqCst);
+        self.running.store(false, Ordering::SeqCst);
+        if !self.terminate.load(Ordering::SeqCst) {
+            self.pipe_stop().await?;
+        }
+        Ok(code)
+    }
+
     pub fn set_theme(&self, _theme: Theme) -> Result<()> {
         #[cfg(target_arch = "wasm32")]
         self.term.set_theme(_theme)?;
@@ -832,6 +1295,10 @@ impl Terminal {
         self.term.cols()
     }
 
+    pub fn rows(&self) -> Option<usize> {
+        self.term.rows()
+    }
+
     pub async fn select<T>(self: &Arc<Terminal>, prompt: &str, list: &[T]) -> Result<Option<T>>
     where
         T: std::fmt::Display + Clone, // + IdT + Clone + Send + Sync + 'static,
@@ -2,6 +2,7 @@
 //! Terminal creation options
 //!
 
+use crate::keymap::Keymap;
 use web_sys::Element;
 
 /// Indicates the target element to which the Terminal instance should be
@@ -32,6 +33,16 @@ pub struct Options {
     pub font_size: Option<f64>,
     /// Default scrollback limit (xterm.js only)
     pub scrollback: Option<u32>,
+    /// Command history file (a local storage key in the browser);
+    /// the history is not persisted if not set
+    pub history_file: Option<String>,
+    /// Maximum number of command history entries
+    pub history_size: Option<usize>,
+    /// Key bindings (the default [`Keymap`] if not set)
+    pub keymap: Option<Keymap>,
+    /// Key bindings configuration file overriding
+    /// the keymap bindings (see [`Keymap::configure`])
+    pub bindings_file: Option<String>,
 }
 
 impl Default for Options {
@@ -43,6 +54,10 @@ impl Default for Options {
             font_family: None,
             font_size: None,
             scrollback: Some(2048),
+            history_file: None,
+            history_size: None,
+            keymap: None,
+            bindings_file: None,
         }
     }
 }
@@ -65,6 +80,30 @@ impl Options {
         self
     }
 
+    /// Set command history file (supports `~/` home folder prefix)
+    pub fn with_history_file(mut self, filename: &str) -> Self {
+        self.history_file = Some(filename.into());
+        self
+    }
+
+    /// Set maximum number of command history entries
+    pub fn with_history_size(mut self, history_size: usize) -> Self {
+        self.history_size = Some(history_size);
+        self
+    }
+
+    /// Set key bindings
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Set key bindings configuration file (supports `~/` home folder prefix)
+    pub fn with_bindings_file(mut self, filename: &str) -> Self {
+        self.bindings_file = Some(filename.into());
+        self
+    }
+
     /// Set target element
     pub fn with_element(mut self, element: TargetElement) -> Self {
         self.element = element;
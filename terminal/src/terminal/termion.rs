@@ -69,6 +69,8 @@ impl Termion {
                 K::Char(c) => {
                     if c == '\n' || c == '\r' {
                         Key::Enter
+                    } else if c == '\t' {
+                        Key::Tab
                     } else {
                         Key::Char(c)
                     }
@@ -81,6 +83,13 @@ impl Termion {
                 K::Up => Key::ArrowUp,
                 K::Down => Key::ArrowDown,
                 K::Backspace => Key::Backspace,
+                K::BackTab => Key::BackTab,
+                K::Home => Key::Home,
+                K::End => Key::End,
+                K::Delete => Key::Delete,
+                K::Insert => Key::Insert,
+                K::PageUp => Key::PageUp,
+                K::PageDown => Key::PageDown,
                 _ => {
                     continue;
                 }
@@ -108,16 +108,67 @@ pub struct SinkEvent {
     ctrl_key: bool,
     alt_key: bool,
     meta_key: bool,
+    shift_key: bool,
 }
 
 impl SinkEvent {
-    fn new(key: String, term_key: String, ctrl_key: bool, alt_key: bool, meta_key: bool) -> Self {
+    /// Key (or `None` if the event does not produce a key)
+    fn to_key(&self) -> Option<Key> {
+        let key = match self.key.as_str() {
+            "Backspace" => Key::Backspace,
+            "ArrowUp" => Key::ArrowUp,
+            "ArrowDown" => Key::ArrowDown,
+            "ArrowLeft" => Key::ArrowLeft,
+            "ArrowRight" => Key::ArrowRight,
+            "Escape" => Key::Esc,
+            "Delete" => Key::Delete,
+            "Home" => Key::Home,
+            "End" => Key::End,
+            "Insert" => Key::Insert,
+            "PageUp" => Key::PageUp,
+            "PageDown" => Key::PageDown,
+            "Tab" => {
+                if self.shift_key {
+                    Key::BackTab
+                } else {
+                    Key::Tab
+                }
+            }
+            "Enter" => Key::Enter,
+            _ => {
+                let printable = !self.meta_key; // ! (e.ctrl_key || e.alt_key || e.meta_key);
+                if !printable {
+                    return None;
+                }
+                //log_trace!("e:{:?}", e);
+                let c = self.key.chars().next()?;
+                if self.ctrl_key {
+                    Key::Ctrl(c)
+                } else if self.alt_key {
+                    Key::Alt(c)
+                } else {
+                    Key::Char(c)
+                }
+            }
+        };
+        Some(key)
+    }
+
+    fn new(
+        key: String,
+        term_key: String,
+        ctrl_key: bool,
+        alt_key: bool,
+        meta_key: bool,
+        shift_key: bool,
+    ) -> Self {
         Self {
             key,
             term_key,
             ctrl_key,
             alt_key,
             meta_key,
+            shift_key,
         }
     }
 }
@@ -481,10 +532,23 @@ impl Xterm {
             //let term_key = try_get_string(&e, "key")?;
             let term_key = e.get_key();
             let dom_event = e.get_dom_event();
-            let key = dom_event.key();
             let ctrl_key = dom_event.ctrl_key();
             let alt_key = dom_event.alt_key();
             let meta_key = dom_event.meta_key();
+            let shift_key = dom_event.shift_key();
+            let mut key = dom_event.key();
+
+            // on macOS the Option key composes characters (e.g. `∫` for Alt-B),
+            // as such the key is derived from the physical key code
+            if alt_key && !ctrl_key && runtime::is_macos() {
+                if let Some(letter) = dom_event.code().strip_prefix("Key") {
+                    key = if shift_key {
+                        letter.to_uppercase()
+                    } else {
+                        letter.to_lowercase()
+                    };
+                }
+            }
 
             // log_info!("key: {key}, ctrl: {ctrl_key}, alt: {alt_key}, meta: {meta_key}");
 
@@ -505,12 +569,18 @@ impl Xterm {
                 }
             }
 
-            this.sink
-                .sender
-                .try_send(Ctl::SinkEvent(SinkEvent::new(
-                    key, term_key, ctrl_key, alt_key, meta_key,
-                )))
-                .unwrap();
+            let event = SinkEvent::new(key, term_key, ctrl_key, alt_key, meta_key, shift_key);
+
+            // prevent the browser default action (e.g. Ctrl-L focusing the address bar)
+            // of bound chords; chords reserved by the browser (such as Ctrl-W) can not
+            // be intercepted
+            if let Some(key @ (Key::Ctrl(_) | Key::Alt(_))) = event.to_key() {
+                if this.terminal().binding(key).is_some() {
+                    dom_event.prevent_default();
+                }
+            }
+
+            this.sink.sender.try_send(Ctl::SinkEvent(event)).unwrap();
 
             Ok(())
         });
@@ -593,40 +663,9 @@ impl Xterm {
     }
 
     async fn sink(&self, e: SinkEvent) -> Result<()> {
-        let key = match e.key.as_str() {
-            "Backspace" => Key::Backspace,
-            "ArrowUp" => Key::ArrowUp,
-            "ArrowDown" => Key::ArrowDown,
-            "ArrowLeft" => Key::ArrowLeft,
-            "ArrowRight" => Key::ArrowRight,
-            "Escape" => Key::Esc,
-            "Delete" => Key::Delete,
-            "Tab" => {
-                // TODO implement completion handler
-                return Ok(());
-            }
-            "Enter" => Key::Enter,
-            _ => {
-                let printable = !e.meta_key; // ! (e.ctrl_key || e.alt_key || e.meta_key);
-                if !printable {
-                    return Ok(());
-                }
-                //log_trace!("e:{:?}", e);
-                if let Some(c) = e.key.chars().next() {
-                    if e.ctrl_key {
-                        Key::Ctrl(c)
-                    } else if e.alt_key {
-                        Key::Alt(c)
-                    } else {
-                        Key::Char(c)
-                    }
-                } else {
-                    return Ok(());
-                }
-            }
-        };
-
-        self.terminal().ingest(key, e.term_key).await?;
+        if let Some(key) = e.to_key() {
+            self.terminal().ingest(key, e.term_key).await?;
+        }
 
         Ok(())
     }